@@ -0,0 +1,102 @@
+//! Compares the cost of `BoardLogic::same` with the former FEN based comparison,
+//! over the positions of a long game replay.
+//!
+//! Run with `cargo bench --bench board_logic`.
+
+#[path = "../src/chess/board_logic.rs"]
+#[allow(dead_code)]
+mod board_logic;
+// Modules of the chess folder used by the board, found by `super` from it.
+#[path = "../src/chess/antichess.rs"]
+#[allow(dead_code)]
+mod antichess;
+#[path = "../src/chess/chess960.rs"]
+#[allow(dead_code)]
+mod chess960;
+#[path = "../src/chess/variant.rs"]
+#[allow(dead_code)]
+mod variant;
+
+use board_logic::BoardLogic;
+use druid::Data;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const REPLAY_PLIES: usize = 300;
+const COMPARISONS_PER_POSITION: usize = 200;
+
+/// Plays a deterministic pseudo random game, keeping every position reached.
+fn replay_long_game() -> Vec<BoardLogic> {
+    let mut logic = BoardLogic::new();
+    let mut positions = vec![logic.clone()];
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+
+    while positions.len() <= REPLAY_PLIES {
+        let moves = logic.legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let chosen_move = moves[(seed % (moves.len() as u64)) as usize];
+        logic.play_move(chosen_move);
+        positions.push(logic.clone());
+    }
+    positions
+}
+
+fn fen_same(first: &BoardLogic, second: &BoardLogic) -> bool {
+    first.fen() == second.fen()
+}
+
+fn measure(
+    positions: &[BoardLogic],
+    compare: impl Fn(&BoardLogic, &BoardLogic) -> bool,
+) -> (Duration, usize) {
+    // Druid mostly compares a position with an unchanged copy of itself.
+    let copies = positions.to_vec();
+    let mut same_count = 0;
+    let start = Instant::now();
+    for index in 1..positions.len() {
+        for _ in 0..COMPARISONS_PER_POSITION {
+            if compare(black_box(&positions[index]), black_box(&copies[index])) {
+                same_count += 1;
+            }
+            if compare(black_box(&positions[index - 1]), black_box(&positions[index])) {
+                same_count += 1;
+            }
+        }
+    }
+    (start.elapsed(), same_count)
+}
+
+fn main() {
+    let positions = replay_long_game();
+    let comparisons = (positions.len() - 1) * COMPARISONS_PER_POSITION * 2;
+
+    let (fen_duration, fen_same_count) = measure(&positions, fen_same);
+    let (data_duration, data_same_count) = measure(&positions, |first, second| first.same(second));
+    assert_eq!(fen_same_count, data_same_count);
+
+    println!(
+        "{} plies replayed, {} comparisons each",
+        positions.len() - 1,
+        comparisons
+    );
+    println!(
+        "fen comparison     : {:?} ({:.1} ns/comparison)",
+        fen_duration,
+        fen_duration.as_nanos() as f64 / comparisons as f64
+    );
+    println!(
+        "BoardLogic::same   : {:?} ({:.1} ns/comparison)",
+        data_duration,
+        data_duration.as_nanos() as f64 / comparisons as f64
+    );
+    println!(
+        "speedup            : {:.1}x",
+        fen_duration.as_secs_f64() / data_duration.as_secs_f64()
+    );
+}
@@ -0,0 +1,361 @@
+-app-name = Chess exercises organizer
+
+# The 'application' menu on macOS
+macos-menu-about-app = About { -app-name }
+macos-menu-preferences = Preferences...
+macos-menu-hide-app = Hide { -app-name }
+macos-menu-hide-others = Hide Others
+macos-menu-show-all = Show All
+macos-menu-services = Services
+macos-menu-application-menu = { -app-name }
+macos-menu-quit-app = Quit { -app-name }
+
+# common 'File' menu items
+common-menu-file-menu = File
+common-menu-file-new = New
+common-menu-file-new-window = New Window
+
+common-menu-file-open = Open...
+common-menu-file-close = Close
+
+common-menu-file-save = Save
+# used for new files, if we need to show a dialog
+common-menu-file-save-ellipsis = Save...
+common-menu-file-save-as = Save As...
+
+common-menu-file-page-setup = Page Setup...
+common-menu-file-print = Print...
+
+# windows 'File' menu items
+win-menu-file-exit = Exit
+
+# common 'Edit' menu items.
+common-menu-edit-menu = Edit
+
+common-menu-cut = Cut
+common-menu-copy = Copy
+common-menu-paste = Paste
+common-menu-undo = Undo
+common-menu-redo = Redo
+
+# Chess exercises organizer
+
+window-title = { -app-name }
+settings-window-title = Settings
+settings-button = Settings
+
+settings-piece-set = Piece set
+settings-board-theme = Board theme
+color-background = Background
+color-light-cells = Light cells
+color-dark-cells = Dark cells
+color-start-cell = Drag start cell
+color-end-cell = Drag end cell
+color-coordinates = Coordinates
+color-cursor = Keyboard cursor
+
+settings-coordinates = Coordinates
+coordinates-all-sides = Around the board
+coordinates-two-sides = Below and left of the board
+coordinates-inside-squares = Inside the squares
+coordinates-hidden = Hidden
+coordinates-uppercase = Uppercase letters
+coordinates-font-size = Font size
+settings-border-width = Border width
+settings-engine = UCI engine program
+settings-engine-hint = Leave empty to analyse with the built-in engine.
+settings-book = Polyglot opening book (.bin)
+settings-book-hint = Leave empty to play and show no book moves.
+settings-tablebases = Syzygy tablebases directory
+settings-tablebases-hint = Leave empty to play endgames with the engine.
+
+board-theme-classic = Classic
+board-theme-ocean = Ocean
+board-theme-forest = Forest
+board-theme-high-contrast = High contrast
+board-theme-colour-blind-friendly = Colour-blind friendly
+
+# Written around the board, from the a file to the h file.
+board-file-letters = ABCDEFGH
+
+# Letters of the king, queen, rook, bishop and knight, in this order,
+# used to write and type moves.
+san-piece-letters = KQRBN
+
+move-entry-placeholder = Type a move (Nf3, e2e4)
+move-error-empty = Please type a move.
+move-error-invalid-syntax = '{ $input }' is not a valid move notation.
+move-error-illegal = '{ $input }' is not a legal move in this position.
+move-error-ambiguous = '{ $input }' is ambiguous : it could be { $candidates }.
+move-error-missing-promotion = '{ $input }' needs a promotion piece, for example { $example }.
+
+position-panel-last-announcement = Last announcement
+position-panel-position = Position
+position-panel-describe = Describe position
+
+list-and = { $head } and { $last }
+list-or = { $head } or { $last }
+
+side-white = White
+side-black = Black
+
+piece-king = king
+piece-queen = queen
+piece-rook = rook
+piece-bishop = bishop
+piece-knight = knight
+piece-pawn = pawn
+
+piece-white-king = white king
+piece-white-queen = white queen
+piece-white-rook = white rook
+piece-white-bishop = white bishop
+piece-white-knight = white knight
+piece-white-pawn = white pawn
+piece-black-king = black king
+piece-black-queen = black queen
+piece-black-rook = black rook
+piece-black-bishop = black bishop
+piece-black-knight = black knight
+piece-black-pawn = black pawn
+
+position-side = { $side } : { $pieces }.
+pieces-kings = { $count ->
+        [one] King
+       *[other] Kings
+    } on { $squares }
+pieces-queens = { $count ->
+        [one] Queen
+       *[other] Queens
+    } on { $squares }
+pieces-rooks = { $count ->
+        [one] Rook
+       *[other] Rooks
+    } on { $squares }
+pieces-bishops = { $count ->
+        [one] Bishop
+       *[other] Bishops
+    } on { $squares }
+pieces-knights = { $count ->
+        [one] Knight
+       *[other] Knights
+    } on { $squares }
+pieces-pawns = { $count ->
+        [one] Pawn
+       *[other] Pawns
+    } on { $squares }
+position-to-move = { $side } to move.
+position-in-check = { $side } to move, in check.
+position-checkmated = { $side } is checkmated.
+position-stalemated = { $side } to move, stalemate.
+position-king-on-hill = { $side } wins, their king reached the hill.
+position-third-check = { $side } wins with a third check.
+position-no-move-left = { $side } to move has no move left, and wins.
+position-checks = Checks given: { $white } by White, { $black } by Black.
+
+announce-quiet-move = { $piece } from { $origin } to { $destination }
+announce-capture = { $piece } on { $origin } takes { $captured } on { $square }
+announce-en-passant = { $piece } on { $origin } takes { $captured } on { $square } en passant, landing on { $destination }
+announce-king-side-castle = { $side } castles king side
+announce-queen-side-castle = { $side } castles queen side
+announce-promotion = { $move }, promotes to { $piece }
+announce-end = { $move }.
+announce-check = { $move }, check.
+announce-checkmate = { $move }, checkmate.
+announce-stalemate = { $move }, stalemate.
+announce-king-on-hill = { $move }, the king reaches the hill, { $side } wins.
+announce-third-check = { $move }, third check, { $side } wins.
+announce-no-move-left = { $move }, { $side } has no move left and wins.
+
+new-game-panel-title = New game
+new-game-standard = Standard game
+new-game-standard-started = New game from the standard start position.
+new-game-chess960-number = Chess960 start position, from 0 to 959 (random if empty):
+new-game-chess960 = Chess960 game
+new-game-chess960-started = New Chess960 game from the start position number { $number }.
+new-game-chess960-invalid = { $number } is not a Chess960 start position: type a number from 0 to 959.
+new-game-king-of-the-hill = King of the Hill game
+new-game-king-of-the-hill-started = New game of King of the Hill: the first king reaching d4, e4, d5 or e5 wins.
+new-game-three-check = Three-check game
+new-game-three-check-started = New game of Three-check: the first player giving three checks wins.
+new-game-antichess = Antichess game
+new-game-antichess-started = New game of Antichess: captures are compulsory, and the first player losing all their pieces or having no move left wins.
+variant-unknown-to-engines = The engines, opening books and tablebases do not play the rules of this game.
+
+clock-panel-title = Clocks
+clock-time = { $side }: { $time }
+clock-time-running = { $side }: { $time } (running)
+clock-time-flagged = { $side }: { $time } (flag fallen)
+clock-start = Start
+clock-pause = Pause
+clock-set = Set the clocks
+clock-export-pgn = Export PGN
+clock-preset-none = No clock
+clock-preset-blitz = 5 minutes
+clock-preset-blitz-increment = 3 minutes + 2 seconds
+clock-preset-rapid-increment = 15 minutes + 10 seconds
+clock-preset-delay = 5 minutes, 3 seconds delay
+clock-preset-classical = 90 minutes for 40 moves, then 30 minutes, + 30 seconds
+clock-flag-fall = { $side } lost on time.
+clock-move-refused = { $side } cannot move anymore, their time ran out.
+pgn-export-done = Game saved to { $path }.
+pgn-export-failed = Could not save the game to { $path }: { $error }
+
+rush-panel-title = Puzzle rush
+rush-no-collection = Add PGN files of exercises to the collections folder of the configuration directory to play a rush.
+rush-minutes = { $minutes } minutes
+rush-start = Start the rush
+rush-stop = Stop the rush
+rush-empty-collection = This collection has no valid exercise.
+rush-load-failed = Could not load the collection: { $error }
+rush-time-left = Time left: { $time }
+rush-progress = Exercise { $number } of { $total }
+rush-score = Score: { $score }, strikes: { $strikes } of { $allowed }
+rush-over = The rush is over.
+rush-exercise = Exercise { $number } of { $total }: { $side } to play.
+rush-solved = Solved!
+rush-strike = Wrong move.
+rush-judging = The engine judges the move…
+rush-no-scores = No score yet for this collection.
+rush-best-scores = Best scores:
+rush-score-line = { $rank }. { $score } ({ $date })
+rush-end = Rush over, { $score } { $score ->
+    [one] exercise
+   *[other] exercises
+} solved. Your best is { $best }.
+rush-end-new-best = Rush over, { $score } { $score ->
+    [one] exercise
+   *[other] exercises
+} solved: new personal best!
+rush-peeks = { $peeks ->
+    [one] One peek
+   *[other] { $peeks } peeks
+}
+rush-acceptance = Also accept, for this collection:
+rush-accept-variations = Moves of the variations of the solution
+rush-accept-mates = Mates as quick as the solution
+rush-accept-tolerance = Moves the engine scores below the solution by at most:
+rush-tolerance-off = Not accepted
+rush-tolerance = { $pawns } pawn
+
+repertoire-panel-title = Opening repertoire
+repertoire-open = Open a repertoire…
+repertoire-none = No repertoire opened.
+repertoire-load-failed = Could not read the repertoire: { $error }
+repertoire-white = I play White
+repertoire-black = I play Black
+repertoire-opponent = The opponent plays:
+repertoire-weighted = The lines of the file, the most prepared more often
+repertoire-random = Any line of the file alike
+repertoire-start = Start the drill
+repertoire-stop = Stop the drill
+repertoire-new-line = New line.
+repertoire-line-done = End of the line.
+repertoire-nothing-to-drill = The repertoire has no move for this side.
+repertoire-wrong = Not the repertoire move. Expected: { $moves }.
+repertoire-due = { $due } of { $total } { $total ->
+    [one] position
+   *[other] positions
+} due for review
+repertoire-answers = Found at the first try: { $correct }, missed: { $mistakes }
+
+endgame-panel-title = Endgame drills
+endgame-open = Open endgame exercises…
+endgame-none = No endgame exercises opened.
+endgame-load-failed = Could not read the endgame exercises: { $error }
+endgame-start = Start the drill
+endgame-stop = Stop the drill
+endgame-retry = Try again
+endgame-next = Next exercise
+endgame-exercise = Exercise { $number } of { $total }: { $title }.
+endgame-goal-win = { $side } to play and win within { $moves } { $moves ->
+    [one] move.
+   *[other] moves.
+}
+endgame-goal-draw = { $side } to play and hold the draw for { $moves } { $moves ->
+    [one] move.
+   *[other] moves.
+}
+endgame-goal-mate = { $side } to play and mate in { $moves } { $moves ->
+    [one] move.
+   *[other] moves.
+}
+endgame-moves-left = { $moves } { $moves ->
+    [one] move
+   *[other] moves
+} left
+endgame-score = Goals reached: { $reached } of { $attempts }
+endgame-wait = Wait for the move of the computer.
+endgame-attempt-over = The attempt is over: try again or go to the next exercise.
+endgame-reached = Goal reached!
+endgame-lost = Checkmated: the goal is missed.
+endgame-out-of-moves = No moves left: the goal is missed.
+endgame-drawn = Draw by { $reason }: the goal is missed.
+endgame-draw-stalemate = stalemate
+endgame-draw-insufficient = insufficient material
+endgame-draw-fifty-moves = the fifty-move rule
+endgame-draw-repetition = repetition
+
+visibility-panel-title = Pieces shown
+visibility-all = All the pieces
+visibility-none = None (blindfold)
+visibility-hide-white = Black pieces only
+visibility-hide-black = White pieces only
+visibility-hide-moved = Pieces which did not move
+visibility-peek-hint = Hold P on the board to peek at the hidden pieces.
+position-hidden = The position is hidden.
+
+computer-panel-title = Computer opponent
+computer-nobody = Nobody
+computer-white = The computer plays White
+computer-black = The computer plays Black
+computer-failed = The computer could not play: { $error }
+
+book-panel-title = Opening book
+book-none = No opening book is chosen in the settings.
+book-error = Could not read the opening book: { $error }
+book-no-move = No book move in this position.
+book-move = { $move } ({ $share }%)
+
+tablebase-panel-title = Tablebases
+tablebase-none = No tablebase directory is chosen in the settings.
+tablebase-error = Could not read the tablebases: { $error }
+tablebase-not-covered = The position is not in the tablebases.
+tablebase-missing = The table { $table } is missing.
+tablebase-win = { $side } wins.
+tablebase-cursed-win = { $side } wins, but the fifty-move rule makes it a draw.
+tablebase-draw = Draw.
+tablebase-dtz = { $plies ->
+    [one] { $plies } ply before the next capture or pawn move.
+   *[other] { $plies } plies before the next capture or pawn move.
+}
+
+analysis-enabled = Analyse the position
+analysis-lines = { $count ->
+    [one] { $count } line
+   *[other] { $count } lines
+}
+analysis-waiting = The engine is thinking…
+analysis-depth = Depth { $depth }, { $nodes } nodes
+analysis-error = The engine failed: { $error }
+review-panel-title = Game review
+review-game = Review the game
+review-progress = Positions evaluated: { $done } of { $total }
+review-done = Game reviewed. Inaccuracies: { $inaccuracies }, mistakes: { $mistakes }, blunders: { $blunders }. The annotated game was written to { $path }.
+review-failed = The review failed: { $error }
+review-player = Player whose blunders become exercises (all players if empty):
+review-blunders = Exercises from the blunders of games…
+review-games-progress = Games reviewed: { $done } of { $total }
+review-blunders-done = { $games } { $games ->
+    [one] game reviewed
+   *[other] games reviewed
+}, { $exercises } { $exercises ->
+    [one] exercise added
+   *[other] exercises added
+} to the collection { $collection }. It can be chosen for a rush once the application is restarted.
+review-verify = Verify the solutions of exercises…
+review-exercises-progress = Exercises verified: { $done } of { $total }
+review-verify-done = { $exercises } { $exercises ->
+    [one] exercise verified
+   *[other] exercises verified
+}: the key move is not the best in { $not_best }, and other moves win in { $multiple }. The findings were written in the Verification tags of { $path }.
@@ -0,0 +1,361 @@
+-app-name = Organiseur d'exercices d'échecs
+
+# The 'application' menu on macOS
+macos-menu-about-app = À propos de { -app-name }
+macos-menu-preferences = Préférences...
+macos-menu-hide-app = Masquer { -app-name }
+macos-menu-hide-others = Masquer les autres
+macos-menu-show-all = Tout afficher
+macos-menu-services = Services
+macos-menu-application-menu = { -app-name }
+macos-menu-quit-app = Quitter { -app-name }
+
+# common 'file' menu items
+common-menu-file-menu = Fichier
+common-menu-file-new = Nouveau
+common-menu-file-new-window = Nouvelle fenêtre
+
+common-menu-file-open = Ouvrir...
+common-menu-file-close = Fermer
+
+common-menu-file-save = Enregistrer
+# used for new files, if we need to show a dialog
+common-menu-file-save-ellipsis = Enregistrer...
+common-menu-file-save-as = Enregistrer sous...
+
+common-menu-file-page-setup = Format d'impression...
+common-menu-file-print = Imprimer...
+
+# windows 'File' menu items
+win-menu-file-exit = Quitter
+
+# common 'Edit' menu items.
+common-menu-edit-menu = Édition
+
+common-menu-cut = Couper
+common-menu-copy = Copier
+common-menu-paste = Coller
+common-menu-undo = Annuler
+common-menu-redo = Rétablir
+
+# Chess exercises organizer
+
+window-title = { -app-name }
+settings-window-title = Réglages
+settings-button = Réglages
+
+settings-piece-set = Jeu de pièces
+settings-board-theme = Thème de l'échiquier
+color-background = Fond
+color-light-cells = Cases claires
+color-dark-cells = Cases foncées
+color-start-cell = Case de départ
+color-end-cell = Case d'arrivée
+color-coordinates = Coordonnées
+color-cursor = Curseur du clavier
+
+settings-coordinates = Coordonnées
+coordinates-all-sides = Autour de l'échiquier
+coordinates-two-sides = En bas et à gauche de l'échiquier
+coordinates-inside-squares = Dans les cases
+coordinates-hidden = Masquées
+coordinates-uppercase = Lettres majuscules
+coordinates-font-size = Taille du texte
+settings-border-width = Largeur du bord
+settings-engine = Programme du moteur UCI
+settings-engine-hint = Laissez vide pour analyser avec le moteur intégré.
+settings-book = Livre d'ouvertures Polyglot (.bin)
+settings-book-hint = Laissez vide pour ne jouer ni afficher aucun coup de livre.
+settings-tablebases = Dossier des tables de finales Syzygy
+settings-tablebases-hint = Laissez vide pour jouer les finales avec le moteur.
+
+board-theme-classic = Classique
+board-theme-ocean = Océan
+board-theme-forest = Forêt
+board-theme-high-contrast = Contraste élevé
+board-theme-colour-blind-friendly = Adapté aux daltoniens
+
+# Written around the board, from the a file to the h file.
+board-file-letters = ABCDEFGH
+
+# Letters of the king, queen, rook, bishop and knight, in this order,
+# used to write and type moves.
+san-piece-letters = RDTFC
+
+move-entry-placeholder = Tapez un coup (Cf3, e2e4)
+move-error-empty = Veuillez taper un coup.
+move-error-invalid-syntax = « { $input } » n'est pas une notation de coup valide.
+move-error-illegal = « { $input } » n'est pas un coup légal dans cette position.
+move-error-ambiguous = « { $input } » est ambigu : ce peut être { $candidates }.
+move-error-missing-promotion = « { $input } » doit préciser la pièce de promotion, par exemple { $example }.
+
+position-panel-last-announcement = Dernière annonce
+position-panel-position = Position
+position-panel-describe = Décrire la position
+
+list-and = { $head } et { $last }
+list-or = { $head } ou { $last }
+
+side-white = Blancs
+side-black = Noirs
+
+piece-king = roi
+piece-queen = dame
+piece-rook = tour
+piece-bishop = fou
+piece-knight = cavalier
+piece-pawn = pion
+
+piece-white-king = roi blanc
+piece-white-queen = dame blanche
+piece-white-rook = tour blanche
+piece-white-bishop = fou blanc
+piece-white-knight = cavalier blanc
+piece-white-pawn = pion blanc
+piece-black-king = roi noir
+piece-black-queen = dame noire
+piece-black-rook = tour noire
+piece-black-bishop = fou noir
+piece-black-knight = cavalier noir
+piece-black-pawn = pion noir
+
+position-side = { $side } : { $pieces }.
+pieces-kings = { $count ->
+        [one] Roi
+       *[other] Rois
+    } en { $squares }
+pieces-queens = { $count ->
+        [one] Dame
+       *[other] Dames
+    } en { $squares }
+pieces-rooks = { $count ->
+        [one] Tour
+       *[other] Tours
+    } en { $squares }
+pieces-bishops = { $count ->
+        [one] Fou
+       *[other] Fous
+    } en { $squares }
+pieces-knights = { $count ->
+        [one] Cavalier
+       *[other] Cavaliers
+    } en { $squares }
+pieces-pawns = { $count ->
+        [one] Pion
+       *[other] Pions
+    } en { $squares }
+position-to-move = Trait aux { $side }.
+position-in-check = Trait aux { $side }, en échec.
+position-checkmated = Les { $side } sont mats.
+position-stalemated = Trait aux { $side }, pat.
+position-king-on-hill = Les { $side } gagnent, leur roi a atteint la colline.
+position-third-check = Les { $side } gagnent par un troisième échec.
+position-no-move-left = Trait aux { $side }, qui n'ont plus de coup et gagnent.
+position-checks = Échecs donnés : { $white } par les Blancs, { $black } par les Noirs.
+
+announce-quiet-move = { $piece } de { $origin } à { $destination }
+announce-capture = { $piece } en { $origin } prend { $captured } en { $square }
+announce-en-passant = { $piece } en { $origin } prend { $captured } en { $square } en passant, arrivant en { $destination }
+announce-king-side-castle = Les { $side } roquent côté roi
+announce-queen-side-castle = Les { $side } roquent côté dame
+announce-promotion = { $move }, promotion en { $piece }
+announce-end = { $move }.
+announce-check = { $move }, échec.
+announce-checkmate = { $move }, échec et mat.
+announce-stalemate = { $move }, pat.
+announce-king-on-hill = { $move }, le roi atteint la colline, les { $side } gagnent.
+announce-third-check = { $move }, troisième échec, les { $side } gagnent.
+announce-no-move-left = { $move }, les { $side } n'ont plus de coup et gagnent.
+
+new-game-panel-title = Nouvelle partie
+new-game-standard = Partie classique
+new-game-standard-started = Nouvelle partie depuis la position de départ classique.
+new-game-chess960-number = Position de départ Chess960, de 0 à 959 (au hasard si vide) :
+new-game-chess960 = Partie Chess960
+new-game-chess960-started = Nouvelle partie Chess960 depuis la position de départ numéro { $number }.
+new-game-chess960-invalid = { $number } n'est pas une position de départ Chess960 : tapez un nombre de 0 à 959.
+new-game-king-of-the-hill = Partie King of the Hill
+new-game-king-of-the-hill-started = Nouvelle partie de King of the Hill : le premier roi atteignant d4, e4, d5 ou e5 gagne.
+new-game-three-check = Partie Three-check
+new-game-three-check-started = Nouvelle partie de Three-check : le premier joueur donnant trois échecs gagne.
+new-game-antichess = Partie Antichess
+new-game-antichess-started = Nouvelle partie d'Antichess : les prises sont obligatoires, et le premier joueur perdant toutes ses pièces ou n'ayant plus de coup gagne.
+variant-unknown-to-engines = Les moteurs, livres d'ouvertures et tables de finales ne jouent pas les règles de cette partie.
+
+clock-panel-title = Pendules
+clock-time = { $side } : { $time }
+clock-time-running = { $side } : { $time } (en marche)
+clock-time-flagged = { $side } : { $time } (drapeau tombé)
+clock-start = Démarrer
+clock-pause = Pause
+clock-set = Régler les pendules
+clock-export-pgn = Exporter en PGN
+clock-preset-none = Pas de pendule
+clock-preset-blitz = 5 minutes
+clock-preset-blitz-increment = 3 minutes + 2 secondes
+clock-preset-rapid-increment = 15 minutes + 10 secondes
+clock-preset-delay = 5 minutes, délai de 3 secondes
+clock-preset-classical = 90 minutes pour 40 coups, puis 30 minutes, + 30 secondes
+clock-flag-fall = Les { $side } ont perdu au temps.
+clock-move-refused = Les { $side } ne peuvent plus jouer, leur temps est écoulé.
+pgn-export-done = Partie enregistrée dans { $path }.
+pgn-export-failed = Impossible d'enregistrer la partie dans { $path } : { $error }
+
+rush-panel-title = Course d'exercices
+rush-no-collection = Ajoutez des fichiers PGN d'exercices dans le dossier collections du répertoire de configuration pour lancer une course.
+rush-minutes = { $minutes } minutes
+rush-start = Lancer la course
+rush-stop = Arrêter la course
+rush-empty-collection = Cette collection n'a aucun exercice valide.
+rush-load-failed = Impossible de charger la collection : { $error }
+rush-time-left = Temps restant : { $time }
+rush-progress = Exercice { $number } sur { $total }
+rush-score = Score : { $score }, erreurs : { $strikes } sur { $allowed }
+rush-over = La course est terminée.
+rush-exercise = Exercice { $number } sur { $total } : trait aux { $side }.
+rush-solved = Résolu !
+rush-strike = Mauvais coup.
+rush-judging = Le moteur juge le coup…
+rush-no-scores = Pas encore de score pour cette collection.
+rush-best-scores = Meilleurs scores :
+rush-score-line = { $rank }. { $score } ({ $date })
+rush-end = Course terminée, { $score } { $score ->
+    [one] exercice résolu
+   *[other] exercices résolus
+}. Votre record est de { $best }.
+rush-end-new-best = Course terminée, { $score } { $score ->
+    [one] exercice résolu
+   *[other] exercices résolus
+} : nouveau record personnel !
+rush-peeks = { $peeks ->
+    [one] { $peeks } coup d'œil
+   *[other] { $peeks } coups d'œil
+}
+rush-acceptance = Accepter aussi, pour cette collection :
+rush-accept-variations = Les coups des variantes de la solution
+rush-accept-mates = Les mats aussi rapides que la solution
+rush-accept-tolerance = Les coups que le moteur évalue en dessous de la solution d'au plus :
+rush-tolerance-off = Non acceptés
+rush-tolerance = { $pawns } pion
+
+repertoire-panel-title = Répertoire d'ouvertures
+repertoire-open = Ouvrir un répertoire…
+repertoire-none = Aucun répertoire ouvert.
+repertoire-load-failed = Impossible de lire le répertoire : { $error }
+repertoire-white = Je joue les Blancs
+repertoire-black = Je joue les Noirs
+repertoire-opponent = L'adversaire joue :
+repertoire-weighted = Les lignes du fichier, les plus préparées plus souvent
+repertoire-random = Toutes les lignes du fichier également
+repertoire-start = Commencer l'entraînement
+repertoire-stop = Arrêter l'entraînement
+repertoire-new-line = Nouvelle ligne.
+repertoire-line-done = Fin de la ligne.
+repertoire-nothing-to-drill = Le répertoire n'a aucun coup pour ce camp.
+repertoire-wrong = Ce n'est pas le coup du répertoire. Attendu : { $moves }.
+repertoire-due = { $due } { $due ->
+    [one] position
+   *[other] positions
+} à revoir sur { $total }
+repertoire-answers = Trouvées du premier coup : { $correct }, manquées : { $mistakes }
+
+endgame-panel-title = Finales
+endgame-open = Ouvrir des exercices de finale…
+endgame-none = Aucun exercice de finale ouvert.
+endgame-load-failed = Impossible de lire les exercices de finale : { $error }
+endgame-start = Commencer l'entraînement
+endgame-stop = Arrêter l'entraînement
+endgame-retry = Réessayer
+endgame-next = Exercice suivant
+endgame-exercise = Exercice { $number } sur { $total } : { $title }.
+endgame-goal-win = Trait aux { $side }, qui gagnent en { $moves } { $moves ->
+    [one] coup
+   *[other] coups
+} au plus.
+endgame-goal-draw = Trait aux { $side }, qui tiennent la nulle pendant { $moves } { $moves ->
+    [one] coup.
+   *[other] coups.
+}
+endgame-goal-mate = Trait aux { $side }, qui matent en { $moves } { $moves ->
+    [one] coup.
+   *[other] coups.
+}
+endgame-moves-left = { $moves ->
+    [one] { $moves } coup restant
+   *[other] { $moves } coups restants
+}
+endgame-score = Objectifs atteints : { $reached } sur { $attempts }
+endgame-wait = Attendez le coup de l'ordinateur.
+endgame-attempt-over = L'essai est terminé : réessayez ou passez à l'exercice suivant.
+endgame-reached = Objectif atteint !
+endgame-lost = Mat : l'objectif est manqué.
+endgame-out-of-moves = Plus de coups : l'objectif est manqué.
+endgame-drawn = Nulle par { $reason } : l'objectif est manqué.
+endgame-draw-stalemate = pat
+endgame-draw-insufficient = matériel insuffisant
+endgame-draw-fifty-moves = la règle des cinquante coups
+endgame-draw-repetition = répétition
+
+visibility-panel-title = Pièces affichées
+visibility-all = Toutes les pièces
+visibility-none = Aucune (à l'aveugle)
+visibility-hide-white = Pièces noires seulement
+visibility-hide-black = Pièces blanches seulement
+visibility-hide-moved = Pièces qui n'ont pas bougé
+visibility-peek-hint = Maintenez P sur l'échiquier pour voir les pièces cachées.
+position-hidden = La position est cachée.
+
+computer-panel-title = Adversaire ordinateur
+computer-nobody = Personne
+computer-white = L'ordinateur joue les Blancs
+computer-black = L'ordinateur joue les Noirs
+computer-failed = L'ordinateur n'a pas pu jouer : { $error }
+
+book-panel-title = Livre d'ouvertures
+book-none = Aucun livre d'ouvertures n'est choisi dans les réglages.
+book-error = Impossible de lire le livre d'ouvertures : { $error }
+book-no-move = Aucun coup du livre dans cette position.
+book-move = { $move } ({ $share } %)
+
+tablebase-panel-title = Tables de finales
+tablebase-none = Aucun dossier de tables de finales n'est choisi dans les réglages.
+tablebase-error = Impossible de lire les tables de finales : { $error }
+tablebase-not-covered = La position n'est pas dans les tables de finales.
+tablebase-missing = La table { $table } est absente.
+tablebase-win = Les { $side } gagnent.
+tablebase-cursed-win = Les { $side } gagnent, mais la règle des cinquante coups en fait une nulle.
+tablebase-draw = Nulle.
+tablebase-dtz = { $plies ->
+    [one] { $plies } demi-coup avant la prochaine prise ou le prochain coup de pion.
+   *[other] { $plies } demi-coups avant la prochaine prise ou le prochain coup de pion.
+}
+
+analysis-enabled = Analyser la position
+analysis-lines = { $count ->
+    [one] { $count } ligne
+   *[other] { $count } lignes
+}
+analysis-waiting = Le moteur réfléchit…
+analysis-depth = Profondeur { $depth }, { $nodes } nœuds
+analysis-error = Le moteur a échoué : { $error }
+review-panel-title = Analyse de la partie
+review-game = Analyser la partie
+review-progress = Positions évaluées : { $done } sur { $total }
+review-done = Partie analysée. Imprécisions : { $inaccuracies }, erreurs : { $mistakes }, gaffes : { $blunders }. La partie annotée a été écrite dans { $path }.
+review-failed = L'analyse a échoué : { $error }
+review-player = Joueur dont les gaffes deviennent des exercices (tous si vide) :
+review-blunders = Exercices tirés des gaffes de parties…
+review-games-progress = Parties analysées : { $done } sur { $total }
+review-blunders-done = { $games } { $games ->
+    [one] partie analysée
+   *[other] parties analysées
+}, { $exercises } { $exercises ->
+    [one] exercice ajouté
+   *[other] exercices ajoutés
+} à la collection { $collection }. Elle pourra être choisie pour un rush au prochain démarrage de l'application.
+review-verify = Vérifier les solutions d'exercices…
+review-exercises-progress = Exercices vérifiés : { $done } sur { $total }
+review-verify-done = { $exercises } { $exercises ->
+    [one] exercice vérifié
+   *[other] exercices vérifiés
+} : le coup clé n'est pas le meilleur dans { $not_best }, et d'autres coups gagnent dans { $multiple }. Les résultats ont été écrits dans les tags Verification de { $path }.
@@ -0,0 +1,20 @@
+mod board_logic;
+pub use board_logic::BoardLogic;
+
+pub mod annotation;
+pub mod antichess;
+pub mod blunders;
+pub mod chess960;
+pub mod clock;
+pub mod description;
+pub mod endgame;
+pub mod engine;
+pub mod exercise;
+pub mod pgn;
+pub mod polyglot;
+pub mod repertoire;
+pub mod rush;
+pub mod san;
+pub mod syzygy;
+pub mod variant;
+pub mod verification;
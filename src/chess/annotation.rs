@@ -0,0 +1,416 @@
+use pleco::{BitMove, Player};
+
+use super::engine::{analyse_position, EngineKind, Score, SearchLimit};
+use super::pgn::{PgnGame, PgnMove};
+use super::san::{move_to_san, PieceLetters};
+use super::BoardLogic;
+
+/// Losses of winning chances, on a scale from -1 to 1, from which a move is an inaccuracy,
+/// a mistake or a blunder, as on lichess.
+const INACCURACY_LOSS: f64 = 0.1;
+const MISTAKE_LOSS: f64 = 0.2;
+const BLUNDER_LOSS: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveQuality {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveQuality {
+    fn from_loss(loss: f64) -> Option<Self> {
+        if loss >= BLUNDER_LOSS {
+            Some(MoveQuality::Blunder)
+        } else if loss >= MISTAKE_LOSS {
+            Some(MoveQuality::Mistake)
+        } else if loss >= INACCURACY_LOSS {
+            Some(MoveQuality::Inaccuracy)
+        } else {
+            None
+        }
+    }
+
+    /// Numeric Annotation Glyph of the quality : `?!`, `?` or `??`.
+    pub fn nag(self) -> u8 {
+        match self {
+            MoveQuality::Inaccuracy => 6,
+            MoveQuality::Mistake => 2,
+            MoveQuality::Blunder => 4,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MoveQuality::Inaccuracy => "Inaccuracy",
+            MoveQuality::Mistake => "Mistake",
+            MoveQuality::Blunder => "Blunder",
+        }
+    }
+}
+
+/// Evaluation of a position of a reviewed game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionEval {
+    /// Winning chances of White, from -1 when Black wins to 1 when White wins.
+    pub white_chances: f64,
+    /// Score given by the engine, none when the game is over.
+    pub score: Option<Score>,
+    /// The best line from the position, empty when the game is over.
+    pub best_line: Vec<BitMove>,
+}
+
+impl PositionEval {
+    fn chances_of(&self, player: Player) -> f64 {
+        match player {
+            Player::White => self.white_chances,
+            Player::Black => -self.white_chances,
+        }
+    }
+}
+
+/// Winning chances of White for a score, following the logistic curve of lichess.
+fn winning_chances(score: Score) -> f64 {
+    let centipawns = f64::from(score.as_centipawns().clamp(-2_000, 2_000));
+    2.0 / (1.0 + (-0.003_682_08 * centipawns).exp()) - 1.0
+}
+
+/// Evaluates the position with the engine, or with the rules when the game is over.
+/// Gives none if the engine found no line in time.
+pub fn evaluate_position(
+    engine: &EngineKind,
+    board: &BoardLogic,
+    limit: SearchLimit,
+) -> Result<Option<PositionEval>, String> {
+    if board.is_checkmate() {
+        let white_chances = match board.turn() {
+            Player::White => -1.0,
+            Player::Black => 1.0,
+        };
+        return Ok(Some(PositionEval {
+            white_chances,
+            score: None,
+            best_line: Vec::new(),
+        }));
+    }
+    if board.is_stalemate() {
+        return Ok(Some(PositionEval {
+            white_chances: 0.0,
+            score: None,
+            best_line: Vec::new(),
+        }));
+    }
+    let lines = analyse_position(engine, &board.fen(), 1, limit)?;
+    Ok(lines.into_iter().next().map(|line| PositionEval {
+        white_chances: winning_chances(line.score),
+        score: Some(line.score),
+        best_line: line.moves,
+    }))
+}
+
+/// What the engine thinks of a move of the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    pub played: BitMove,
+    /// None for a good move, or when a position could not be evaluated.
+    pub quality: Option<MoveQuality>,
+    /// Evaluations of the positions before and after the move.
+    pub before: Option<PositionEval>,
+    pub after: Option<PositionEval>,
+}
+
+/// Evaluates every position of the game, and judges each move by the winning chances
+/// it loses compared with the best move. `progress` is given the number of positions
+/// evaluated and their total after each one.
+pub fn review_game(
+    engine: &EngineKind,
+    start: &BoardLogic,
+    moves: &[BitMove],
+    limit: SearchLimit,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<MoveReview>, String> {
+    let mut board = start.clone();
+    let total = moves.len() + 1;
+    let mut evals = Vec::with_capacity(total);
+    evals.push(evaluate_position(engine, &board, limit)?);
+    progress(1, total);
+    for chess_move in moves {
+        board.play_move(*chess_move);
+        evals.push(evaluate_position(engine, &board, limit)?);
+        progress(evals.len(), total);
+    }
+    Ok(judge_moves(start, moves, &evals))
+}
+
+/// Judges the moves played from the board with the evaluations of the positions of the game,
+/// the start position first.
+pub fn judge_moves(
+    board: &BoardLogic,
+    moves: &[BitMove],
+    evals: &[Option<PositionEval>],
+) -> Vec<MoveReview> {
+    let mut board = board.clone();
+    let mut reviews = Vec::with_capacity(moves.len());
+    for (index, chess_move) in moves.iter().enumerate() {
+        let mover = board.turn();
+        let before = evals[index].clone();
+        let after = evals[index + 1].clone();
+        let quality = match (&before, &after) {
+            (Some(before), Some(after)) if before.best_line.first() != Some(chess_move) => {
+                MoveQuality::from_loss(before.chances_of(mover) - after.chances_of(mover))
+            }
+            _ => None,
+        };
+        reviews.push(MoveReview {
+            played: *chess_move,
+            quality,
+            before,
+            after,
+        });
+        board.play_move(*chess_move);
+    }
+    reviews
+}
+
+/// Writes the review in the moves of the game : the evaluation after each move as an
+/// `[%eval]` comment, and for bad moves, their glyph, a comment naming the best move,
+/// and the best line as a variation.
+pub fn annotate_game(game: &mut PgnGame, reviews: &[MoveReview]) {
+    let mut board = match game.start_board() {
+        Some(board) => board,
+        None => return,
+    };
+    for (pgn_move, review) in game.moves.iter_mut().zip(reviews) {
+        if let Some(score) = review.after.as_ref().and_then(|after| after.score) {
+            pgn_move.add_comment(&format!("[%eval {}]", eval_comment(score)));
+        }
+        let best_line = review
+            .before
+            .as_ref()
+            .map(|before| before.best_line.as_slice())
+            .unwrap_or_default();
+        if let (Some(quality), Some(best_move)) = (review.quality, best_line.first()) {
+            pgn_move.nags.push(quality.nag());
+            pgn_move.add_comment(&format!(
+                "{}. {} was best.",
+                quality.name(),
+                move_to_san(&board, *best_move, &PieceLetters::ENGLISH)
+            ));
+            pgn_move.variations.push(variation(&board, best_line));
+        }
+        board.play_move(review.played);
+    }
+}
+
+/// Value of an `[%eval]` comment : pawns from the point of view of White, or `#` and the
+/// number of moves of a mate, negative when Black mates.
+fn eval_comment(score: Score) -> String {
+    match score {
+        Score::Centipawns(centipawns) => format!("{:.2}", f64::from(centipawns) / 100.0),
+        Score::Mate(moves) => format!("#{}", moves),
+    }
+}
+
+/// The moves of a line played from the position, in English algebraic notation.
+fn variation(board: &BoardLogic, moves: &[BitMove]) -> Vec<PgnMove> {
+    let mut board = board.clone();
+    let mut line = Vec::new();
+    for chess_move in moves {
+        line.push(PgnMove::new(move_to_san(
+            &board,
+            *chess_move,
+            &PieceLetters::ENGLISH,
+        )));
+        board.play_move(*chess_move);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    const JUST_BELOW: f64 = 1e-6;
+
+    fn uci_moves(board: &BoardLogic, uci_moves: &[&str]) -> Vec<BitMove> {
+        let mut board = board.clone();
+        uci_moves
+            .iter()
+            .map(|uci| {
+                let chess_move = find_uci_move(&board, uci).unwrap();
+                board.play_move(chess_move);
+                chess_move
+            })
+            .collect()
+    }
+
+    fn eval(
+        board: &BoardLogic,
+        white_chances: f64,
+        centipawns: i32,
+        best: &[&str],
+    ) -> PositionEval {
+        PositionEval {
+            white_chances,
+            score: Some(Score::Centipawns(centipawns)),
+            best_line: uci_moves(board, best),
+        }
+    }
+
+    fn after(board: &BoardLogic, uci: &str) -> BoardLogic {
+        let mut board = board.clone();
+        board.play_move(find_uci_move(&board, uci).unwrap());
+        board
+    }
+
+    #[test]
+    fn losses_are_classified_from_their_threshold() {
+        assert_eq!(MoveQuality::from_loss(0.0), None);
+        assert_eq!(MoveQuality::from_loss(-0.5), None);
+        assert_eq!(MoveQuality::from_loss(INACCURACY_LOSS - JUST_BELOW), None);
+        assert_eq!(
+            MoveQuality::from_loss(INACCURACY_LOSS),
+            Some(MoveQuality::Inaccuracy)
+        );
+        assert_eq!(
+            MoveQuality::from_loss(MISTAKE_LOSS - JUST_BELOW),
+            Some(MoveQuality::Inaccuracy)
+        );
+        assert_eq!(
+            MoveQuality::from_loss(MISTAKE_LOSS),
+            Some(MoveQuality::Mistake)
+        );
+        assert_eq!(
+            MoveQuality::from_loss(BLUNDER_LOSS - JUST_BELOW),
+            Some(MoveQuality::Mistake)
+        );
+        assert_eq!(
+            MoveQuality::from_loss(BLUNDER_LOSS),
+            Some(MoveQuality::Blunder)
+        );
+        assert_eq!(MoveQuality::from_loss(2.0), Some(MoveQuality::Blunder));
+    }
+
+    #[test]
+    fn winning_chances_follow_the_score() {
+        assert_eq!(winning_chances(Score::Centipawns(0)), 0.0);
+        let pawn_up = winning_chances(Score::Centipawns(100));
+        assert!((pawn_up - 0.182).abs() < 0.001, "{}", pawn_up);
+        assert!((winning_chances(Score::Centipawns(-100)) + pawn_up).abs() < 1e-12);
+        assert!(winning_chances(Score::Mate(1)) > 0.99);
+        assert!(winning_chances(Score::Mate(-1)) < -0.99);
+        assert_eq!(
+            winning_chances(Score::Mate(3)),
+            winning_chances(Score::Centipawns(5_000))
+        );
+    }
+
+    #[test]
+    fn moves_are_judged_by_the_chances_their_player_loses() {
+        let start = BoardLogic::new();
+        let moves = uci_moves(&start, &["e2e4", "e7e5", "g1f3"]);
+        let after_e4 = after(&start, "e2e4");
+        let after_e5 = after(&after_e4, "e7e5");
+        let evals = vec![
+            Some(eval(&start, 0.5, 200, &["d2d4"])),
+            // White loses 0.25 : a mistake.
+            Some(eval(&after_e4, 0.25, 80, &["c7c5"])),
+            // Black loses 0.375 : a blunder.
+            Some(eval(&after_e5, 0.625, 300, &["g1f3"])),
+            // The best move loses nothing, whatever the evaluations say.
+            Some(eval(&after(&after_e5, "g1f3"), 0.0, 0, &[])),
+        ];
+        let reviews = judge_moves(&start, &moves, &evals);
+        let qualities: Vec<_> = reviews.iter().map(|review| review.quality).collect();
+        assert_eq!(
+            qualities,
+            [Some(MoveQuality::Mistake), Some(MoveQuality::Blunder), None]
+        );
+        assert_eq!(reviews[0].played, moves[0]);
+        assert_eq!(reviews[0].before, evals[0]);
+        assert_eq!(reviews[0].after, evals[1]);
+    }
+
+    #[test]
+    fn losses_just_below_a_threshold_are_not_flagged() {
+        let start = BoardLogic::new();
+        let moves = uci_moves(&start, &["e2e4"]);
+        let judge = |after_chances: f64| {
+            let evals = vec![
+                Some(eval(&start, 0.5, 200, &["d2d4"])),
+                Some(eval(&after(&start, "e2e4"), after_chances, 0, &[])),
+            ];
+            judge_moves(&start, &moves, &evals)[0].quality
+        };
+        assert_eq!(judge(0.5 - INACCURACY_LOSS + JUST_BELOW), None);
+        assert_eq!(
+            judge(0.5 - INACCURACY_LOSS - JUST_BELOW),
+            Some(MoveQuality::Inaccuracy)
+        );
+        assert_eq!(
+            judge(0.5 - MISTAKE_LOSS + JUST_BELOW),
+            Some(MoveQuality::Inaccuracy)
+        );
+        assert_eq!(
+            judge(0.5 - MISTAKE_LOSS - JUST_BELOW),
+            Some(MoveQuality::Mistake)
+        );
+        assert_eq!(
+            judge(0.5 - BLUNDER_LOSS + JUST_BELOW),
+            Some(MoveQuality::Mistake)
+        );
+        assert_eq!(
+            judge(0.5 - BLUNDER_LOSS - JUST_BELOW),
+            Some(MoveQuality::Blunder)
+        );
+        // Gaining chances is never a bad move.
+        assert_eq!(judge(0.9), None);
+    }
+
+    #[test]
+    fn moves_without_evaluation_are_not_judged() {
+        let start = BoardLogic::new();
+        let moves = uci_moves(&start, &["e2e4"]);
+        let reviews = judge_moves(
+            &start,
+            &moves,
+            &[Some(eval(&start, 1.0, 900, &["d2d4"])), None],
+        );
+        assert_eq!(reviews[0].quality, None);
+        assert_eq!(reviews[0].after, None);
+    }
+
+    #[test]
+    fn bad_moves_are_annotated_with_the_best_line() {
+        let mut game = parse_pgn("1. e4 e5 *").unwrap().remove(0);
+        let start = BoardLogic::new();
+        let moves = uci_moves(&start, &["e2e4", "e7e5"]);
+        let after_e4 = after(&start, "e2e4");
+        let mut last = eval(&after(&after_e4, "e7e5"), 0.25, 30, &[]);
+        last.score = Some(Score::Mate(-2));
+        let evals = vec![
+            Some(eval(&start, 0.5, 200, &["d2d4", "d7d5"])),
+            Some(eval(&after_e4, 0.25, 30, &["e7e5"])),
+            Some(last),
+        ];
+        let reviews = judge_moves(&start, &moves, &evals);
+        annotate_game(&mut game, &reviews);
+
+        assert_eq!(game.moves[0].nags, [MoveQuality::Mistake.nag()]);
+        assert_eq!(
+            game.moves[0].comment.as_deref(),
+            Some("[%eval 0.30] Mistake. d4 was best.")
+        );
+        let variation: Vec<&str> = game.moves[0].variations[0]
+            .iter()
+            .map(|pgn_move| pgn_move.san.as_str())
+            .collect();
+        assert_eq!(variation, ["d4", "d5"]);
+        assert!(game.moves[1].nags.is_empty());
+        assert!(game.moves[1].variations.is_empty());
+        assert_eq!(game.moves[1].comment.as_deref(), Some("[%eval #-2]"));
+    }
+}
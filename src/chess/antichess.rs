@@ -0,0 +1,412 @@
+use pleco::core::piece_move::{MoveFlag, PreMoveInfo};
+use pleco::{BitMove, Piece, PieceType, Player, SQ};
+
+use super::chess960::fen_placement;
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+/// Pawns may also promote to a king in Antichess.
+const PROMOTION_TYPES: [PieceType; 5] = [
+    PieceType::Q,
+    PieceType::R,
+    PieceType::B,
+    PieceType::N,
+    PieceType::K,
+];
+/// Flags of the moves of pleco promoting to a king, which it has no flags for. They take
+/// the two flags it leaves unused, both of which it reads as captures.
+const KING_PROMOTION_FLAG: u16 = 0b0110;
+const KING_PROMOTION_CAPTURE_FLAG: u16 = 0b0111;
+
+/// A position of Antichess. The board of pleco cannot hold it, as it needs exactly one king
+/// by player, whereas kings are captured like the other pieces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AntichessPosition {
+    /// The pieces of the 64 squares, from a1 to h8.
+    pieces: Vec<Piece>,
+    turn: Player,
+    /// Square a pawn which just moved two squares went over, where it may be taken en passant.
+    en_passant: Option<SQ>,
+    half_moves: u32,
+    move_number: u32,
+}
+
+impl AntichessPosition {
+    /// The position of the FEN, its castling rights being ignored as Antichess has no castling.
+    pub fn from_fen(fen: &str) -> Option<Self> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let mut pieces = vec![Piece::None; 64];
+        let ranks: Vec<&str> = fields.first()?.split('/').collect();
+        if ranks.len() != 8 {
+            return None;
+        }
+        for (index, rank_pieces) in ranks.iter().enumerate() {
+            let rank = 7 - index;
+            let mut file = 0;
+            for character in rank_pieces.chars() {
+                if let Some(empty_squares) = character.to_digit(10) {
+                    file += empty_squares as usize;
+                    continue;
+                }
+                if file > 7 {
+                    return None;
+                }
+                let piece = piece_of_char(character)?;
+                if piece.type_of() == PieceType::P && (rank == 0 || rank == 7) {
+                    return None;
+                }
+                pieces[rank * 8 + file] = piece;
+                file += 1;
+            }
+            if file != 8 {
+                return None;
+            }
+        }
+        let turn = match fields.get(1) {
+            Some(&"w") | None => Player::White,
+            Some(&"b") => Player::Black,
+            Some(_) => return None,
+        };
+        let en_passant = match fields.get(3) {
+            Some(&"-") | None => None,
+            Some(square) => Some(square_of_name(square)?),
+        };
+        let number = |index: usize, default: u32| match fields.get(index) {
+            Some(number) => number.parse().ok(),
+            None => Some(default),
+        };
+        Some(AntichessPosition {
+            pieces,
+            turn,
+            en_passant,
+            half_moves: number(4, 0)?,
+            move_number: number(5, 1)?,
+        })
+    }
+
+    pub fn fen(&self) -> String {
+        let en_passant = match self.en_passant {
+            Some(square) => square.to_string(),
+            None => String::from("-"),
+        };
+        format!(
+            "{} {} - {} {} {}",
+            fen_placement(&self.pieces),
+            if self.turn == Player::White { "w" } else { "b" },
+            en_passant,
+            self.half_moves,
+            self.move_number
+        )
+    }
+
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    pub fn piece_at(&self, square: SQ) -> Piece {
+        self.pieces[square.0 as usize]
+    }
+
+    pub fn en_passant(&self) -> Option<SQ> {
+        self.en_passant
+    }
+
+    /// Plies played since the last capture or pawn move.
+    pub fn half_moves(&self) -> u32 {
+        self.half_moves
+    }
+
+    /// The legal moves : the captures when there are some, as capturing is compulsory,
+    /// or else all the moves of the pieces, none being ever prevented by a check.
+    pub fn legal_moves(&self) -> Vec<BitMove> {
+        let mut moves = Vec::new();
+        for square in (0..64).map(SQ) {
+            let piece = self.piece_at(square);
+            if piece.player() != Some(self.turn) {
+                continue;
+            }
+            match piece.type_of() {
+                PieceType::P => self.add_pawn_moves(square, &mut moves),
+                PieceType::N => self.add_steps(square, &KNIGHT_STEPS, &mut moves),
+                PieceType::K => self.add_steps(square, &KING_STEPS, &mut moves),
+                PieceType::B => self.add_slides(square, &BISHOP_DIRECTIONS, &mut moves),
+                PieceType::R => self.add_slides(square, &ROOK_DIRECTIONS, &mut moves),
+                _ => {
+                    self.add_slides(square, &BISHOP_DIRECTIONS, &mut moves);
+                    self.add_slides(square, &ROOK_DIRECTIONS, &mut moves);
+                }
+            }
+        }
+        if moves.iter().any(|chess_move| is_capture(*chess_move)) {
+            moves.retain(|chess_move| is_capture(*chess_move));
+        }
+        moves
+    }
+
+    /// Plays a move, which must be legal in the position.
+    pub fn apply_move(&mut self, chess_move: BitMove) {
+        let origin = chess_move.get_src();
+        let destination = chess_move.get_dest();
+        let piece = self.piece_at(origin);
+        if chess_move.is_en_passant() {
+            let taken_square = origin.rank_idx_of_sq() * 8 + destination.file_idx_of_sq();
+            self.pieces[taken_square as usize] = Piece::None;
+        }
+        self.pieces[destination.0 as usize] = match promotion_type(chess_move) {
+            Some(promotion) => Piece::make_lossy(self.turn, promotion),
+            None => piece,
+        };
+        self.pieces[origin.0 as usize] = Piece::None;
+        self.en_passant = if chess_move.is_double_push().0 {
+            Some(SQ((origin.0 + destination.0) / 2))
+        } else {
+            None
+        };
+        if is_capture(chess_move) || piece.type_of() == PieceType::P {
+            self.half_moves = 0;
+        } else {
+            self.half_moves += 1;
+        }
+        if self.turn == Player::Black {
+            self.move_number += 1;
+        }
+        self.turn = self.turn.other_player();
+    }
+
+    fn add_pawn_moves(&self, origin: SQ, moves: &mut Vec<BitMove>) {
+        let (forward, start_rank) = match self.turn {
+            Player::White => (1, 1),
+            Player::Black => (-1, 6),
+        };
+        if let Some(destination) = shifted(origin, (0, forward)) {
+            if self.piece_at(destination) == Piece::None {
+                add_pawn_move(origin, destination, false, moves);
+                let double_push = shifted(destination, (0, forward))
+                    .filter(|_| origin.rank_idx_of_sq() == start_rank)
+                    .filter(|square| self.piece_at(*square) == Piece::None);
+                if let Some(destination) = double_push {
+                    moves.push(new_move(origin, destination, MoveFlag::DoublePawnPush));
+                }
+            }
+        }
+        for side in &[-1, 1] {
+            let destination = match shifted(origin, (*side, forward)) {
+                Some(destination) => destination,
+                None => continue,
+            };
+            if Some(destination) == self.en_passant {
+                let flags = MoveFlag::Capture { ep_capture: true };
+                moves.push(new_move(origin, destination, flags));
+            } else if self.is_opponent_at(destination) {
+                add_pawn_move(origin, destination, true, moves);
+            }
+        }
+    }
+
+    fn add_steps(&self, origin: SQ, steps: &[(i8, i8)], moves: &mut Vec<BitMove>) {
+        for step in steps {
+            if let Some(destination) = shifted(origin, *step) {
+                self.add_move_to(origin, destination, moves);
+            }
+        }
+    }
+
+    fn add_slides(&self, origin: SQ, directions: &[(i8, i8)], moves: &mut Vec<BitMove>) {
+        for direction in directions {
+            let mut square = origin;
+            while let Some(destination) = shifted(square, *direction) {
+                self.add_move_to(origin, destination, moves);
+                if self.piece_at(destination) != Piece::None {
+                    break;
+                }
+                square = destination;
+            }
+        }
+    }
+
+    /// Adds the move of a piece other than a pawn, unless a piece of its player stands there.
+    fn add_move_to(&self, origin: SQ, destination: SQ, moves: &mut Vec<BitMove>) {
+        if self.piece_at(destination) == Piece::None {
+            moves.push(new_move(origin, destination, MoveFlag::QuietMove));
+        } else if self.is_opponent_at(destination) {
+            let flags = MoveFlag::Capture { ep_capture: false };
+            moves.push(new_move(origin, destination, flags));
+        }
+    }
+
+    fn is_opponent_at(&self, square: SQ) -> bool {
+        self.piece_at(square).player() == Some(self.turn.other_player())
+    }
+}
+
+/// Adds the move of a pawn, or its promotions when it reaches the last rank.
+fn add_pawn_move(origin: SQ, destination: SQ, capture: bool, moves: &mut Vec<BitMove>) {
+    let rank = destination.rank_idx_of_sq();
+    if rank == 0 || rank == 7 {
+        for prom in &PROMOTION_TYPES {
+            if *prom == PieceType::K {
+                moves.push(king_promotion(origin, destination, capture));
+                continue;
+            }
+            let flags = MoveFlag::Promotion {
+                capture,
+                prom: *prom,
+            };
+            moves.push(new_move(origin, destination, flags));
+        }
+    } else if capture {
+        let flags = MoveFlag::Capture { ep_capture: false };
+        moves.push(new_move(origin, destination, flags));
+    } else {
+        moves.push(new_move(origin, destination, MoveFlag::QuietMove));
+    }
+}
+
+fn new_move(src: SQ, dst: SQ, flags: MoveFlag) -> BitMove {
+    BitMove::init(PreMoveInfo { src, dst, flags })
+}
+
+fn king_promotion(src: SQ, dst: SQ, capture: bool) -> BitMove {
+    let flag = if capture {
+        KING_PROMOTION_CAPTURE_FLAG
+    } else {
+        KING_PROMOTION_FLAG
+    };
+    BitMove::new(flag << 12 | u16::from(dst.0) << 6 | u16::from(src.0))
+}
+
+/// The piece the move promotes to, which may be a king in Antichess.
+pub fn promotion_type(chess_move: BitMove) -> Option<PieceType> {
+    match chess_move.flag() {
+        KING_PROMOTION_FLAG | KING_PROMOTION_CAPTURE_FLAG => Some(PieceType::K),
+        _ if chess_move.is_promo() => Some(chess_move.promo_piece()),
+        _ => None,
+    }
+}
+
+/// Whether the move captures, which pleco tells wrong of a promotion to a king.
+pub fn is_capture(chess_move: BitMove) -> bool {
+    chess_move.is_capture() && chess_move.flag() != KING_PROMOTION_FLAG
+}
+
+/// The square at the given files and ranks from the square, if it is on the board.
+fn shifted(square: SQ, (files, ranks): (i8, i8)) -> Option<SQ> {
+    let file = square.file_idx_of_sq() as i8 + files;
+    let rank = square.rank_idx_of_sq() as i8 + ranks;
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some(SQ((rank * 8 + file) as u8))
+    } else {
+        None
+    }
+}
+
+fn piece_of_char(character: char) -> Option<Piece> {
+    let player = if character.is_ascii_uppercase() {
+        Player::White
+    } else {
+        Player::Black
+    };
+    let piece_type = match character.to_ascii_lowercase() {
+        'p' => PieceType::P,
+        'n' => PieceType::N,
+        'b' => PieceType::B,
+        'r' => PieceType::R,
+        'q' => PieceType::Q,
+        'k' => PieceType::K,
+        _ => return None,
+    };
+    Some(Piece::make_lossy(player, piece_type))
+}
+
+fn square_of_name(name: &str) -> Option<SQ> {
+    let chars: Vec<char> = name.chars().collect();
+    match chars.as_slice() {
+        [file @ 'a'..='h', rank @ '1'..='8'] => {
+            Some(SQ((*rank as u8 - b'1') * 8 + (*file as u8 - b'a')))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::board_logic::BoardLogic;
+    use super::super::variant::{Variant, VariantEnd, VariantWin};
+    use super::*;
+
+    fn board(fen: &str) -> BoardLogic {
+        BoardLogic::from_variant_fen(Variant::Antichess, fen).unwrap()
+    }
+
+    /// Plays the legal move going from a square to another.
+    fn play(board: &mut BoardLogic, origin: &str, destination: &str) {
+        let chess_move = board
+            .dragged_move(
+                square_of_name(origin).unwrap(),
+                square_of_name(destination).unwrap(),
+            )
+            .unwrap();
+        board.play_move(chess_move);
+    }
+
+    /// Whether White won, having lost all their pieces or being blocked.
+    fn white_has_no_move_left(board: &BoardLogic) -> bool {
+        board.variant_win()
+            == Some(VariantWin {
+                end: VariantEnd::NoMoveLeft,
+                winner: Player::White,
+            })
+    }
+
+    #[test]
+    fn captures_are_compulsory() {
+        let position = AntichessPosition::from_fen("k7/8/8/3p4/4P3/8/8/3R3K w - - 0 1").unwrap();
+        let mut moves: Vec<String> = position
+            .legal_moves()
+            .iter()
+            .map(|chess_move| chess_move.stringify())
+            .collect();
+        moves.sort();
+        assert_eq!(moves, vec!["d1d5", "e4d5"]);
+    }
+
+    #[test]
+    fn losing_all_pieces_wins() {
+        let mut board = board("r7/8/8/8/8/8/8/R7 b - - 0 1");
+        assert_eq!(board.variant_win(), None);
+        play(&mut board, "a8", "a1");
+        assert_eq!(board.pieces_count(Player::White), 0);
+        assert!(white_has_no_move_left(&board));
+        assert!(board.is_game_over());
+    }
+
+    #[test]
+    fn being_stalemated_wins() {
+        let mut board = board("8/8/p7/8/P7/8/8/8 b - - 0 1");
+        play(&mut board, "a6", "a5");
+        // The pawn of White is blocked, and it has no other piece.
+        assert!(board.legal_moves().is_empty());
+        assert!(white_has_no_move_left(&board));
+        assert!(!board.is_stalemate());
+    }
+}
@@ -0,0 +1,235 @@
+use pleco::Player;
+
+use super::annotation::{MoveQuality, MoveReview};
+use super::exercise::Exercise;
+use super::pgn::{PgnGame, PgnMove, CHESS960_VARIANT, VARIANT_TAG};
+use super::san::{move_to_san, PieceLetters};
+use super::BoardLogic;
+
+/// The best line is cut so that the solver finds at most this many moves.
+const MAX_SOLVER_MOVES: usize = 3;
+
+/// Tags of the game copied to its exercises, so that they tell which game they come from.
+const SOURCE_TAGS: [&str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+
+/// Exercises set in the positions where a blunder was played, the blunderer being the solver
+/// and the best line of the engine the solution. With a player name, only their blunders
+/// are kept.
+pub fn blunder_exercises(
+    game: &PgnGame,
+    reviews: &[MoveReview],
+    player: Option<&str>,
+) -> Vec<Exercise> {
+    let mut board = match game.start_board() {
+        Some(board) => board,
+        None => return Vec::new(),
+    };
+    let mut exercises = Vec::new();
+    for review in reviews {
+        let is_blunder = review.quality == Some(MoveQuality::Blunder);
+        if is_blunder && is_player_of(game, board.turn(), player) {
+            if let Some(exercise) = blunder_exercise(game, &board, review) {
+                exercises.push(exercise);
+            }
+        }
+        board.play_move(review.played);
+    }
+    exercises
+}
+
+/// Whether the player is the given one, by the name of the `White` or `Black` tag.
+fn is_player_of(game: &PgnGame, side: Player, player: Option<&str>) -> bool {
+    let player = match player {
+        Some(player) if !player.trim().is_empty() => player.trim(),
+        _ => return true,
+    };
+    let tag = match side {
+        Player::White => "White",
+        Player::Black => "Black",
+    };
+    game.tag(tag)
+        .map(|name| name.trim().eq_ignore_ascii_case(player))
+        .unwrap_or(false)
+}
+
+fn blunder_exercise(game: &PgnGame, board: &BoardLogic, review: &MoveReview) -> Option<Exercise> {
+    let best_line = &review.before.as_ref()?.best_line;
+    // The solution ends with a move of the solver.
+    let plies = best_line.len().min(2 * MAX_SOLVER_MOVES - 1);
+    if plies == 0 {
+        return None;
+    }
+    let plies = plies - (plies + 1) % 2;
+
+    let fen = board.fen();
+    let move_number: u32 = fen
+        .split_whitespace()
+        .nth(5)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(1);
+    let mut tags: Vec<(String, String)> = SOURCE_TAGS
+        .iter()
+        .map(|name| (name.to_string(), game.tag(name).unwrap_or("?").to_string()))
+        .collect();
+    tags.push((String::from("Result"), String::from("*")));
+    if board.is_chess960() {
+        tags.push((VARIANT_TAG.to_string(), CHESS960_VARIANT.to_string()));
+    }
+    tags.push((String::from("SetUp"), String::from("1")));
+    tags.push((String::from("FEN"), fen));
+
+    let mut line_board = board.clone();
+    let mut moves = Vec::new();
+    for chess_move in &best_line[..plies] {
+        moves.push(PgnMove::new(move_to_san(
+            &line_board,
+            *chess_move,
+            &PieceLetters::ENGLISH,
+        )));
+        line_board.play_move(*chess_move);
+    }
+    let dots = match board.turn() {
+        Player::White => ".",
+        Player::Black => "...",
+    };
+    moves[0].add_comment(&format!(
+        "{}{} {}?? was played in the game.",
+        move_number,
+        dots,
+        move_to_san(board, review.played, &PieceLetters::ENGLISH)
+    ));
+
+    let exercise_game = PgnGame {
+        tags,
+        moves,
+        first_move_number: move_number,
+        black_starts: board.turn() == Player::Black,
+        result: String::from("*"),
+    };
+    Exercise::from_pgn_game(exercise_game).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::annotation::{judge_moves, PositionEval};
+    use super::super::engine::Score;
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    use pleco::BitMove;
+
+    const GAME: &str = "[Event \"Club\"]\n[White \"Alice\"]\n[Black \"Bob\"]\n\n1. f3 e5 2. g4 *";
+
+    /// Winning chances losses around the blunder threshold of 0.3.
+    const BLUNDER: f64 = 0.3 + 1e-6;
+    const ALMOST_BLUNDER: f64 = 0.3 - 1e-6;
+
+    fn uci_moves(board: &BoardLogic, uci_moves: &[&str]) -> Vec<BitMove> {
+        let mut board = board.clone();
+        uci_moves
+            .iter()
+            .map(|uci| {
+                let chess_move = find_uci_move(&board, uci).unwrap();
+                board.play_move(chess_move);
+                chess_move
+            })
+            .collect()
+    }
+
+    /// Reviews of `1. f3 e5 2. g4`, each move losing the given winning chances for its
+    /// player, with the given best lines.
+    fn reviews(losses: [f64; 3], best_lines: [&[&str]; 3]) -> Vec<MoveReview> {
+        let mut board = BoardLogic::new();
+        let moves = uci_moves(&board, &["f2f3", "e7e5", "g2g4"]);
+        let mut white_chances = 0.0;
+        let mut evals = Vec::new();
+        for (index, chess_move) in moves.iter().enumerate() {
+            evals.push(Some(PositionEval {
+                white_chances,
+                score: Some(Score::Centipawns(0)),
+                best_line: uci_moves(&board, best_lines[index]),
+            }));
+            white_chances += match board.turn() {
+                Player::White => -losses[index],
+                Player::Black => losses[index],
+            };
+            board.play_move(*chess_move);
+        }
+        evals.push(Some(PositionEval {
+            white_chances,
+            score: None,
+            best_line: Vec::new(),
+        }));
+        judge_moves(&BoardLogic::new(), &moves, &evals)
+    }
+
+    fn game() -> PgnGame {
+        parse_pgn(GAME).unwrap().remove(0)
+    }
+
+    const LONG_LINE: &[&str] = &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4"];
+    const BLACK_LINE: &[&str] = &["d7d5", "d2d4", "c8f5"];
+    const SHORT_LINE: &[&str] = &["d7d5", "d2d4"];
+
+    #[test]
+    fn only_blunders_become_exercises() {
+        let reviews = reviews(
+            [BLUNDER, ALMOST_BLUNDER, BLUNDER],
+            [LONG_LINE, BLACK_LINE, &[]],
+        );
+        let exercises = blunder_exercises(&game(), &reviews, None);
+        // The last blunder has no best line to make a solution of.
+        assert_eq!(exercises.len(), 1);
+        assert_eq!(exercises[0].solver(), Player::White);
+        assert_eq!(exercises[0].game.start_fen(), BoardLogic::new().fen());
+    }
+
+    #[test]
+    fn solutions_end_with_a_move_of_the_solver() {
+        let reviews = reviews([BLUNDER, BLUNDER, 0.0], [LONG_LINE, SHORT_LINE, &[]]);
+        let exercises = blunder_exercises(&game(), &reviews, None);
+        assert_eq!(exercises.len(), 2);
+        // Seven plies are cut to the three moves of the solver and the two replies.
+        assert_eq!(exercises[0].solution.len(), 5);
+        assert_eq!(exercises[0].game.moves[0].san, "e4");
+        // An even line loses its last reply.
+        assert_eq!(exercises[1].solution.len(), 1);
+        assert_eq!(exercises[1].solver(), Player::Black);
+    }
+
+    #[test]
+    fn exercises_tell_the_blunder_and_the_game() {
+        let reviews = reviews([0.0, BLUNDER, 0.0], [&[], BLACK_LINE, &[]]);
+        let exercises = blunder_exercises(&game(), &reviews, None);
+        assert_eq!(exercises.len(), 1);
+        let exercise_game = &exercises[0].game;
+        assert_eq!(
+            exercise_game.moves[0].comment.as_deref(),
+            Some("1... e5?? was played in the game.")
+        );
+        assert_eq!(exercise_game.tag("Event"), Some("Club"));
+        assert_eq!(exercise_game.tag("White"), Some("Alice"));
+        assert_eq!(exercise_game.tag("Site"), Some("?"));
+        assert_eq!(exercise_game.tag("SetUp"), Some("1"));
+        assert!(exercise_game.black_starts);
+        assert_eq!(exercise_game.first_move_number, 1);
+    }
+
+    #[test]
+    fn blunders_are_filtered_by_player_name() {
+        let reviews = reviews([BLUNDER, BLUNDER, 0.0], [LONG_LINE, BLACK_LINE, &[]]);
+        let solvers = |player| -> Vec<Player> {
+            blunder_exercises(&game(), &reviews, player)
+                .iter()
+                .map(|exercise| exercise.solver())
+                .collect()
+        };
+        assert_eq!(solvers(None), [Player::White, Player::Black]);
+        assert_eq!(solvers(Some(" ")), [Player::White, Player::Black]);
+        assert_eq!(solvers(Some(" bob ")), [Player::Black]);
+        assert_eq!(solvers(Some("ALICE")), [Player::White]);
+        assert!(solvers(Some("Carol")).is_empty());
+    }
+}
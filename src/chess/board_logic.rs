@@ -0,0 +1,440 @@
+use druid::Data;
+
+use pleco::core::CastleType;
+use pleco::{BitMove, Board, Piece, Player, SQ};
+
+use super::antichess::{promotion_type, AntichessPosition};
+use super::chess960::{self, split_castling, with_castling, CastlingRights};
+use super::variant::{Variant, VariantEnd, VariantWin, HILL_SQUARES, WINNING_CHECKS};
+
+pub const STANDARD_START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone, Debug)]
+pub struct BoardLogic {
+    /// The position for pleco, which only its accessors read as it does not hold
+    /// the position of an Antichess game.
+    inner_logic: Board,
+    /// Position the game started from, needed to write the moves as a game.
+    pub start_fen: String,
+    /// Moves played since the start position.
+    pub history: Vec<BitMove>,
+    /// The castling rights of a Chess960 game, which pleco cannot play : its board then has
+    /// no castling rights at all. None for the standard castling.
+    pub chess960_castling: Option<CastlingRights>,
+    pub variant: Variant,
+    /// Checks given by White and by Black, which Three-check counts.
+    pub checks: [u8; 2],
+    /// The position of an Antichess game, which pleco cannot hold : its board then stays
+    /// in the standard start position, and the methods of the board logic must be used.
+    pub antichess: Option<AntichessPosition>,
+}
+
+impl BoardLogic {
+    pub fn new() -> Self {
+        Self {
+            inner_logic: Board::from_fen(STANDARD_START_FEN).unwrap(),
+            start_fen: String::from(STANDARD_START_FEN),
+            history: Vec::new(),
+            chess960_castling: None,
+            variant: Variant::Standard,
+            checks: [0, 0],
+            antichess: None,
+        }
+    }
+
+    /// The standard start position, played with the rules of the variant.
+    pub fn with_variant(variant: Variant) -> Self {
+        Self::from_variant_fen(variant, STANDARD_START_FEN)
+            .expect("The standard start position is valid")
+    }
+
+    /// The board set up in the given position, if the FEN is valid. Its castling field may be
+    /// the one of X-FEN or of Shredder-FEN : castling rights other than those of the standard
+    /// start position make it a Chess960 game.
+    pub fn from_fen(fen: &str) -> Option<Self> {
+        let (_, field) = split_castling(fen)?;
+        let logic = match Self::from_chess960_fen(fen) {
+            Some(logic) => logic,
+            // Standard castling rights without their rook were always accepted, pleco
+            // ignoring them.
+            None if field.chars().all(|right| "KQkq-".contains(right)) => {
+                return Board::from_fen(fen).ok().map(|inner_logic| Self {
+                    inner_logic,
+                    start_fen: fen.to_string(),
+                    ..Self::new()
+                });
+            }
+            None => return None,
+        };
+        let rights = logic.chess960_castling.unwrap_or_default();
+        match rights.standard_field(&logic.inner_logic) {
+            Some(field) => Board::from_fen(&with_castling(fen, &field))
+                .ok()
+                .map(|inner_logic| Self {
+                    inner_logic,
+                    chess960_castling: None,
+                    ..logic
+                }),
+            None => Some(logic),
+        }
+    }
+
+    /// The board set up in the given position of a Chess960 game, where the king castling
+    /// ends on the g or c file whatever the start squares of the king and rook.
+    pub fn from_chess960_fen(fen: &str) -> Option<Self> {
+        let (fen_without_castling, field) = split_castling(fen)?;
+        let inner_logic = Board::from_fen(&fen_without_castling).ok()?;
+        let rights = CastlingRights::parse(&field, &inner_logic)?;
+        Some(Self {
+            inner_logic,
+            start_fen: fen.to_string(),
+            chess960_castling: Some(rights),
+            ..Self::new()
+        })
+    }
+
+    /// The board set up in the given position of a game of the variant. The checks already
+    /// given in Three-check are read from the field of the FEN written by lichess, either
+    /// the checks left to give (`3+3` before the move counters) or those given (`+0+0` at
+    /// the end).
+    pub fn from_variant_fen(variant: Variant, fen: &str) -> Option<Self> {
+        match variant {
+            Variant::Standard => Self::from_fen(fen),
+            Variant::KingOfTheHill => Some(Self {
+                variant,
+                ..Self::from_fen(fen)?
+            }),
+            Variant::ThreeCheck => {
+                let (fen_without_checks, checks) = split_checks(fen)?;
+                Some(Self {
+                    variant,
+                    checks,
+                    start_fen: fen.to_string(),
+                    ..Self::from_fen(&fen_without_checks)?
+                })
+            }
+            Variant::Antichess => Some(Self {
+                start_fen: fen.to_string(),
+                variant,
+                antichess: Some(AntichessPosition::from_fen(fen)?),
+                ..Self::new()
+            }),
+        }
+    }
+
+    /// Whether the castling follows the rules of Chess960.
+    pub fn is_chess960(&self) -> bool {
+        self.chess960_castling.is_some()
+    }
+
+    /// Whether the player may still castle on the side, now or later.
+    pub fn can_castle(&self, player: Player, castle_type: CastleType) -> bool {
+        match &self.chess960_castling {
+            Some(rights) => rights.can_castle(player, castle_type),
+            None => self.inner_logic.can_castle(player, castle_type),
+        }
+    }
+
+    /// Whether a player may still castle, which tablebases do not cover.
+    pub fn has_castling_rights(&self) -> bool {
+        [Player::White, Player::Black].iter().any(|player| {
+            self.can_castle(*player, CastleType::KingSide)
+                || self.can_castle(*player, CastleType::QueenSide)
+        })
+    }
+
+    /// The board back in its start position, with the same castling rules.
+    pub fn start_position(&self) -> Self {
+        let start = if self.is_chess960() {
+            Self::from_chess960_fen(&self.start_fen).map(|start| Self {
+                variant: self.variant,
+                ..start
+            })
+        } else {
+            Self::from_variant_fen(self.variant, &self.start_fen)
+        };
+        start.expect("The start position was valid")
+    }
+
+    pub fn turn(&self) -> Player {
+        match &self.antichess {
+            Some(position) => position.turn(),
+            None => self.inner_logic.turn(),
+        }
+    }
+
+    pub fn piece_at(&self, square: SQ) -> Piece {
+        match &self.antichess {
+            Some(position) => position.piece_at(square),
+            None => self.inner_logic.piece_at_sq(square),
+        }
+    }
+
+    /// The number of pieces of the player, their king included.
+    pub fn pieces_count(&self, player: Player) -> usize {
+        (0..64)
+            .filter(|index| self.piece_at(SQ(*index)).player() == Some(player))
+            .count()
+    }
+
+    /// The square a pawn which just moved two squares went over, where it may be taken
+    /// en passant.
+    pub fn en_passant_square(&self) -> Option<SQ> {
+        let square = match &self.antichess {
+            Some(position) => return position.en_passant(),
+            None => self.inner_logic.ep_square(),
+        };
+        if square == SQ::NONE {
+            None
+        } else {
+            Some(square)
+        }
+    }
+
+    /// Plies played since the last capture or pawn move, which the fifty moves rule counts.
+    pub fn half_moves(&self) -> u32 {
+        match &self.antichess {
+            Some(position) => position.half_moves(),
+            None => self.inner_logic.rule_50().max(0) as u32,
+        }
+    }
+
+    /// Whether the king of the side to move is in check. Antichess has no check.
+    pub fn in_check(&self) -> bool {
+        self.antichess.is_none() && self.inner_logic.in_check()
+    }
+
+    /// Whether the side to move is checkmated. Antichess has no checkmate.
+    pub fn is_checkmate(&self) -> bool {
+        self.antichess.is_none() && self.inner_logic.checkmate()
+    }
+
+    /// Whether the side to move has no legal move without being in check. In Antichess,
+    /// having no move left wins the game instead, as `variant_win` tells.
+    pub fn is_stalemate(&self) -> bool {
+        // `Board::stalemate` also holds after fifty plies without capture nor pawn move.
+        self.antichess.is_none()
+            && !self.inner_logic.in_check()
+            && self.inner_logic.generate_moves().is_empty()
+    }
+
+    /// The position without its move counters, which identifies it whatever the moves
+    /// played before.
+    pub fn position_key(&self) -> String {
+        let fen = self.fen();
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        fields[..fields.len().saturating_sub(2)].join(" ")
+    }
+
+    /// The board of pleco, for its search and for the tablebases which read it. None in
+    /// Antichess, whose position pleco cannot hold. In Chess960, it has no castling rights.
+    pub fn pleco_board(&self) -> Option<&Board> {
+        match &self.antichess {
+            Some(_) => None,
+            None => Some(&self.inner_logic),
+        }
+    }
+
+    /// The FEN of the current position, with the checks left to give in Three-check.
+    /// The castling rights of Chess960 are written as in X-FEN, which engines read with
+    /// their `UCI_Chess960` option.
+    pub fn fen(&self) -> String {
+        if let Some(position) = &self.antichess {
+            return position.fen();
+        }
+        let fen = match &self.chess960_castling {
+            Some(rights) => {
+                with_castling(&self.inner_logic.fen(), &rights.field(&self.inner_logic))
+            }
+            None => self.inner_logic.fen(),
+        };
+        if self.variant != Variant::ThreeCheck {
+            return fen;
+        }
+        let mut fields: Vec<String> = fen.split_whitespace().map(String::from).collect();
+        let checks_left =
+            |player: Player| WINNING_CHECKS.saturating_sub(self.checks[player as usize]);
+        let checks_field = format!(
+            "{}+{}",
+            checks_left(Player::White),
+            checks_left(Player::Black)
+        );
+        let position = fields.len().min(4);
+        fields.insert(position, checks_field);
+        fields.join(" ")
+    }
+
+    /// The legal moves in the current position, castles of Chess960 included. None once
+    /// a variant rule ended the game.
+    pub fn legal_moves(&self) -> Vec<BitMove> {
+        if let Some(position) = &self.antichess {
+            return position.legal_moves();
+        }
+        if self.variant_win().is_some() {
+            return Vec::new();
+        }
+        let mut moves: Vec<BitMove> = self.inner_logic.generate_moves().iter().cloned().collect();
+        if let Some(rights) = &self.chess960_castling {
+            moves.extend(rights.castling_moves(&self.inner_logic));
+        }
+        moves
+    }
+
+    /// Plays a move, which must be legal in the current position.
+    pub fn play_move(&mut self, chess_move: BitMove) {
+        if let Some(position) = &mut self.antichess {
+            position.apply_move(chess_move);
+            self.history.push(chess_move);
+            return;
+        }
+        let player = self.inner_logic.turn();
+        if let Some(rights) = &self.chess960_castling {
+            self.chess960_castling = Some(rights.after_move(&self.inner_logic, chess_move));
+        }
+        chess960::apply_move(&mut self.inner_logic, chess_move);
+        if self.inner_logic.in_check() {
+            self.checks[player as usize] += 1;
+        }
+        self.history.push(chess_move);
+    }
+
+    /// The end of the game by a rule of its variant, if it is over this way : a king on
+    /// the hill in King of the Hill, a third check in Three-check, and in Antichess the side
+    /// to move with no move left, having lost all their pieces or being blocked.
+    pub fn variant_win(&self) -> Option<VariantWin> {
+        if let Some(position) = &self.antichess {
+            return if position.legal_moves().is_empty() {
+                Some(VariantWin {
+                    end: VariantEnd::NoMoveLeft,
+                    winner: position.turn(),
+                })
+            } else {
+                None
+            };
+        }
+        let end = match self.variant {
+            Variant::KingOfTheHill => VariantEnd::KingOnHill,
+            Variant::ThreeCheck => VariantEnd::ThirdCheck,
+            _ => return None,
+        };
+        let has_won = |player: Player| match end {
+            VariantEnd::KingOnHill => HILL_SQUARES.contains(&self.inner_logic.king_sq(player)),
+            _ => self.checks[player as usize] >= WINNING_CHECKS,
+        };
+        // The player who just moved is the one who may have won.
+        let last_mover = self.inner_logic.turn().other_player();
+        [last_mover, last_mover.other_player()]
+            .iter()
+            .find(|player| has_won(**player))
+            .map(|winner| VariantWin {
+                end,
+                winner: *winner,
+            })
+    }
+
+    /// The move of a piece dragged from a square and dropped on another one. A castle is played
+    /// by dropping the king on its castling square or on the rook it castles with, which is the
+    /// only way when the king does not move : dropping it back on its square plays nothing.
+    pub fn dragged_move(&self, origin: SQ, destination: SQ) -> Option<BitMove> {
+        let moves = self.legal_moves();
+        let exact_move = moves.iter().find(|chess_move| {
+            !chess_move.is_castle()
+                && chess_move.get_src() == origin
+                && chess_move.get_dest() == destination
+                && promotion_type(**chess_move).is_none()
+        });
+        if exact_move.is_some() {
+            return exact_move.cloned();
+        }
+        moves.into_iter().find(|chess_move| {
+            let castled_king_file = if chess_move.is_king_castle() { 6 } else { 2 };
+            chess_move.is_castle()
+                && chess_move.get_src() == origin
+                && (chess_move.get_dest() == destination
+                    || (destination != origin
+                        && destination.rank_idx_of_sq() == origin.rank_idx_of_sq()
+                        && destination.file_idx_of_sq() == castled_king_file))
+        })
+    }
+
+    /// Squares of the pieces which moved since the start position.
+    pub fn moved_pieces_squares(&self) -> Vec<SQ> {
+        let mut squares: Vec<SQ> = Vec::new();
+        for chess_move in &self.history {
+            let origin = chess_move.get_src();
+            let destination = chess_move.get_dest();
+            squares.retain(|square| *square != origin && *square != destination);
+            if chess_move.is_castle() {
+                // The destination of a castle is the rook square.
+                let (king_file, rook_file) = if chess_move.is_king_castle() {
+                    (6, 5)
+                } else {
+                    (2, 3)
+                };
+                let rank_start = origin.0 - origin.0 % 8;
+                squares.push(SQ(rank_start + king_file));
+                squares.push(SQ(rank_start + rook_file));
+            } else {
+                squares.push(destination);
+            }
+        }
+        squares
+    }
+
+    /// Whether the side to move has no legal move, being checkmated or stalemated,
+    /// or the game ended by a rule of its variant.
+    pub fn is_game_over(&self) -> bool {
+        self.legal_moves().is_empty()
+    }
+}
+
+/// Druid calls `same()` very often, so instead of comparing both FEN strings
+/// we compare the Zobrist keys (which cover pieces placement, turn, castles and
+/// en-passant) and the two move counters, which are the only other parts of the FEN,
+/// with the castling rights of Chess960 and the state of the variant. The moves played and
+/// the start position are compared too, as the same position reached by another move order
+/// is another game.
+impl Data for BoardLogic {
+    fn same(&self, other: &Self) -> bool {
+        let this_board = &self.inner_logic;
+        let other_board = &other.inner_logic;
+        this_board.zobrist() == other_board.zobrist()
+            && this_board.moves_played() == other_board.moves_played()
+            && this_board.rule_50() == other_board.rule_50()
+            && self.history == other.history
+            && self.start_fen == other.start_fen
+            && self.chess960_castling == other.chess960_castling
+            && self.variant == other.variant
+            && self.checks == other.checks
+            && self.antichess == other.antichess
+    }
+}
+
+/// Splits the field of the checks of Three-check out of a FEN : the FEN without it, and the
+/// checks given by White and by Black, none when the FEN has no such field.
+fn split_checks(fen: &str) -> Option<(String, [u8; 2])> {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    let index = match fields.iter().position(|field| field.contains('+')) {
+        Some(index) => index,
+        None => return Some((fen.to_string(), [0, 0])),
+    };
+    let field = fields.remove(index);
+    let counts: Vec<u8> = field
+        .split('+')
+        .filter(|count| !count.is_empty())
+        .map(|count| count.parse().ok())
+        .collect::<Option<_>>()?;
+    let (white, black) = match counts.as_slice() {
+        [white, black] => (*white, *black),
+        _ => return None,
+    };
+    let checks = if field.starts_with('+') {
+        [white, black]
+    } else {
+        [
+            WINNING_CHECKS.saturating_sub(white),
+            WINNING_CHECKS.saturating_sub(black),
+        ]
+    };
+    Some((fields.join(" "), checks))
+}
@@ -0,0 +1,499 @@
+use pleco::core::piece_move::{MoveFlag, PreMoveInfo};
+use pleco::core::CastleType;
+use pleco::{BitBoard, BitMove, Board, Piece, PieceType, Player, SQ};
+
+/// Number of the start positions of Chess960, numbered from 0.
+pub const START_POSITIONS: u32 = 960;
+
+/// Placements of the two knights among the five squares left by the bishops and the queen.
+const KNIGHTS_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+const KING_SIDE: usize = 0;
+const QUEEN_SIDE: usize = 1;
+
+/// The start position of the given number, from 0 to 959, in the numbering of Reinhard
+/// Scharnagl where 518 is the standard one. Its castling rights are written `KQkq`, as X-FEN
+/// does when the rooks are the outermost ones.
+pub fn start_position_fen(number: u32) -> Option<String> {
+    if number >= START_POSITIONS {
+        return None;
+    }
+    let mut pieces = [None; 8];
+    let mut rest = number as usize;
+    pieces[2 * (rest % 4) + 1] = Some('b');
+    rest /= 4;
+    pieces[2 * (rest % 4)] = Some('b');
+    rest /= 4;
+    put_on_free_square(&mut pieces, rest % 6, 'q');
+    rest /= 6;
+    let (first_knight, second_knight) = KNIGHTS_PLACEMENTS[rest];
+    // The second knight is counted among the squares left by the first one.
+    put_on_free_square(&mut pieces, second_knight, 'n');
+    put_on_free_square(&mut pieces, first_knight, 'n');
+    for piece in &['r', 'k', 'r'] {
+        put_on_free_square(&mut pieces, 0, *piece);
+    }
+    let black_pieces: String = pieces.iter().map(|piece| piece.unwrap_or('r')).collect();
+    Some(format!(
+        "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1",
+        black_pieces,
+        black_pieces.to_uppercase()
+    ))
+}
+
+fn put_on_free_square(pieces: &mut [Option<char>; 8], free_index: usize, piece: char) {
+    if let Some(square) = pieces
+        .iter_mut()
+        .filter(|square| square.is_none())
+        .nth(free_index)
+    {
+        *square = Some(piece);
+    }
+}
+
+/// Castling rights of both players, as the files of the rooks they may still castle with.
+/// The board of pleco only knows the rooks of the standard start position, so the castling
+/// of Chess960 positions is played with these rights instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CastlingRights {
+    /// By player, the file of the king side rook and of the queen side one.
+    rooks: [[Option<u8>; 2]; 2],
+}
+
+impl CastlingRights {
+    /// Reads the castling field of a FEN for the given board : `KQkq` for the outermost rooks
+    /// as in X-FEN, or the files of the rooks as in Shredder-FEN (`HAha`), both being accepted
+    /// together. None when a right has no rook of its player on the side of their king.
+    pub fn parse(field: &str, board: &Board) -> Option<Self> {
+        let mut rights = CastlingRights::default();
+        if field == "-" {
+            return Some(rights);
+        }
+        for character in field.chars() {
+            let player = if character.is_ascii_uppercase() {
+                Player::White
+            } else {
+                Player::Black
+            };
+            let king = board.king_sq(player);
+            if king.rank_idx_of_sq() != home_rank(player) {
+                return None;
+            }
+            let king_file = king.file_idx_of_sq();
+            let rook_files: Vec<u8> = (0..8)
+                .filter(|file| is_rook_of(board, player, home_square(player, *file)))
+                .collect();
+            let (side, file) = match character.to_ascii_lowercase() {
+                'k' => (
+                    KING_SIDE,
+                    rook_files.iter().rev().find(|file| **file > king_file),
+                ),
+                'q' => (
+                    QUEEN_SIDE,
+                    rook_files.iter().find(|file| **file < king_file),
+                ),
+                letter @ 'a'..='h' => {
+                    let file = letter as u8 - b'a';
+                    let side = if file > king_file {
+                        KING_SIDE
+                    } else {
+                        QUEEN_SIDE
+                    };
+                    (
+                        side,
+                        rook_files
+                            .iter()
+                            .find(|rook| **rook == file && file != king_file),
+                    )
+                }
+                _ => return None,
+            };
+            rights.rooks[player as usize][side] = Some(*file?);
+        }
+        Some(rights)
+    }
+
+    /// The castling field pleco can read when every right is the one of the standard start
+    /// position, a king on the e file and rooks on the a and h files.
+    pub fn standard_field(&self, board: &Board) -> Option<String> {
+        let mut field = String::new();
+        for player in &[Player::White, Player::Black] {
+            let on_e_file = board.king_sq(*player).file_idx_of_sq() == 4;
+            for (side, letter) in [(KING_SIDE, 'K'), (QUEEN_SIDE, 'Q')].iter() {
+                if let Some(file) = self.rooks[*player as usize][*side] {
+                    let standard_file = if *side == KING_SIDE { 7 } else { 0 };
+                    if !on_e_file || file != standard_file {
+                        return None;
+                    }
+                    field.push(match player {
+                        Player::White => *letter,
+                        Player::Black => letter.to_ascii_lowercase(),
+                    });
+                }
+            }
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        Some(field)
+    }
+
+    /// The castling field of X-FEN : `K` or `Q` for the outermost rook of a side, as in
+    /// the standard start position, and the file of the rook otherwise, such as `B` or `g`.
+    pub fn field(&self, board: &Board) -> String {
+        let mut field = String::new();
+        for player in &[Player::White, Player::Black] {
+            let rook_files: Vec<u8> = (0..8)
+                .filter(|file| is_rook_of(board, *player, home_square(*player, *file)))
+                .collect();
+            for (side, letter) in [(KING_SIDE, 'K'), (QUEEN_SIDE, 'Q')].iter() {
+                let file = match self.rooks[*player as usize][*side] {
+                    Some(file) => file,
+                    None => continue,
+                };
+                let outermost = if *side == KING_SIDE {
+                    rook_files.iter().all(|rook| *rook <= file)
+                } else {
+                    rook_files.iter().all(|rook| *rook >= file)
+                };
+                let right = if outermost {
+                    *letter
+                } else {
+                    (b'A' + file) as char
+                };
+                field.push(match player {
+                    Player::White => right,
+                    Player::Black => right.to_ascii_lowercase(),
+                });
+            }
+        }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+
+    /// Whether the player may still castle on the side, now or later.
+    pub fn can_castle(&self, player: Player, castle_type: CastleType) -> bool {
+        let side = match castle_type {
+            CastleType::KingSide => KING_SIDE,
+            CastleType::QueenSide => QUEEN_SIDE,
+        };
+        self.rooks[player as usize][side].is_some()
+    }
+
+    /// The castles the side to move may play in the position : the squares between the king
+    /// and its destination and between the rook and its destination are empty but for them,
+    /// and the king is not in check on its way nor once castled.
+    pub fn castling_moves(&self, board: &Board) -> Vec<BitMove> {
+        let player = board.turn();
+        let king = board.king_sq(player);
+        if board.in_check() || king.rank_idx_of_sq() != home_rank(player) {
+            return Vec::new();
+        }
+        let opponent_pieces = board.get_occupied_player(player.other_player());
+        let mut moves = Vec::new();
+        for side in &[KING_SIDE, QUEEN_SIDE] {
+            let rook = match self.rooks[player as usize][*side] {
+                Some(file) => home_square(player, file),
+                None => continue,
+            };
+            if !is_rook_of(board, player, rook) {
+                continue;
+            }
+            let (king_destination, rook_destination) = castled_squares(player, *side);
+            let castling_pieces = king.to_bb() | rook.to_bb();
+            let others = board.occupied() & !castling_pieces;
+            let crossed =
+                squares_between(king, king_destination) | squares_between(rook, rook_destination);
+            if (crossed & others).is_not_empty() {
+                continue;
+            }
+            let king_path = squares_between(king, king_destination).0;
+            let attacked = (0..64)
+                .filter(|square| king_path & (1 << square) != 0)
+                .any(|square| {
+                    (board.attackers_to(SQ(square), board.occupied()) & opponent_pieces)
+                        .is_not_empty()
+                });
+            if attacked {
+                continue;
+            }
+            let castle = BitMove::init(PreMoveInfo {
+                src: king,
+                dst: rook,
+                flags: MoveFlag::Castle {
+                    king_side: *side == KING_SIDE,
+                },
+            });
+            // The rook may have hidden the king from an opponent rook or queen on its rank.
+            let castled = castled_board(board, castle);
+            let castled_king = castled.king_sq(player);
+            if (castled.attackers_to(castled_king, castled.occupied()) & opponent_pieces).is_empty()
+            {
+                moves.push(castle);
+            }
+        }
+        moves
+    }
+
+    /// The rights left once the move is played on the board : none for the player once
+    /// their king moves, and none with a rook once it moves or is captured.
+    pub fn after_move(&self, board: &Board, chess_move: BitMove) -> Self {
+        let mut rights = *self;
+        let player = board.turn();
+        if chess_move.is_castle() || board.moved_piece(chess_move).type_of() == PieceType::K {
+            rights.rooks[player as usize] = [None, None];
+        }
+        for (owner, square) in &[
+            (player, chess_move.get_src()),
+            (player.other_player(), chess_move.get_dest()),
+        ] {
+            if square.rank_idx_of_sq() == home_rank(*owner) {
+                for file in rights.rooks[*owner as usize].iter_mut() {
+                    if *file == Some(square.file_idx_of_sq()) {
+                        *file = None;
+                    }
+                }
+            }
+        }
+        rights
+    }
+}
+
+/// Plays a legal move on the board. The castles pleco does not know, those of Chess960,
+/// are played by setting up the position once castled.
+pub fn apply_move(board: &mut Board, chess_move: BitMove) {
+    let castle_type = if chess_move.is_king_castle() {
+        CastleType::KingSide
+    } else {
+        CastleType::QueenSide
+    };
+    if chess_move.is_castle() && !board.can_castle(board.turn(), castle_type) {
+        *board = castled_board(board, chess_move);
+    } else {
+        board.apply_move(chess_move);
+    }
+}
+
+/// The position after a castle, encoded as pleco does with the king square as origin and
+/// the rook square as destination. The king goes to the g or c file and the rook to the f
+/// or d file, whatever their start squares.
+fn castled_board(board: &Board, castle: BitMove) -> Board {
+    let player = board.turn();
+    let side = if castle.is_king_castle() {
+        KING_SIDE
+    } else {
+        QUEEN_SIDE
+    };
+    let (king_destination, rook_destination) = castled_squares(player, side);
+    let mut pieces: Vec<Piece> = (0..64)
+        .map(|square| board.piece_at_sq(SQ(square)))
+        .collect();
+    pieces[castle.get_src().0 as usize] = Piece::None;
+    pieces[castle.get_dest().0 as usize] = Piece::None;
+    pieces[king_destination.0 as usize] = Piece::make_lossy(player, PieceType::K);
+    pieces[rook_destination.0 as usize] = Piece::make_lossy(player, PieceType::R);
+
+    let placement = fen_placement(&pieces);
+    let fen = board.fen();
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let half_moves = fields.get(4).and_then(|number| number.parse::<u32>().ok());
+    let move_number = fields.get(5).and_then(|number| number.parse::<u32>().ok());
+    let (turn, move_number) = match player {
+        Player::White => ("b", move_number.unwrap_or(1)),
+        Player::Black => ("w", move_number.unwrap_or(1) + 1),
+    };
+    let castled_fen = format!(
+        "{} {} - - {} {}",
+        placement,
+        turn,
+        half_moves.unwrap_or(0) + 1,
+        move_number
+    );
+    Board::from_fen(&castled_fen).unwrap()
+}
+
+/// The placement field of a FEN for the pieces of the 64 squares, from a1 to h8.
+pub fn fen_placement(pieces: &[Piece]) -> String {
+    let mut placement = String::new();
+    for rank in (0..8).rev() {
+        let mut empty_squares = 0;
+        for file in 0..8 {
+            match pieces[rank * 8 + file].character() {
+                Some(character) => {
+                    if empty_squares > 0 {
+                        placement.push_str(&empty_squares.to_string());
+                        empty_squares = 0;
+                    }
+                    placement.push(character);
+                }
+                None => empty_squares += 1,
+            }
+        }
+        if empty_squares > 0 {
+            placement.push_str(&empty_squares.to_string());
+        }
+        if rank > 0 {
+            placement.push('/');
+        }
+    }
+    placement
+}
+
+/// Destinations of the king and of the rook castling on the given side.
+fn castled_squares(player: Player, side: usize) -> (SQ, SQ) {
+    let (king_file, rook_file) = if side == KING_SIDE { (6, 5) } else { (2, 3) };
+    (
+        home_square(player, king_file),
+        home_square(player, rook_file),
+    )
+}
+
+/// Squares from the first to the second on their rank, both included.
+fn squares_between(first: SQ, second: SQ) -> BitBoard {
+    let (low, high) = if first.0 <= second.0 {
+        (first.0, second.0)
+    } else {
+        (second.0, first.0)
+    };
+    BitBoard((low..=high).fold(0, |squares, square| squares | 1 << square))
+}
+
+fn home_rank(player: Player) -> u8 {
+    match player {
+        Player::White => 0,
+        Player::Black => 7,
+    }
+}
+
+fn home_square(player: Player, file: u8) -> SQ {
+    SQ(home_rank(player) * 8 + file)
+}
+
+fn is_rook_of(board: &Board, player: Player, square: SQ) -> bool {
+    board.piece_at_sq(square) == Piece::make_lossy(player, PieceType::R)
+}
+
+/// Splits the castling field out of a FEN : the FEN with no castling rights, and the field.
+pub fn split_castling(fen: &str) -> Option<(String, String)> {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    let field = fields.get(2)?.to_string();
+    fields[2] = "-";
+    Some((fields.join(" "), field))
+}
+
+/// The FEN with the given castling field instead of its own.
+pub fn with_castling(fen: &str, field: &str) -> String {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() > 2 {
+        fields[2] = field;
+    }
+    fields.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::board_logic::BoardLogic;
+    use super::*;
+
+    /// Kings on the b file, rooks on the a and h files.
+    const CASTLES_FEN: &str = "rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1";
+
+    fn square(name: &str) -> SQ {
+        let bytes = name.as_bytes();
+        SQ((bytes[1] - b'1') * 8 + (bytes[0] - b'a'))
+    }
+
+    fn placement(board: &BoardLogic) -> String {
+        board.fen().split_whitespace().next().unwrap().to_string()
+    }
+
+    fn castling_field(board: &BoardLogic) -> String {
+        split_castling(&board.fen()).unwrap().1
+    }
+
+    #[test]
+    fn start_positions_follow_the_scharnagl_numbering() {
+        assert_eq!(
+            start_position_fen(518).unwrap(),
+            BoardLogic::new().start_fen
+        );
+        let back_rank = |number| {
+            let fen = start_position_fen(number).unwrap();
+            fen.split('/').next().unwrap().to_string()
+        };
+        assert_eq!(back_rank(0), "bbqnnrkr");
+        assert_eq!(back_rank(1), "bqnbnrkr");
+        assert_eq!(back_rank(959), "rkrnnqbb");
+        assert_eq!(start_position_fen(START_POSITIONS), None);
+    }
+
+    #[test]
+    fn the_king_castles_when_dropped_on_its_rook() {
+        let mut board = BoardLogic::from_fen(CASTLES_FEN).unwrap();
+        assert!(board.is_chess960());
+        // The king could also go to c1, which the drop on a1 does not mean.
+        let castle = board.dragged_move(square("b1"), square("a1")).unwrap();
+        assert!(castle.is_queen_castle());
+        board.play_move(castle);
+        assert_eq!(placement(&board), "rk5r/8/8/8/8/8/8/2KR3R");
+        assert_eq!(castling_field(&board), "kq");
+    }
+
+    #[test]
+    fn the_king_castles_when_dropped_on_its_castling_square() {
+        let mut board = BoardLogic::from_fen(CASTLES_FEN).unwrap();
+        let castle = board.dragged_move(square("b1"), square("g1")).unwrap();
+        assert!(castle.is_king_castle());
+        board.play_move(castle);
+        assert_eq!(placement(&board), "rk5r/8/8/8/8/8/8/R4RK1");
+        assert_eq!(castling_field(&board), "kq");
+    }
+
+    #[test]
+    fn the_king_dropped_back_on_its_castling_square_does_not_castle() {
+        // The king already stands on the square it castles to on the king side.
+        let board = BoardLogic::from_fen("4k3/8/8/8/8/8/8/6KR w H - 0 1").unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(board.dragged_move(square("g1"), square("g1")), None);
+        let castle = board.dragged_move(square("g1"), square("h1")).unwrap();
+        assert!(castle.is_king_castle());
+    }
+
+    #[test]
+    fn shredder_fen_is_read_and_written_as_x_fen() {
+        let board = BoardLogic::from_fen("rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1").unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(board.fen(), CASTLES_FEN);
+        // Only the castles with the outermost rooks are left to White.
+        let board = BoardLogic::from_fen("rk5r/8/8/8/8/8/8/RK5R w Hh - 0 1").unwrap();
+        assert!(board.can_castle(Player::White, CastleType::KingSide));
+        assert!(!board.can_castle(Player::White, CastleType::QueenSide));
+        assert_eq!(castling_field(&board), "Kk");
+    }
+
+    #[test]
+    fn x_fen_names_the_file_of_an_inner_rook() {
+        // White may castle on the queen side with the rook of b1, not the one of a1.
+        let fen = "4k3/8/8/8/8/8/8/RR1K3R w KB - 0 1";
+        let board = BoardLogic::from_fen(fen).unwrap();
+        assert!(board.is_chess960());
+        assert!(board.can_castle(Player::White, CastleType::QueenSide));
+        assert_eq!(board.fen(), fen);
+        let castle = board.dragged_move(square("d1"), square("b1")).unwrap();
+        assert!(castle.is_queen_castle());
+        assert_eq!(board.dragged_move(square("d1"), square("a1")), None);
+    }
+}
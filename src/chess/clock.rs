@@ -0,0 +1,411 @@
+use druid::Data;
+
+use pleco::Player;
+
+use std::time::{Duration, Instant};
+
+/// Time given after each move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBonus {
+    None,
+    /// Fischer increment : added after each move.
+    Increment(Duration),
+    /// Bronstein delay : after each move, the time spent is given back, up to the delay.
+    Delay(Duration),
+}
+
+/// A period of the game : `time` is added to the clock when it begins, and it lasts
+/// `moves` moves of each player, or until the end of the game for the last stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeStage {
+    pub moves: Option<u32>,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeControl {
+    pub stages: Vec<TimeStage>,
+    pub bonus: TimeBonus,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        TimeControl::multi_stage(vec![], time, TimeBonus::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        TimeControl::multi_stage(vec![], time, TimeBonus::Increment(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        TimeControl::multi_stage(vec![], time, TimeBonus::Delay(delay))
+    }
+
+    /// Stages of `(moves, time)`, such as 40 moves in 90 minutes, then `final_time`
+    /// for the rest of the game.
+    pub fn multi_stage(
+        stages: Vec<(u32, Duration)>,
+        final_time: Duration,
+        bonus: TimeBonus,
+    ) -> Self {
+        let mut all_stages: Vec<TimeStage> = stages
+            .into_iter()
+            .map(|(moves, time)| TimeStage {
+                moves: Some(moves),
+                time,
+            })
+            .collect();
+        all_stages.push(TimeStage {
+            moves: None,
+            time: final_time,
+        });
+        TimeControl {
+            stages: all_stages,
+            bonus,
+        }
+    }
+
+    /// Value of the PGN `TimeControl` tag, such as "40/5400:1800+30".
+    /// The standard has no notation for delays, which are left out.
+    pub fn pgn_tag(&self) -> String {
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|stage| match stage.moves {
+                Some(moves) => format!("{}/{}", moves, stage.time.as_secs()),
+                None => stage.time.as_secs().to_string(),
+            })
+            .collect();
+        let mut tag = stages.join(":");
+        if let TimeBonus::Increment(increment) = self.bonus {
+            tag.push_str(&format!("+{}", increment.as_secs()));
+        }
+        tag
+    }
+
+    fn initial_time(&self) -> Duration {
+        self.stages
+            .first()
+            .map(|stage| stage.time)
+            .unwrap_or_default()
+    }
+
+    /// Time added once the player has played the given number of moves,
+    /// when this number ends a stage.
+    fn stage_time_after(&self, moves_played: u32) -> Duration {
+        let mut stage_end = 0;
+        for (index, stage) in self.stages.iter().enumerate() {
+            match stage.moves {
+                Some(moves) => {
+                    stage_end += moves;
+                    if stage_end == moves_played {
+                        return self
+                            .stages
+                            .get(index + 1)
+                            .map(|next_stage| next_stage.time)
+                            .unwrap_or_default();
+                    }
+                }
+                None => break,
+            }
+        }
+        Duration::default()
+    }
+}
+
+/// The two clocks of a game. Times are given explicitly so that the clock does not depend
+/// on when it is looked at : the user interface gives `Instant::now()`.
+#[derive(Debug, Clone)]
+pub struct ChessClock {
+    control: TimeControl,
+    /// Remaining time of White and Black, when their turn started.
+    remaining: [Duration; 2],
+    moves_played: [u32; 2],
+    turn: Player,
+    turn_started: Option<Instant>,
+    /// Time spent in the current turn, at the last tick.
+    turn_elapsed: Duration,
+    flagged: Option<Player>,
+    /// Remaining time of the player who just moved, after each move played since the clock
+    /// was set, if it was running.
+    move_clocks: Vec<Option<Duration>>,
+}
+
+impl ChessClock {
+    /// A stopped clock, `turn` being the player who will move first.
+    pub fn new(control: TimeControl, turn: Player) -> Self {
+        let initial_time = control.initial_time();
+        ChessClock {
+            control,
+            remaining: [initial_time, initial_time],
+            moves_played: [0, 0],
+            turn,
+            turn_started: None,
+            turn_elapsed: Duration::default(),
+            flagged: None,
+            move_clocks: Vec::new(),
+        }
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.turn_started.is_some()
+    }
+
+    /// The player whose time ran out, if any.
+    pub fn flagged(&self) -> Option<Player> {
+        self.flagged
+    }
+
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// Remaining time of the player, as of the last tick.
+    pub fn remaining(&self, player: Player) -> Duration {
+        let remaining = self.remaining[player_index(player)];
+        if player == self.turn {
+            remaining.checked_sub(self.turn_elapsed).unwrap_or_default()
+        } else {
+            remaining
+        }
+    }
+
+    pub fn move_clocks(&self) -> &[Option<Duration>] {
+        &self.move_clocks
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        if self.flagged.is_none() && !self.is_running() {
+            self.turn_started = Some(now - self.turn_elapsed);
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        self.tick(now);
+        self.turn_started = None;
+    }
+
+    /// Updates the time spent in the current turn, and returns the player whose time
+    /// has just run out, if any.
+    pub fn tick(&mut self, now: Instant) -> Option<Player> {
+        let turn_started = self.turn_started?;
+        self.turn_elapsed = now.saturating_duration_since(turn_started);
+        if self.turn_elapsed >= self.remaining[player_index(self.turn)] {
+            self.turn_elapsed = self.remaining[player_index(self.turn)];
+            self.turn_started = None;
+            self.flagged = Some(self.turn);
+            return self.flagged;
+        }
+        None
+    }
+
+    /// Ends the turn of the player to move, once the move is played, and starts the clock
+    /// of the opponent if the clock is running. Returns false if the time of the player
+    /// already ran out, in which case the move must not be played.
+    pub fn press(&mut self, now: Instant) -> bool {
+        if self.flagged.is_some() {
+            return false;
+        }
+        let running = self.is_running();
+        if running && self.tick(now).is_some() {
+            return false;
+        }
+        let index = player_index(self.turn);
+        let spent = self.turn_elapsed;
+        let mut remaining = self.remaining[index] - spent;
+        self.moves_played[index] += 1;
+        if running {
+            remaining += match self.control.bonus {
+                TimeBonus::None => Duration::default(),
+                TimeBonus::Increment(increment) => increment,
+                TimeBonus::Delay(delay) => delay.min(spent),
+            };
+            remaining += self.control.stage_time_after(self.moves_played[index]);
+            self.move_clocks.push(Some(remaining));
+            self.turn_started = Some(now);
+        } else {
+            self.move_clocks.push(None);
+        }
+        self.remaining[index] = remaining;
+
+        self.turn = self.turn.other_player();
+        self.turn_elapsed = Duration::default();
+        true
+    }
+}
+
+/// Compares what is shown, not the instant the turn started.
+impl Data for ChessClock {
+    fn same(&self, other: &Self) -> bool {
+        self.remaining == other.remaining
+            && self.turn_elapsed == other.turn_elapsed
+            && self.turn == other.turn
+            && self.is_running() == other.is_running()
+            && self.flagged == other.flagged
+            && self.move_clocks.len() == other.move_clocks.len()
+            && self.control == other.control
+    }
+}
+
+fn player_index(player: Player) -> usize {
+    match player {
+        Player::White => 0,
+        Player::Black => 1,
+    }
+}
+
+/// Formats a time as `h:mm:ss`, as in the `[%clk]` comments of PGN files.
+pub fn format_clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    /// A clock started at `start`, White to move.
+    fn running_clock(control: TimeControl, start: Instant) -> ChessClock {
+        let mut clock = ChessClock::new(control, Player::White);
+        clock.start(start);
+        clock
+    }
+
+    #[test]
+    fn sudden_death_only_takes_the_time_spent() {
+        let start = Instant::now();
+        let mut clock = running_clock(TimeControl::sudden_death(seconds(60)), start);
+        assert!(clock.press(start + seconds(10)));
+        assert_eq!(clock.remaining(Player::White), seconds(50));
+        assert_eq!(clock.turn(), Player::Black);
+        assert!(clock.press(start + seconds(25)));
+        assert_eq!(clock.remaining(Player::Black), seconds(45));
+        assert_eq!(clock.move_clocks(), &[Some(seconds(50)), Some(seconds(45))]);
+    }
+
+    #[test]
+    fn fischer_increment_is_added_after_each_move() {
+        let start = Instant::now();
+        let mut clock = running_clock(TimeControl::fischer(seconds(60), seconds(5)), start);
+        assert!(clock.press(start + seconds(10)));
+        assert_eq!(clock.remaining(Player::White), seconds(55));
+        assert!(clock.press(start + seconds(11)));
+        assert_eq!(clock.remaining(Player::Black), seconds(64));
+    }
+
+    #[test]
+    fn bronstein_delay_gives_back_the_time_spent_up_to_the_delay() {
+        let start = Instant::now();
+        let mut clock = running_clock(TimeControl::bronstein(seconds(60), seconds(5)), start);
+        assert!(clock.press(start + seconds(3)));
+        assert_eq!(clock.remaining(Player::White), seconds(60));
+        assert!(clock.press(start + seconds(13)));
+        assert_eq!(clock.remaining(Player::Black), seconds(55));
+    }
+
+    #[test]
+    fn next_stage_time_is_added_when_a_stage_ends() {
+        let start = Instant::now();
+        let control =
+            TimeControl::multi_stage(vec![(2, seconds(60))], seconds(30), TimeBonus::None);
+        let mut clock = running_clock(control, start);
+        assert!(clock.press(start + seconds(10)));
+        assert_eq!(clock.remaining(Player::White), seconds(50));
+        assert!(clock.press(start + seconds(11)));
+        assert!(clock.press(start + seconds(21)));
+        assert_eq!(clock.remaining(Player::White), seconds(70));
+        assert!(clock.press(start + seconds(22)));
+        assert_eq!(clock.remaining(Player::Black), seconds(88));
+        // The last stage lasts until the end of the game.
+        assert!(clock.press(start + seconds(32)));
+        assert_eq!(clock.remaining(Player::White), seconds(60));
+    }
+
+    #[test]
+    fn flag_falls_with_every_time_bonus() {
+        let controls = vec![
+            TimeControl::sudden_death(seconds(60)),
+            TimeControl::fischer(seconds(60), seconds(5)),
+            TimeControl::bronstein(seconds(60), seconds(5)),
+            TimeControl::multi_stage(vec![(40, seconds(60))], seconds(30), TimeBonus::None),
+        ];
+        for control in controls {
+            let start = Instant::now();
+            let mut clock = running_clock(control.clone(), start);
+            assert_eq!(clock.tick(start + seconds(59)), None, "{:?}", control);
+            assert_eq!(
+                clock.tick(start + seconds(60)),
+                Some(Player::White),
+                "{:?}",
+                control
+            );
+            assert_eq!(clock.flagged(), Some(Player::White));
+            assert_eq!(clock.remaining(Player::White), Duration::default());
+            assert!(!clock.is_running());
+            // The move can no longer be played, nor the clock be restarted.
+            assert!(!clock.press(start + seconds(61)));
+            clock.start(start + seconds(62));
+            assert!(!clock.is_running());
+        }
+    }
+
+    #[test]
+    fn pressing_after_the_time_ran_out_flags_the_player() {
+        let start = Instant::now();
+        let mut clock = running_clock(TimeControl::fischer(seconds(60), seconds(5)), start);
+        assert!(!clock.press(start + seconds(61)));
+        assert_eq!(clock.flagged(), Some(Player::White));
+        assert!(clock.move_clocks().is_empty());
+    }
+
+    #[test]
+    fn stopped_clock_only_counts_moves() {
+        let start = Instant::now();
+        let mut clock =
+            ChessClock::new(TimeControl::fischer(seconds(60), seconds(5)), Player::White);
+        assert!(clock.press(start + seconds(10)));
+        assert_eq!(clock.remaining(Player::White), seconds(60));
+        assert_eq!(clock.turn(), Player::Black);
+        assert_eq!(clock.move_clocks(), &[None]);
+    }
+
+    #[test]
+    fn paused_clock_keeps_the_time_spent() {
+        let start = Instant::now();
+        let mut clock = running_clock(TimeControl::sudden_death(seconds(60)), start);
+        clock.pause(start + seconds(10));
+        assert_eq!(clock.remaining(Player::White), seconds(50));
+        clock.start(start + seconds(100));
+        assert!(clock.press(start + seconds(105)));
+        assert_eq!(clock.remaining(Player::White), seconds(45));
+    }
+
+    #[test]
+    fn time_controls_are_written_as_pgn_tags() {
+        let control = TimeControl::multi_stage(
+            vec![(40, seconds(5400))],
+            seconds(1800),
+            TimeBonus::Increment(seconds(30)),
+        );
+        assert_eq!(control.pgn_tag(), "40/5400:1800+30");
+        assert_eq!(
+            TimeControl::bronstein(seconds(300), seconds(2)).pgn_tag(),
+            "300"
+        );
+        assert_eq!(format_clock(seconds(3723)), "1:02:03");
+        assert_eq!(format_clock(seconds(59)), "0:00:59");
+    }
+}
@@ -0,0 +1,166 @@
+use pleco::core::{sq::SQ, PieceType, Player};
+use pleco::BitMove;
+
+use super::antichess::{is_capture, promotion_type};
+use super::variant::{Variant, VariantWin};
+use super::BoardLogic;
+
+const DESCRIBED_PIECES_TYPES: [PieceType; 6] = [
+    PieceType::K,
+    PieceType::Q,
+    PieceType::R,
+    PieceType::B,
+    PieceType::N,
+    PieceType::P,
+];
+
+/// The pieces of one type owned by a player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecesGroup {
+    pub piece_type: PieceType,
+    pub squares: Vec<SQ>,
+}
+
+/// What a player can do, or not, when it is its turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TurnState {
+    ToMove,
+    InCheck,
+    Checkmated,
+    Stalemated,
+    /// The game ended by a rule of its variant.
+    VariantEnded(VariantWin),
+}
+
+/// Plain words view of a position, the wording itself being left to the user interface
+/// so that it can be translated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionDescription {
+    pub white_pieces: Vec<PiecesGroup>,
+    pub black_pieces: Vec<PiecesGroup>,
+    pub turn: Player,
+    pub turn_state: TurnState,
+    /// Checks given by White and by Black, in Three-check.
+    pub checks: Option<[u8; 2]>,
+}
+
+/// Describes the position : the pieces of each side, then the side to move
+/// and whether it is in check, mated or stalemated, or the game is won by a rule
+/// of its variant.
+pub fn describe_position(board: &BoardLogic) -> PositionDescription {
+    PositionDescription {
+        white_pieces: describe_pieces(board, Player::White),
+        black_pieces: describe_pieces(board, Player::Black),
+        turn: board.turn(),
+        turn_state: turn_state(board),
+        checks: if board.variant == Variant::ThreeCheck {
+            Some(board.checks)
+        } else {
+            None
+        },
+    }
+}
+
+fn describe_pieces(board: &BoardLogic, player: Player) -> Vec<PiecesGroup> {
+    DESCRIBED_PIECES_TYPES
+        .iter()
+        .map(|piece_type| PiecesGroup {
+            piece_type: *piece_type,
+            squares: (0..64)
+                .map(SQ)
+                .filter(|square| {
+                    let piece = board.piece_at(*square);
+                    piece.player() == Some(player) && piece.type_of() == *piece_type
+                })
+                .collect(),
+        })
+        .filter(|group| !group.squares.is_empty())
+        .collect()
+}
+
+fn turn_state(board: &BoardLogic) -> TurnState {
+    if let Some(win) = board.variant_win() {
+        TurnState::VariantEnded(win)
+    } else if board.is_checkmate() {
+        TurnState::Checkmated
+    } else if board.is_stalemate() {
+        TurnState::Stalemated
+    } else if board.in_check() {
+        TurnState::InCheck
+    } else {
+        TurnState::ToMove
+    }
+}
+
+/// How the pieces moved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveKind {
+    KingSideCastle,
+    QueenSideCastle,
+    Quiet {
+        piece_type: PieceType,
+        origin: SQ,
+        destination: SQ,
+    },
+    Capture {
+        piece_type: PieceType,
+        origin: SQ,
+        captured_piece_type: PieceType,
+        captured_square: SQ,
+    },
+    EnPassant {
+        origin: SQ,
+        captured_square: SQ,
+        destination: SQ,
+    },
+}
+
+/// A legal move as it could be spoken : "White knight from g1 to f3.",
+/// "Black pawn on e5 takes white pawn on d4, check."
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveAnnouncement {
+    pub player: Player,
+    pub kind: MoveKind,
+    pub promotion: Option<PieceType>,
+    /// State of the opponent once the move is played.
+    pub opponent_state: TurnState,
+}
+
+/// Announces a legal move of the given position.
+pub fn announce_move(board: &BoardLogic, chess_move: BitMove) -> MoveAnnouncement {
+    let origin = chess_move.get_src();
+    let destination = chess_move.get_dest();
+    let kind = if chess_move.is_king_castle() {
+        MoveKind::KingSideCastle
+    } else if chess_move.is_queen_castle() {
+        MoveKind::QueenSideCastle
+    } else if chess_move.is_en_passant() {
+        MoveKind::EnPassant {
+            origin,
+            captured_square: SQ(origin.rank_idx_of_sq() * 8 + destination.file_idx_of_sq()),
+            destination,
+        }
+    } else if is_capture(chess_move) {
+        MoveKind::Capture {
+            piece_type: board.piece_at(origin).type_of(),
+            origin,
+            captured_piece_type: board.piece_at(destination).type_of(),
+            captured_square: destination,
+        }
+    } else {
+        MoveKind::Quiet {
+            piece_type: board.piece_at(origin).type_of(),
+            origin,
+            destination,
+        }
+    };
+
+    let mut board_after_move = board.clone();
+    board_after_move.play_move(chess_move);
+    MoveAnnouncement {
+        player: board.turn(),
+        kind,
+        promotion: promotion_type(chess_move),
+        opponent_state: turn_state(&board_after_move),
+    }
+}
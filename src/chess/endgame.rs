@@ -0,0 +1,558 @@
+use druid::Data;
+
+use pleco::core::PieceType;
+use pleco::{Player, SQ};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use super::pgn::{PgnGame, VARIANT_TAG};
+use super::BoardLogic;
+
+/// Tag of the goal of an endgame exercise : `win`, `draw` or `mate in N`.
+pub const GOAL_TAG: &str = "Goal";
+/// Tag of the number of moves the solver has to reach the goal.
+pub const MOVE_LIMIT_TAG: &str = "MoveLimit";
+/// Moves given to win or to hold the draw when the exercise sets no limit.
+pub const DEFAULT_MOVE_LIMIT: u32 = 50;
+
+/// What the solver of an endgame exercise must achieve against the computer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndgameGoal {
+    /// Checkmate the computer.
+    Win,
+    /// Reach a draw, or play the moves of the limit without being checkmated.
+    Draw,
+    /// Checkmate the computer in at most this many moves.
+    MateIn(u32),
+}
+
+impl EndgameGoal {
+    /// Reads the value of the `Goal` tag, whatever its case.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        match text.as_str() {
+            "win" => Some(EndgameGoal::Win),
+            "draw" => Some(EndgameGoal::Draw),
+            _ => text
+                .strip_prefix("mate in")
+                .and_then(|moves| moves.trim().parse().ok())
+                .filter(|moves| *moves > 0)
+                .map(EndgameGoal::MateIn),
+        }
+    }
+}
+
+impl fmt::Display for EndgameGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndgameGoal::Win => write!(f, "win"),
+            EndgameGoal::Draw => write!(f, "draw"),
+            EndgameGoal::MateIn(moves) => write!(f, "mate in {}", moves),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EndgameError {
+    InvalidFen(String),
+    NoGoal,
+    InvalidGoal(String),
+    InvalidMoveLimit(String),
+    /// The `Variant` tag tells rules the computer does not play by.
+    UnknownVariant(String),
+}
+
+impl fmt::Display for EndgameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndgameError::InvalidFen(fen) => write!(f, "'{}' is not a valid position.", fen),
+            EndgameError::NoGoal => write!(f, "The exercise has no {} tag.", GOAL_TAG),
+            EndgameError::InvalidGoal(goal) => write!(
+                f,
+                "'{}' is not a goal : win, draw or mate in N are expected.",
+                goal
+            ),
+            EndgameError::InvalidMoveLimit(limit) => {
+                write!(f, "'{}' is not a number of moves.", limit)
+            }
+            EndgameError::UnknownVariant(variant) => {
+                write!(f, "The computer does not play {}.", variant)
+            }
+        }
+    }
+}
+
+/// A position to play against the computer, read from a PGN game : the side to move
+/// in the start position is the solver, and the `Goal` tag tells what they must achieve.
+/// The moves of the game, if any, are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndgameExercise {
+    /// The game it was read from, with its tags.
+    pub game: PgnGame,
+    pub goal: EndgameGoal,
+    /// Number of moves of the solver to reach the goal.
+    pub move_limit: u32,
+}
+
+impl EndgameExercise {
+    pub fn from_pgn_game(game: PgnGame) -> Result<Self, EndgameError> {
+        if game.start_board().is_none() {
+            return Err(EndgameError::InvalidFen(game.start_fen().to_string()));
+        }
+        if !game.is_known_to_engines() {
+            let variant = game.tag(VARIANT_TAG).unwrap_or_default();
+            return Err(EndgameError::UnknownVariant(variant.to_string()));
+        }
+        let goal_tag = game.tag(GOAL_TAG).ok_or(EndgameError::NoGoal)?;
+        let goal = EndgameGoal::parse(goal_tag)
+            .ok_or_else(|| EndgameError::InvalidGoal(goal_tag.to_string()))?;
+        let move_limit = match (goal, game.tag(MOVE_LIMIT_TAG)) {
+            // The mate gives its own limit.
+            (EndgameGoal::MateIn(moves), _) => moves,
+            (_, Some(limit)) => limit
+                .trim()
+                .parse()
+                .ok()
+                .filter(|limit| *limit > 0)
+                .ok_or_else(|| EndgameError::InvalidMoveLimit(limit.to_string()))?,
+            (_, None) => DEFAULT_MOVE_LIMIT,
+        };
+        Ok(EndgameExercise {
+            game,
+            goal,
+            move_limit,
+        })
+    }
+
+    /// The start position, with the castling of Chess960 if the game follows its rules.
+    pub fn start_position(&self) -> BoardLogic {
+        self.game.start_board().unwrap()
+    }
+
+    /// The side to move in the start position.
+    pub fn solver(&self) -> Player {
+        self.start_position().turn()
+    }
+
+    pub fn title(&self) -> String {
+        self.game.title()
+    }
+
+    /// Moves the solver played on the board since the start position.
+    pub fn solver_moves(&self, board: &BoardLogic) -> u32 {
+        ((board.history.len() + 1) / 2) as u32
+    }
+
+    /// The outcome of the attempt played on the board, none while it goes on.
+    /// A mate or a draw ends it at once ; otherwise a win is missed once the solver has played
+    /// all the moves of the limit, and a draw is held once the computer replied to the last one.
+    pub fn judge(&self, board: &BoardLogic) -> Option<EndgameOutcome> {
+        let solver = self.solver();
+        let solver_to_move = board.turn() == solver;
+        if board.is_checkmate() {
+            return Some(if solver_to_move {
+                EndgameOutcome::Lost
+            } else {
+                EndgameOutcome::Reached
+            });
+        }
+        if let Some(draw) = draw_kind(board) {
+            return Some(match self.goal {
+                EndgameGoal::Draw => EndgameOutcome::Reached,
+                EndgameGoal::Win | EndgameGoal::MateIn(_) => EndgameOutcome::Drawn(draw),
+            });
+        }
+        let limit_played = self.solver_moves(board) >= self.move_limit;
+        match self.goal {
+            EndgameGoal::Draw if limit_played && solver_to_move => Some(EndgameOutcome::Reached),
+            EndgameGoal::Win | EndgameGoal::MateIn(_) if limit_played && !solver_to_move => {
+                Some(EndgameOutcome::OutOfMoves)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Endgame exercises of a PGN file, with the errors of the games which are not valid exercises.
+pub fn endgame_exercises_from_games(
+    games: Vec<PgnGame>,
+) -> (Vec<EndgameExercise>, Vec<(usize, EndgameError)>) {
+    let mut exercises = Vec::new();
+    let mut errors = Vec::new();
+    for (index, game) in games.into_iter().enumerate() {
+        match EndgameExercise::from_pgn_game(game) {
+            Ok(exercise) => exercises.push(exercise),
+            Err(err) => errors.push((index, err)),
+        }
+    }
+    (exercises, errors)
+}
+
+/// Why a game is drawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawKind {
+    Stalemate,
+    /// Neither side has pieces enough to checkmate.
+    InsufficientMaterial,
+    /// A hundred plies without capture nor pawn move.
+    FiftyMoves,
+    /// The same position for the third time.
+    Repetition,
+}
+
+/// How an attempt at an endgame exercise ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndgameOutcome {
+    Reached,
+    /// The solver was checkmated.
+    Lost,
+    /// The game was drawn while the solver had to win.
+    Drawn(DrawKind),
+    /// The solver played all the moves of the limit without winning.
+    OutOfMoves,
+}
+
+/// Why the game on the board is drawn, if it is.
+pub fn draw_kind(board: &BoardLogic) -> Option<DrawKind> {
+    if board.is_stalemate() {
+        Some(DrawKind::Stalemate)
+    } else if insufficient_material(board) {
+        Some(DrawKind::InsufficientMaterial)
+    } else if board.half_moves() >= 100 {
+        Some(DrawKind::FiftyMoves)
+    } else if repetitions(board) >= 3 {
+        Some(DrawKind::Repetition)
+    } else {
+        None
+    }
+}
+
+/// Whether only kings are left, with at most one minor piece or with bishops all on
+/// squares of the same colour.
+fn insufficient_material(board: &BoardLogic) -> bool {
+    let squares_of = |piece_type: PieceType| -> u64 {
+        (0..64u8)
+            .filter(|index| board.piece_at(SQ(*index)).type_of() == piece_type)
+            .fold(0, |squares, index| squares | 1 << index)
+    };
+    let count = |piece_type: PieceType| squares_of(piece_type).count_ones();
+    if count(PieceType::P) + count(PieceType::R) + count(PieceType::Q) > 0 {
+        return false;
+    }
+    if count(PieceType::N) + count(PieceType::B) <= 1 {
+        return true;
+    }
+    if count(PieceType::N) > 0 {
+        return false;
+    }
+    let bishops = squares_of(PieceType::B);
+    const DARK_SQUARES: u64 = 0xAA55_AA55_AA55_AA55;
+    bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0
+}
+
+/// How many times the current position of the board occurred since its start position.
+fn repetitions(board: &BoardLogic) -> usize {
+    let mut replayed = board.start_position();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    *occurrences.entry(replayed.position_key()).or_insert(0) += 1;
+    for chess_move in &board.history {
+        replayed.play_move(*chess_move);
+        *occurrences.entry(replayed.position_key()).or_insert(0) += 1;
+    }
+    occurrences.get(&board.position_key()).copied().unwrap_or(1)
+}
+
+/// A series of endgame exercises played against the computer, one attempt at a time.
+#[derive(Debug, Clone)]
+pub struct EndgameDrill {
+    exercises: Arc<Vec<EndgameExercise>>,
+    index: usize,
+    /// How the current attempt ended, none while it goes on.
+    outcome: Option<EndgameOutcome>,
+    reached: u32,
+    attempts: u32,
+}
+
+impl EndgameDrill {
+    /// Starts with the first exercise, which must exist.
+    pub fn new(exercises: Arc<Vec<EndgameExercise>>) -> Self {
+        EndgameDrill {
+            exercises,
+            index: 0,
+            outcome: None,
+            reached: 0,
+            attempts: 0,
+        }
+    }
+
+    pub fn exercise(&self) -> &EndgameExercise {
+        &self.exercises[self.index]
+    }
+
+    /// Number of the current exercise, from 1, and the number of exercises.
+    pub fn position(&self) -> (usize, usize) {
+        (self.index + 1, self.exercises.len())
+    }
+
+    pub fn outcome(&self) -> Option<EndgameOutcome> {
+        self.outcome
+    }
+
+    pub fn is_attempt_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Attempts ended so far, and those reaching their goal.
+    pub fn score(&self) -> (u32, u32) {
+        (self.reached, self.attempts)
+    }
+
+    /// Whether the computer should play the next move of the attempt.
+    pub fn computer_to_move(&self, board: &BoardLogic) -> bool {
+        !self.is_attempt_over() && board.turn() != self.exercise().solver()
+    }
+
+    /// Judges the board after a move of the attempt, and records its outcome once it ends.
+    pub fn judge(&mut self, board: &BoardLogic) -> Option<EndgameOutcome> {
+        if self.is_attempt_over() {
+            return self.outcome;
+        }
+        self.outcome = self.exercise().judge(board);
+        if let Some(outcome) = self.outcome {
+            self.attempts += 1;
+            if outcome == EndgameOutcome::Reached {
+                self.reached += 1;
+            }
+        }
+        self.outcome
+    }
+
+    /// Plays the current exercise again.
+    pub fn retry(&mut self) {
+        self.outcome = None;
+    }
+
+    /// Moves on to the next exercise, back to the first one after the last.
+    pub fn next_exercise(&mut self) {
+        self.index = (self.index + 1) % self.exercises.len();
+        self.outcome = None;
+    }
+
+    /// Whether the user may move : on the turn of the solver, while the attempt goes on.
+    pub fn solver_to_move(&self, board: &BoardLogic) -> bool {
+        !self.is_attempt_over() && board.turn() == self.exercise().solver()
+    }
+}
+
+impl Data for EndgameDrill {
+    fn same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.exercises, &other.exercises)
+            && self.index == other.index
+            && self.outcome == other.outcome
+            && self.reached == other.reached
+            && self.attempts == other.attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::board_logic::STANDARD_START_FEN;
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    /// White mates with `b1b8` after the only reply of black, `h8g8`.
+    const LADDER_MATE: &str = "7k/8/6K1/8/8/8/8/1Q6 b - - 0 1";
+    /// White stalemates with `f1f7`.
+    const STALEMATE_TRAP: &str = "7k/8/6K1/8/8/8/8/5Q2 w - - 0 1";
+    const ROOK_ENDGAME: &str = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+
+    fn exercise(fen: &str, tags: &[(&str, &str)]) -> Result<EndgameExercise, EndgameError> {
+        let mut text = format!("[FEN \"{}\"]\n", fen);
+        for (name, value) in tags {
+            text.push_str(&format!("[{} \"{}\"]\n", name, value));
+        }
+        text.push_str("\n*\n");
+        EndgameExercise::from_pgn_game(parse_pgn(&text).unwrap().remove(0))
+    }
+
+    fn played(fen: &str, uci_moves: &[&str]) -> BoardLogic {
+        let mut board = BoardLogic::from_fen(fen).unwrap();
+        for uci in uci_moves {
+            let chess_move = find_uci_move(&board, uci).unwrap();
+            board.play_move(chess_move);
+        }
+        board
+    }
+
+    #[test]
+    fn goals_are_parsed_whatever_their_case() {
+        assert_eq!(EndgameGoal::parse("Win"), Some(EndgameGoal::Win));
+        assert_eq!(EndgameGoal::parse(" DRAW "), Some(EndgameGoal::Draw));
+        assert_eq!(
+            EndgameGoal::parse("Mate in 3"),
+            Some(EndgameGoal::MateIn(3))
+        );
+        assert_eq!(
+            EndgameGoal::parse("mate in12"),
+            Some(EndgameGoal::MateIn(12))
+        );
+        assert_eq!(EndgameGoal::parse("mate in 0"), None);
+        assert_eq!(EndgameGoal::parse("mate in two"), None);
+        assert_eq!(EndgameGoal::parse("lose"), None);
+        for goal in &[EndgameGoal::Win, EndgameGoal::Draw, EndgameGoal::MateIn(4)] {
+            assert_eq!(EndgameGoal::parse(&goal.to_string()), Some(*goal));
+        }
+    }
+
+    #[test]
+    fn move_limit_defaults_and_is_validated() {
+        let win = exercise(ROOK_ENDGAME, &[(GOAL_TAG, "win")]).unwrap();
+        assert_eq!(win.move_limit, DEFAULT_MOVE_LIMIT);
+        assert_eq!(win.solver(), Player::White);
+        let limited = exercise(
+            ROOK_ENDGAME,
+            &[(GOAL_TAG, "draw"), (MOVE_LIMIT_TAG, " 12 ")],
+        );
+        assert_eq!(limited.unwrap().move_limit, 12);
+        for limit in &["0", "ten", "-3"] {
+            assert_eq!(
+                exercise(ROOK_ENDGAME, &[(GOAL_TAG, "win"), (MOVE_LIMIT_TAG, limit)]),
+                Err(EndgameError::InvalidMoveLimit(limit.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn mate_goals_use_their_own_limit() {
+        let mate = exercise(
+            ROOK_ENDGAME,
+            &[(GOAL_TAG, "mate in 2"), (MOVE_LIMIT_TAG, "10")],
+        );
+        assert_eq!(mate.unwrap().move_limit, 2);
+        // Even an invalid limit does not matter.
+        let mate = exercise(
+            ROOK_ENDGAME,
+            &[(GOAL_TAG, "mate in 2"), (MOVE_LIMIT_TAG, "x")],
+        );
+        assert_eq!(mate.unwrap().move_limit, 2);
+    }
+
+    #[test]
+    fn invalid_exercises_are_reported() {
+        assert_eq!(exercise(ROOK_ENDGAME, &[]), Err(EndgameError::NoGoal));
+        assert_eq!(
+            exercise(ROOK_ENDGAME, &[(GOAL_TAG, "survive")]),
+            Err(EndgameError::InvalidGoal("survive".to_string()))
+        );
+        assert_eq!(
+            exercise("8/8/8 w - - 0 1", &[(GOAL_TAG, "win")]),
+            Err(EndgameError::InvalidFen("8/8/8 w - - 0 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn checkmates_end_the_attempt() {
+        let win = exercise("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1", &[(GOAL_TAG, "win")]).unwrap();
+        let board = played(win.game.start_fen(), &["b1b8"]);
+        assert_eq!(win.judge(&board), Some(EndgameOutcome::Reached));
+
+        let draw = exercise(LADDER_MATE, &[(GOAL_TAG, "draw")]).unwrap();
+        assert_eq!(draw.solver(), Player::Black);
+        assert_eq!(draw.judge(&played(LADDER_MATE, &["h8g8"])), None);
+        let board = played(LADDER_MATE, &["h8g8", "b1b8"]);
+        assert_eq!(draw.judge(&board), Some(EndgameOutcome::Lost));
+    }
+
+    #[test]
+    fn draws_reach_or_miss_the_goal() {
+        let win = exercise(STALEMATE_TRAP, &[(GOAL_TAG, "win")]).unwrap();
+        let board = played(STALEMATE_TRAP, &["f1f7"]);
+        assert_eq!(draw_kind(&board), Some(DrawKind::Stalemate));
+        assert_eq!(
+            win.judge(&board),
+            Some(EndgameOutcome::Drawn(DrawKind::Stalemate))
+        );
+
+        let fen = "7k/8/8/8/8/8/1r6/K7 w - - 0 1";
+        let board = played(fen, &["a1b2"]);
+        let draw = exercise(fen, &[(GOAL_TAG, "draw")]).unwrap();
+        assert_eq!(draw.judge(&board), Some(EndgameOutcome::Reached));
+        let mate = exercise(fen, &[(GOAL_TAG, "mate in 3")]).unwrap();
+        assert_eq!(
+            mate.judge(&board),
+            Some(EndgameOutcome::Drawn(DrawKind::InsufficientMaterial))
+        );
+    }
+
+    #[test]
+    fn fifty_quiet_moves_draw() {
+        let board = played("4k3/8/8/8/8/8/8/R3K3 w - - 59 80", &["a1a2"]);
+        assert_eq!(draw_kind(&board), None);
+        let board = played("4k3/8/8/8/8/8/8/R3K3 w - - 99 80", &["a1a2"]);
+        assert_eq!(draw_kind(&board), Some(DrawKind::FiftyMoves));
+    }
+
+    #[test]
+    fn a_win_is_missed_once_the_limit_is_played() {
+        let win = exercise(ROOK_ENDGAME, &[(GOAL_TAG, "win"), (MOVE_LIMIT_TAG, "2")]).unwrap();
+        assert_eq!(win.judge(&played(ROOK_ENDGAME, &["a1a7"])), None);
+        assert_eq!(win.judge(&played(ROOK_ENDGAME, &["a1a7", "e8d8"])), None);
+        let board = played(ROOK_ENDGAME, &["a1a7", "e8d8", "e1e2"]);
+        assert_eq!(win.solver_moves(&board), 2);
+        assert_eq!(win.judge(&board), Some(EndgameOutcome::OutOfMoves));
+
+        let mate = exercise(
+            ROOK_ENDGAME,
+            &[(GOAL_TAG, "mate in 1"), (MOVE_LIMIT_TAG, "9")],
+        );
+        let board = played(ROOK_ENDGAME, &["a1a7"]);
+        assert_eq!(
+            mate.unwrap().judge(&board),
+            Some(EndgameOutcome::OutOfMoves)
+        );
+    }
+
+    #[test]
+    fn a_draw_is_held_once_the_computer_replied_to_the_last_move() {
+        let fen = "4k3/8/8/8/8/8/8/R3K3 b - - 0 1";
+        let draw = exercise(fen, &[(GOAL_TAG, "draw"), (MOVE_LIMIT_TAG, "1")]).unwrap();
+        let board = played(fen, &["e8d8"]);
+        assert_eq!(draw.solver_moves(&board), 1);
+        assert_eq!(draw.judge(&board), None);
+        let board = played(fen, &["e8d8", "a1a7"]);
+        assert_eq!(draw.judge(&board), Some(EndgameOutcome::Reached));
+    }
+
+    #[test]
+    fn insufficient_material_allows_bishops_of_one_colour() {
+        let insufficient = |fen: &str| insufficient_material(&BoardLogic::from_fen(fen).unwrap());
+        assert!(insufficient("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+        // c1 and f4, then c1 and h6 of the other side : all dark squares.
+        assert!(insufficient("4k3/8/8/8/5B2/8/8/2B1K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/7b/8/8/8/8/2B1K3 w - - 0 1"));
+        // c1 is dark, f1 is light.
+        assert!(!insufficient("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert!(!insufficient(ROOK_ENDGAME));
+    }
+
+    #[test]
+    fn positions_are_repeated_from_the_start_position() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        assert_eq!(repetitions(&BoardLogic::new()), 1);
+        let board = played(STANDARD_START_FEN, &shuffle);
+        assert_eq!(repetitions(&board), 2);
+        assert_eq!(draw_kind(&board), None);
+        let twice: Vec<&str> = shuffle.iter().chain(shuffle.iter()).copied().collect();
+        let board = played(STANDARD_START_FEN, &twice);
+        assert_eq!(repetitions(&board), 3);
+        assert_eq!(draw_kind(&board), Some(DrawKind::Repetition));
+    }
+}
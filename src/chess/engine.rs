@@ -0,0 +1,208 @@
+mod internal_search;
+mod uci;
+
+use pleco::{BitMove, Player};
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::san::{move_to_san, PieceLetters};
+use super::BoardLogic;
+
+/// Evaluation of a position, from the point of view of White.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Number of moves before mate, negative when White is mated.
+    Mate(i32),
+}
+
+impl Score {
+    /// Converts a score given from the point of view of the side to move, as engines do.
+    fn for_white(self, turn: Player) -> Score {
+        match (turn, self) {
+            (Player::White, _) => self,
+            (Player::Black, Score::Centipawns(centipawns)) => Score::Centipawns(-centipawns),
+            (Player::Black, Score::Mate(moves)) => Score::Mate(-moves),
+        }
+    }
+
+    /// The score from the point of view of the player, positive when they are winning.
+    pub fn for_player(self, player: Player) -> Score {
+        // Seeing the score of White from the side of Black is the same change of sign.
+        self.for_white(player)
+    }
+
+    /// A value to compare scores, mates being worth more than any material advantage,
+    /// and quicker mates more than slower ones.
+    pub fn as_centipawns(&self) -> i32 {
+        const MATE_VALUE: i32 = 100_000;
+        match *self {
+            Score::Centipawns(centipawns) => centipawns,
+            Score::Mate(moves) if moves > 0 => MATE_VALUE - moves,
+            Score::Mate(moves) => -MATE_VALUE - moves,
+        }
+    }
+}
+
+/// Written as "+0.35" in pawns, or "#3" and "#-3" for mates.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Centipawns(centipawns) => {
+                write!(f, "{:+.2}", f64::from(*centipawns) / 100.0)
+            }
+            Score::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+/// A principal variation found by the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisLine {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub moves: Vec<BitMove>,
+}
+
+/// The best lines found so far for the position, the best one first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisUpdate {
+    pub fen: String,
+    pub lines: Vec<AnalysisLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisEvent {
+    Update(AnalysisUpdate),
+    /// The engine could not be started or stopped answering.
+    Error(String),
+}
+
+/// The engine analysing positions : the search of the application, or an external
+/// program speaking the UCI protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineKind {
+    Internal,
+    Uci(PathBuf),
+}
+
+/// A running analysis, sending its results as they come until it is stopped or dropped.
+pub struct Analysis {
+    stop: Arc<AtomicBool>,
+    uci_process: Option<uci::UciProcess>,
+}
+
+impl Analysis {
+    /// Analyses the position, giving the `multi_pv` best lines to `send` from another thread.
+    pub fn start(
+        engine: &EngineKind,
+        fen: &str,
+        multi_pv: usize,
+        send: impl Fn(AnalysisEvent) + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut uci_process = None;
+        match engine {
+            EngineKind::Internal => {
+                let fen = fen.to_string();
+                let stop = stop.clone();
+                thread::spawn(move || internal_search::analyse(&fen, multi_pv, &stop, send));
+            }
+            EngineKind::Uci(path) => match uci::UciProcess::start(path, fen, multi_pv, send) {
+                Ok(process) => uci_process = Some(process),
+                Err((err, send)) => send(AnalysisEvent::Error(format!(
+                    "Could not start the engine {:?} : {}",
+                    path, err
+                ))),
+            },
+        }
+        Analysis { stop, uci_process }
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(process) = self.uci_process.take() {
+            process.quit();
+        }
+    }
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// How long a position is searched when its lines are needed at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchLimit {
+    pub depth: u32,
+    pub time: Duration,
+}
+
+/// Searches the position until every line reaches the depth of the limit, its time is over,
+/// or the engine has nothing left to search, and gives the best lines found, if any.
+/// The calling thread is blocked meanwhile.
+pub fn analyse_position(
+    engine: &EngineKind,
+    fen: &str,
+    multi_pv: usize,
+    limit: SearchLimit,
+) -> Result<Vec<AnalysisLine>, String> {
+    let (sender, receiver) = mpsc::channel();
+    let _analysis = Analysis::start(engine, fen, multi_pv, move |event| {
+        // The receiver is gone once enough lines were found.
+        let _ = sender.send(event);
+    });
+    let deadline = Instant::now() + limit.time;
+    let mut lines = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok(AnalysisEvent::Update(update)) => {
+                lines = update.lines;
+                if !lines.is_empty() && lines.iter().all(|line| line.depth >= limit.depth) {
+                    break;
+                }
+            }
+            Ok(AnalysisEvent::Error(message)) => return Err(message),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(lines)
+}
+
+/// The moves of a line in algebraic notation, numbered, such as "12... Nf6 13. e5".
+pub fn format_line(board: &BoardLogic, moves: &[BitMove], letters: &PieceLetters) -> String {
+    let mut board = board.clone();
+    let mut words = Vec::new();
+    let fen_fields: Vec<String> = board.fen().split_whitespace().map(String::from).collect();
+    let mut move_number: u32 = fen_fields
+        .get(5)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(1);
+    for (index, chess_move) in moves.iter().enumerate() {
+        let is_legal = board.legal_moves().contains(chess_move);
+        if !is_legal {
+            break;
+        }
+        match board.turn() {
+            Player::White => words.push(format!("{}.", move_number)),
+            Player::Black if index == 0 => words.push(format!("{}...", move_number)),
+            Player::Black => {}
+        }
+        words.push(move_to_san(&board, *chess_move, letters));
+        if board.turn() == Player::Black {
+            move_number += 1;
+        }
+        board.play_move(*chess_move);
+    }
+    words.join(" ")
+}
@@ -0,0 +1,190 @@
+use pleco::core::score::PAWN_EG;
+use pleco::{BitMove, Board, Player};
+
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{AnalysisEvent, AnalysisLine, AnalysisUpdate, Score};
+use crate::chess::BoardLogic;
+
+/// Deeper searches take too long with this simple search.
+const MAX_DEPTH: u32 = 7;
+/// Score of a mate on the board, mates further away being worth one less per ply.
+const MATE_SCORE: i32 = 30_000;
+/// Scores above this are mates.
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1_000;
+/// The stop flag is read every this many nodes.
+const STOP_CHECK_NODES: u64 = 1_024;
+/// Non-pawn material of both sides from which the middle game values are used alone,
+/// and below which the end game values are, as in Stockfish.
+const MIDGAME_MATERIAL: i32 = 15_258;
+const ENDGAME_MATERIAL: i32 = 3_915;
+
+struct Search<'a> {
+    board: Board,
+    nodes: u64,
+    stop: &'a AtomicBool,
+}
+
+/// Iterative deepening alpha-beta search over the evaluation of pleco. Every root move
+/// is searched with a full window, so that the scores of the `multi_pv` best lines are exact.
+pub fn analyse(
+    fen: &str,
+    multi_pv: usize,
+    stop: &AtomicBool,
+    send: impl Fn(AnalysisEvent) + Send + 'static,
+) {
+    // The castles of Chess960 are left out of the search, which plays its moves on pleco.
+    let board = match BoardLogic::from_fen(fen)
+        .as_ref()
+        .and_then(BoardLogic::pleco_board)
+    {
+        Some(board) => board.clone(),
+        None => return send(AnalysisEvent::Error(format!("Invalid position {}", fen))),
+    };
+    let turn = board.turn();
+    let root_moves: Vec<BitMove> = board.generate_moves().iter().cloned().collect();
+    let mut search = Search {
+        board,
+        nodes: 0,
+        stop,
+    };
+
+    for depth in 1..=MAX_DEPTH {
+        let mut results = Vec::new();
+        for root_move in &root_moves {
+            search.board.apply_move(*root_move);
+            let mut line = vec![*root_move];
+            let score = -search.negamax(depth - 1, -MATE_SCORE - 1, MATE_SCORE + 1, 1, &mut line);
+            search.board.undo_move();
+            if search.stopped() {
+                return;
+            }
+            results.push((score, line));
+        }
+        results.sort_by_key(|(score, _)| Reverse(*score));
+
+        let lines = results
+            .into_iter()
+            .take(multi_pv)
+            .map(|(score, moves)| AnalysisLine {
+                depth,
+                score: search_score(score).for_white(turn),
+                nodes: search.nodes,
+                moves,
+            })
+            .collect();
+        send(AnalysisEvent::Update(AnalysisUpdate {
+            fen: fen.to_string(),
+            lines,
+        }));
+        if root_moves.is_empty() {
+            return;
+        }
+    }
+}
+
+fn search_score(score: i32) -> Score {
+    if score > MATE_THRESHOLD {
+        Score::Mate((MATE_SCORE - score + 1) / 2)
+    } else if score < -MATE_THRESHOLD {
+        Score::Mate(-(MATE_SCORE + score) / 2)
+    } else {
+        Score::Centipawns(score)
+    }
+}
+
+impl Search<'_> {
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Score of the position for the side to move, the best line found being added to `line`.
+    fn negamax(
+        &mut self,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+        line: &mut Vec<BitMove>,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes % STOP_CHECK_NODES == 0 && self.stopped() {
+            return 0;
+        }
+        let mut moves: Vec<BitMove> = self.board.generate_moves().iter().cloned().collect();
+        if moves.is_empty() {
+            return if self.board.in_check() {
+                -(MATE_SCORE - ply)
+            } else {
+                0
+            };
+        }
+        if depth == 0 {
+            return self.quiescence(alpha, beta);
+        }
+        // Captures first, for more cut-offs.
+        moves.sort_by_key(|chess_move| !chess_move.is_capture());
+
+        let mut best_line = Vec::new();
+        for chess_move in moves {
+            self.board.apply_move(chess_move);
+            let mut child_line = vec![chess_move];
+            let score = -self.negamax(depth - 1, -beta, -alpha, ply + 1, &mut child_line);
+            self.board.undo_move();
+            if score > alpha {
+                alpha = score;
+                best_line = child_line;
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+        line.append(&mut best_line);
+        alpha
+    }
+
+    /// Searches the captures only, so that the evaluation is not done in the middle of an exchange.
+    fn quiescence(&mut self, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let stand_pat = evaluate(&self.board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+        let captures: Vec<BitMove> = self
+            .board
+            .generate_moves()
+            .iter()
+            .filter(|chess_move| chess_move.is_capture())
+            .cloned()
+            .collect();
+        for capture in captures {
+            self.board.apply_move(capture);
+            let score = -self.quiescence(-beta, -alpha);
+            self.board.undo_move();
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// Material and piece placement, in centipawns for the side to move, blending the middle game
+/// and end game values of pleco according to the material left.
+fn evaluate(board: &Board) -> i32 {
+    let material = board
+        .non_pawn_material_all()
+        .clamp(ENDGAME_MATERIAL, MIDGAME_MATERIAL);
+    let phase = material - ENDGAME_MATERIAL;
+    let full_phase = MIDGAME_MATERIAL - ENDGAME_MATERIAL;
+    let psq = board.psq();
+    let blended = (psq.mg() * phase + psq.eg() * (full_phase - phase)) / full_phase;
+    let centipawns = blended * 100 / PAWN_EG;
+    match board.turn() {
+        Player::White => centipawns,
+        Player::Black => -centipawns,
+    }
+}
@@ -0,0 +1,200 @@
+use log::error;
+
+use pleco::BitMove;
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{AnalysisEvent, AnalysisLine, AnalysisUpdate, Score};
+use crate::chess::san::find_uci_move;
+use crate::chess::BoardLogic;
+
+/// Updates are sent at most this often, engines giving several lines per second.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// An engine program analysing one position until it is told to quit.
+pub struct UciProcess {
+    child: Child,
+    input: ChildStdin,
+}
+
+impl UciProcess {
+    /// Starts the engine and its analysis. On failure, `send` is given back with the error.
+    pub fn start<F>(
+        path: &Path,
+        fen: &str,
+        multi_pv: usize,
+        send: F,
+    ) -> Result<Self, (io::Error, F)>
+    where
+        F: Fn(AnalysisEvent) + Send + 'static,
+    {
+        let mut child = match Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => return Err((err, send)),
+        };
+        let mut input = child.stdin.take().unwrap();
+        let output = child.stdout.take().unwrap();
+
+        // Engines read the castling rights of Chess960 and play its castles as the king
+        // taking its rook only once told the game is one.
+        let is_chess960 = BoardLogic::from_fen(fen)
+            .map(|board| board.is_chess960())
+            .unwrap_or(false);
+        let chess960_option = if is_chess960 {
+            "setoption name UCI_Chess960 value true\n"
+        } else {
+            ""
+        };
+        let commands = format!(
+            "uci\n{}setoption name MultiPV value {}\nisready\nposition fen {}\ngo infinite\n",
+            chess960_option, multi_pv, fen
+        );
+        if let Err(err) = input.write_all(commands.as_bytes()) {
+            let _ = child.kill();
+            return Err((err, send));
+        }
+
+        let fen = fen.to_string();
+        thread::spawn(move || read_analysis(BufReader::new(output), &fen, multi_pv, send));
+        Ok(UciProcess { child, input })
+    }
+
+    /// Stops the engine, which is killed if it does not quit by itself.
+    pub fn quit(mut self) {
+        let _ = self.input.write_all(b"stop\nquit\n");
+        let _ = self.input.flush();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs(1));
+            if let Ok(None) = self.child.try_wait() {
+                let _ = self.child.kill();
+            }
+            let _ = self.child.wait();
+        });
+    }
+}
+
+/// Reads the `info` lines of the engine until it quits, sending the best lines found.
+fn read_analysis(output: impl BufRead, fen: &str, multi_pv: usize, send: impl Fn(AnalysisEvent)) {
+    let board = match BoardLogic::from_fen(fen) {
+        Some(board) => board,
+        None => return,
+    };
+    let mut lines: Vec<Option<AnalysisLine>> = vec![None; multi_pv];
+    let mut last_update: Option<Instant> = None;
+    for text_line in output.lines() {
+        let text_line = match text_line {
+            Ok(text_line) => text_line,
+            Err(err) => {
+                error!("Could not read the engine output : {}", err);
+                return;
+            }
+        };
+        let (index, line) = match parse_info(&text_line, &board) {
+            Some(info) => info,
+            None => continue,
+        };
+        if index >= lines.len() {
+            continue;
+        }
+        lines[index] = Some(line);
+        // The last line of an iteration completes a consistent set of lines.
+        let iteration_done = index + 1 == multi_pv;
+        let update_due = last_update
+            .map(|time| time.elapsed() >= UPDATE_INTERVAL)
+            .unwrap_or(true);
+        if iteration_done || update_due {
+            last_update = Some(Instant::now());
+            send(AnalysisEvent::Update(AnalysisUpdate {
+                fen: fen.to_string(),
+                lines: lines.iter().flatten().cloned().collect(),
+            }));
+        }
+    }
+}
+
+/// Reads an `info` line giving a principal variation, such as
+/// `info depth 12 multipv 2 score cp -31 nodes 123456 pv e7e5 g1f3`,
+/// into the index of the line and the line, the score being converted for White.
+fn parse_info(text_line: &str, board: &BoardLogic) -> Option<(usize, AnalysisLine)> {
+    let mut words = text_line.split_whitespace();
+    if words.next() != Some("info") {
+        return None;
+    }
+    let mut depth = None;
+    let mut index = 0;
+    let mut score = None;
+    let mut nodes = 0;
+    let mut moves = Vec::new();
+    while let Some(word) = words.next() {
+        match word {
+            "depth" => depth = words.next().and_then(|value| value.parse().ok()),
+            "multipv" => {
+                index = words
+                    .next()
+                    .and_then(|value| value.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .saturating_sub(1)
+            }
+            "score" => {
+                let kind = words.next();
+                let value = words.next().and_then(|value| value.parse().ok());
+                score = match (kind, value) {
+                    (Some("cp"), Some(value)) => Some(Score::Centipawns(value)),
+                    (Some("mate"), Some(value)) => Some(Score::Mate(value)),
+                    _ => None,
+                };
+            }
+            // Bounds are given while the search fails high or low : the score is not exact.
+            "lowerbound" | "upperbound" => return None,
+            "nodes" => {
+                nodes = words
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(0)
+            }
+            "pv" => {
+                moves = legal_moves(board, words.by_ref());
+                break;
+            }
+            "string" => return None,
+            _ => {}
+        }
+    }
+    if moves.is_empty() {
+        return None;
+    }
+    Some((
+        index,
+        AnalysisLine {
+            depth: depth?,
+            score: score?.for_white(board.turn()),
+            nodes,
+            moves,
+        },
+    ))
+}
+
+/// The moves of a variation written in UCI notation, up to the first illegal one.
+fn legal_moves<'a>(board: &BoardLogic, uci_moves: impl Iterator<Item = &'a str>) -> Vec<BitMove> {
+    let mut board = board.clone();
+    let mut moves = Vec::new();
+    for uci_move in uci_moves {
+        match find_uci_move(&board, uci_move) {
+            Some(chess_move) => {
+                board.play_move(chess_move);
+                moves.push(chess_move);
+            }
+            None => break,
+        }
+    }
+    moves
+}
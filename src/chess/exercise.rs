@@ -0,0 +1,685 @@
+use druid::{Data, Lens};
+use log::error;
+
+use pleco::{BitMove, Player};
+
+use std::fmt;
+use std::time::Duration;
+
+use super::engine::{analyse_position, EngineKind, Score, SearchLimit};
+use super::pgn::{PgnGame, PgnMove};
+use super::san::{parse_move, MoveParseError, PieceLetters};
+use super::BoardLogic;
+
+/// Search of the positions after a move of the solver which is not the one of the solution.
+const ACCEPTANCE_LIMIT: SearchLimit = SearchLimit {
+    depth: 10,
+    time: Duration::from_millis(500),
+};
+
+/// A position to solve, read from a PGN game : the side to move in the start position
+/// is the solver, and the main line alternates the solver moves and the expected replies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exercise {
+    /// The game it was read from, with its tags, comments and variations.
+    pub game: PgnGame,
+    pub solution: Vec<BitMove>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExerciseError {
+    InvalidFen(String),
+    /// A move of the main line, given by its index, cannot be played.
+    InvalidMove(usize, MoveParseError),
+    NoSolution,
+}
+
+impl fmt::Display for ExerciseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExerciseError::InvalidFen(fen) => write!(f, "'{}' is not a valid position.", fen),
+            ExerciseError::InvalidMove(index, err) => {
+                write!(f, "Move {} of the solution : {}", index + 1, err)
+            }
+            ExerciseError::NoSolution => write!(f, "The exercise has no solution moves."),
+        }
+    }
+}
+
+impl Exercise {
+    pub fn from_pgn_game(game: PgnGame) -> Result<Self, ExerciseError> {
+        let solution = main_line_moves(&game)?;
+        if solution.is_empty() {
+            return Err(ExerciseError::NoSolution);
+        }
+        Ok(Exercise { game, solution })
+    }
+
+    /// The start position, with the castling of Chess960 if the game follows its rules.
+    pub fn start_position(&self) -> BoardLogic {
+        self.game.start_board().unwrap()
+    }
+
+    /// The side to move in the start position.
+    pub fn solver(&self) -> Player {
+        self.start_position().turn()
+    }
+}
+
+/// The moves of the main line of a game, played from its start position.
+pub fn main_line_moves(game: &PgnGame) -> Result<Vec<BitMove>, ExerciseError> {
+    let mut board = game
+        .start_board()
+        .ok_or_else(|| ExerciseError::InvalidFen(game.start_fen().to_string()))?;
+    let mut moves = Vec::new();
+    for (index, pgn_move) in game.moves.iter().enumerate() {
+        let chess_move = parse_move(&board, &pgn_move.san, &PieceLetters::ENGLISH)
+            .map_err(|err| ExerciseError::InvalidMove(index, err))?;
+        board.play_move(chess_move);
+        moves.push(chess_move);
+    }
+    Ok(moves)
+}
+
+/// Which moves of the solver, other than the one of the solution, are accepted as well.
+/// Nothing else is accepted by default.
+#[derive(Data, Lens, Debug, Clone, Copy, Default, PartialEq)]
+pub struct AcceptancePolicy {
+    /// Moves starting a variation of the solution move, whose moves are then expected.
+    pub variations: bool,
+    /// Moves mating as quickly as the solution, when it ends with a mate.
+    pub same_mate: bool,
+    /// Moves scored by the engine at most this many centipawns below the solution move.
+    pub eval_tolerance: Option<u32>,
+}
+
+impl AcceptancePolicy {
+    fn needs_engine(&self) -> bool {
+        self.same_mate || self.eval_tolerance.is_some()
+    }
+}
+
+/// Result of a move played by the solver.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveVerdict {
+    Wrong,
+    /// The expected move, followed by the reply to play for the opponent, if any.
+    /// The exercise is solved once the last move of the solution is played.
+    Correct {
+        reply: Option<BitMove>,
+        solved: bool,
+    },
+    /// Only the engine can tell whether the move is accepted : the attempt waits for the
+    /// judgement, given to `ExerciseAttempt::conclude`.
+    Judging(MoveJudging),
+}
+
+/// A move of the solver which is neither expected nor in a variation, to be judged by the
+/// engine against the expected line, as the policy asks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveJudging {
+    /// Position in which the move is played.
+    fen: String,
+    line: Vec<BitMove>,
+    ply: usize,
+    chess_move: BitMove,
+    policy: AcceptancePolicy,
+}
+
+/// The judgement of the engine on a move, with the position and the move it is about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JudgedMove {
+    pub fen: String,
+    pub chess_move: BitMove,
+    /// The line to follow when the move is accepted.
+    pub alternative: Option<Vec<BitMove>>,
+}
+
+impl MoveJudging {
+    pub fn fen(&self) -> &str {
+        &self.fen
+    }
+
+    pub fn chess_move(&self) -> BitMove {
+        self.chess_move
+    }
+
+    /// Searches the positions after the move and after the expected one. This takes a moment,
+    /// so it is done out of the thread of the user interface.
+    pub fn judge(&self, engine: &EngineKind) -> JudgedMove {
+        let board = BoardLogic::from_fen(&self.fen).unwrap();
+        let continuation = engine_continuation(
+            &board,
+            &self.line,
+            self.ply,
+            self.chess_move,
+            &self.policy,
+            engine,
+        )
+        .unwrap_or_else(|err| {
+            error!("Could not judge the move {} : {}", self.chess_move, err);
+            None
+        });
+        JudgedMove {
+            fen: self.fen.clone(),
+            chess_move: self.chess_move,
+            alternative: continuation.map(|continuation| {
+                accepted_line(&self.line, self.ply, self.chess_move, continuation)
+            }),
+        }
+    }
+}
+
+/// Progress of the user through the solution of an exercise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExerciseAttempt {
+    /// Number of moves of the solution already played, replies included.
+    pub ply: usize,
+    pub mistakes: u32,
+    /// Number of times the hidden pieces were revealed during the attempt.
+    pub peeks: u32,
+    /// The moves expected since an alternative move was accepted, instead of the solution.
+    alternative_line: Option<Vec<BitMove>>,
+}
+
+impl ExerciseAttempt {
+    pub fn new() -> Self {
+        ExerciseAttempt::default()
+    }
+
+    /// The moves the solver is expected to play, with the replies : the solution,
+    /// or the line of the alternative move accepted.
+    pub fn expected_line<'a>(&'a self, exercise: &'a Exercise) -> &'a [BitMove] {
+        match &self.alternative_line {
+            Some(line) => line,
+            None => &exercise.solution,
+        }
+    }
+
+    /// Checks the move of the solver, and moves on in the expected line when it is the expected
+    /// move, or another one accepted by the policy. Policies comparing mates or scores leave
+    /// the other moves to the engine, unless it does not know the variant of the exercise.
+    pub fn check_move(
+        &mut self,
+        exercise: &Exercise,
+        chess_move: BitMove,
+        policy: &AcceptancePolicy,
+    ) -> MoveVerdict {
+        let line = self.expected_line(exercise);
+        if line.get(self.ply) == Some(&chess_move) {
+            return self.advance(exercise);
+        }
+        let on_solution = self.alternative_line.is_none();
+        let mut board = exercise.start_position();
+        for expected in line.iter().take(self.ply) {
+            board.play_move(*expected);
+        }
+        if let Some(alternative) = quick_alternative(
+            exercise,
+            &board,
+            line,
+            self.ply,
+            chess_move,
+            on_solution,
+            policy,
+        ) {
+            self.alternative_line = Some(alternative);
+            return self.advance(exercise);
+        }
+        if policy.needs_engine() && board.variant.is_known_to_engines() && self.ply < line.len() {
+            return MoveVerdict::Judging(MoveJudging {
+                fen: board.fen(),
+                line: line.to_vec(),
+                ply: self.ply,
+                chess_move,
+                policy: *policy,
+            });
+        }
+        self.mistakes += 1;
+        MoveVerdict::Wrong
+    }
+
+    /// Ends the check of a move left to the engine, with its judgement.
+    pub fn conclude(&mut self, exercise: &Exercise, judged: &JudgedMove) -> MoveVerdict {
+        match &judged.alternative {
+            Some(alternative) => {
+                self.alternative_line = Some(alternative.clone());
+                self.advance(exercise)
+            }
+            None => {
+                self.mistakes += 1;
+                MoveVerdict::Wrong
+            }
+        }
+    }
+
+    /// Moves on after the expected move, with the reply of the opponent.
+    fn advance(&mut self, exercise: &Exercise) -> MoveVerdict {
+        self.ply += 1;
+        let line = self.expected_line(exercise);
+        let reply = line.get(self.ply).cloned();
+        let length = line.len();
+        if reply.is_some() {
+            self.ply += 1;
+        }
+        MoveVerdict::Correct {
+            reply,
+            solved: self.ply >= length,
+        }
+    }
+}
+
+/// The line to follow when the move of the solver, which is not the expected one at this ply,
+/// is accepted without the engine : a move starting a variation, with the moves of the
+/// variation, or a mate whatever the expected line.
+fn quick_alternative(
+    exercise: &Exercise,
+    board: &BoardLogic,
+    line: &[BitMove],
+    ply: usize,
+    chess_move: BitMove,
+    on_solution: bool,
+    policy: &AcceptancePolicy,
+) -> Option<Vec<BitMove>> {
+    let mut continuation = None;
+    let varied_move = exercise.game.moves.get(ply);
+    if let (true, true, Some(varied_move)) = (policy.variations, on_solution, varied_move) {
+        continuation = varied_move
+            .variations
+            .iter()
+            .filter_map(|variation| variation_moves(board, variation))
+            .find(|moves| moves.first() == Some(&chess_move))
+            .map(|moves| moves[1..].to_vec());
+    }
+    if continuation.is_none() && policy.needs_engine() && board.variant.is_known_to_engines() {
+        let mut after_move = board.clone();
+        after_move.play_move(chess_move);
+        if after_move.is_checkmate() {
+            // Nothing can be better, whatever the policy.
+            continuation = Some(Vec::new());
+        }
+    }
+    continuation.map(|continuation| accepted_line(line, ply, chess_move, continuation))
+}
+
+/// The expected moves up to the ply, the accepted move, and its continuation.
+fn accepted_line(
+    line: &[BitMove],
+    ply: usize,
+    chess_move: BitMove,
+    continuation: Vec<BitMove>,
+) -> Vec<BitMove> {
+    let mut alternative = line[..ply].to_vec();
+    alternative.push(chess_move);
+    alternative.extend(continuation);
+    alternative
+}
+
+/// The legal moves of a variation played from the position, cut after the last move
+/// of the solver.
+fn variation_moves(board: &BoardLogic, variation: &[PgnMove]) -> Option<Vec<BitMove>> {
+    let mut board = board.clone();
+    let mut moves = Vec::new();
+    for pgn_move in variation {
+        match parse_move(&board, &pgn_move.san, &PieceLetters::ENGLISH) {
+            Ok(chess_move) => {
+                board.play_move(chess_move);
+                moves.push(chess_move);
+            }
+            Err(_) => break,
+        }
+    }
+    if moves.is_empty() {
+        return None;
+    }
+    moves.truncate(moves.len() - (moves.len() + 1) % 2);
+    Some(moves)
+}
+
+/// The best line of the engine after the move, when it mates as quickly as the expected line
+/// or scores close enough to the expected move. It has at most as many moves as are left
+/// in the expected line, ending with a move of the solver.
+fn engine_continuation(
+    board: &BoardLogic,
+    line: &[BitMove],
+    ply: usize,
+    chess_move: BitMove,
+    policy: &AcceptancePolicy,
+    engine: &EngineKind,
+) -> Result<Option<Vec<BitMove>>, String> {
+    if ply >= line.len() {
+        return Ok(None);
+    }
+    let solver = board.turn();
+    let mut after_move = board.clone();
+    after_move.play_move(chess_move);
+    let left_plies = line.len() - ply - 1;
+    let best_line = analyse_position(engine, &after_move.fen(), 1, ACCEPTANCE_LIMIT)?
+        .into_iter()
+        .next();
+    let best_line = match best_line {
+        Some(best_line) => best_line,
+        None => return Ok(None),
+    };
+    let score = best_line.score.for_player(solver);
+
+    let mut accepted =
+        policy.same_mate && ends_with_mate(board, &line[ply..]) && mates_in_time(score, left_plies);
+    if let (false, Some(tolerance)) = (accepted, policy.eval_tolerance) {
+        let mut after_expected = board.clone();
+        after_expected.play_move(line[ply]);
+        // No other move comes close to a mate.
+        let expected_score = if after_expected.is_checkmate() {
+            None
+        } else {
+            analyse_position(engine, &after_expected.fen(), 1, ACCEPTANCE_LIMIT)?
+                .first()
+                .map(|expected| expected.score.for_player(solver))
+        };
+        if let Some(expected_score) = expected_score {
+            accepted = within_tolerance(score, expected_score, tolerance);
+        }
+    }
+    if !accepted {
+        return Ok(None);
+    }
+    Ok(Some(cut_continuation(best_line.moves, left_plies)))
+}
+
+/// Whether the score of the solver after their move is a mate in at most as many moves
+/// as are left to them in the expected line.
+fn mates_in_time(score: Score, left_plies: usize) -> bool {
+    let left_moves = (left_plies / 2) as i32;
+    matches!(score, Score::Mate(moves) if moves > 0 && moves <= left_moves)
+}
+
+/// Whether the score of the solver is at most `tolerance` centipawns below the expected one.
+fn within_tolerance(score: Score, expected_score: Score, tolerance: u32) -> bool {
+    score.as_centipawns() + tolerance as i32 >= expected_score.as_centipawns()
+}
+
+/// The moves of the line after a move of the solver, cut to at most the plies left in the
+/// expected line and so that the last one is a move of the solver.
+fn cut_continuation(mut continuation: Vec<BitMove>, left_plies: usize) -> Vec<BitMove> {
+    let plies = continuation.len().min(left_plies);
+    continuation.truncate(plies - plies % 2);
+    continuation
+}
+
+/// Whether the moves played from the position end with a checkmate.
+fn ends_with_mate(board: &BoardLogic, moves: &[BitMove]) -> bool {
+    let mut board = board.clone();
+    for chess_move in moves {
+        board.play_move(*chess_move);
+    }
+    board.is_checkmate()
+}
+
+/// Exercises of a PGN collection, with the errors of the games which are not valid exercises.
+pub fn exercises_from_games(games: Vec<PgnGame>) -> (Vec<Exercise>, Vec<(usize, ExerciseError)>) {
+    let mut exercises = Vec::new();
+    let mut errors = Vec::new();
+    for (index, game) in games.into_iter().enumerate() {
+        match Exercise::from_pgn_game(game) {
+            Ok(exercise) => exercises.push(exercise),
+            Err(err) => errors.push((index, err)),
+        }
+    }
+    (exercises, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    /// The solution `1. e4 e5 2. Nf3`, with variations of four, two and two legal moves.
+    const VARIATIONS: &str =
+        "[Event \"?\"]\n\n1. e4 (1. d4 d5 2. c4 e6) (1. c4 e5) (1. Nf3 d5 2. Zz9) e5 2. Nf3 *";
+    /// Two rooks which both mate, the solution being `Ra8#`.
+    const TWO_MATES: &str = "[SetUp \"1\"]\n[FEN \"6k1/5ppp/8/8/8/8/5PPP/R3R1K1 w - - 0 1\"]\n\n\
+                             1. Ra8# *";
+
+    fn exercise(text: &str) -> Exercise {
+        Exercise::from_pgn_game(parse_pgn(text).unwrap().remove(0)).unwrap()
+    }
+
+    /// The move in the position reached by the expected moves of the attempt.
+    fn uci_move(exercise: &Exercise, attempt: &ExerciseAttempt, uci: &str) -> BitMove {
+        let mut board = exercise.start_position();
+        for chess_move in &attempt.expected_line(exercise)[..attempt.ply] {
+            board.play_move(*chess_move);
+        }
+        find_uci_move(&board, uci).unwrap()
+    }
+
+    fn play(
+        exercise: &Exercise,
+        attempt: &mut ExerciseAttempt,
+        uci: &str,
+        policy: &AcceptancePolicy,
+    ) -> MoveVerdict {
+        let chess_move = uci_move(exercise, attempt, uci);
+        attempt.check_move(exercise, chess_move, policy)
+    }
+
+    fn variations_policy() -> AcceptancePolicy {
+        AcceptancePolicy {
+            variations: true,
+            ..AcceptancePolicy::default()
+        }
+    }
+
+    fn tolerance_policy() -> AcceptancePolicy {
+        AcceptancePolicy {
+            eval_tolerance: Some(50),
+            ..AcceptancePolicy::default()
+        }
+    }
+
+    fn correct(reply: Option<BitMove>, solved: bool) -> MoveVerdict {
+        MoveVerdict::Correct { reply, solved }
+    }
+
+    #[test]
+    fn expected_moves_are_answered_until_solved() {
+        let exercise = exercise(VARIATIONS);
+        let mut attempt = ExerciseAttempt::new();
+        let policy = AcceptancePolicy::default();
+        assert_eq!(
+            play(&exercise, &mut attempt, "e2e4", &policy),
+            correct(Some(exercise.solution[1]), false)
+        );
+        assert_eq!(
+            play(&exercise, &mut attempt, "g1f3", &policy),
+            correct(None, true)
+        );
+        assert_eq!(attempt.mistakes, 0);
+    }
+
+    #[test]
+    fn other_moves_are_wrong_by_default() {
+        let exercise = exercise(VARIATIONS);
+        let mut attempt = ExerciseAttempt::new();
+        let policy = AcceptancePolicy::default();
+        assert_eq!(
+            play(&exercise, &mut attempt, "d2d4", &policy),
+            MoveVerdict::Wrong
+        );
+        assert_eq!(attempt.mistakes, 1);
+        assert_eq!(attempt.ply, 0);
+    }
+
+    #[test]
+    fn variation_moves_are_accepted_with_their_continuation() {
+        let exercise = exercise(VARIATIONS);
+        let mut attempt = ExerciseAttempt::new();
+        let policy = variations_policy();
+        let d5 = {
+            let mut board = exercise.start_position();
+            board.play_move(uci_move(&exercise, &attempt, "d2d4"));
+            find_uci_move(&board, "d7d5").unwrap()
+        };
+        assert_eq!(
+            play(&exercise, &mut attempt, "d2d4", &policy),
+            correct(Some(d5), false)
+        );
+        // The four moves of the variation are cut after the last move of the solver.
+        assert_eq!(attempt.expected_line(&exercise).len(), 3);
+        assert_eq!(
+            play(&exercise, &mut attempt, "c2c4", &policy),
+            correct(None, true)
+        );
+    }
+
+    #[test]
+    fn even_variations_lose_their_last_reply() {
+        let exercise = exercise(VARIATIONS);
+        for uci in &["c2c4", "g1f3"] {
+            let mut attempt = ExerciseAttempt::new();
+            assert_eq!(
+                play(&exercise, &mut attempt, uci, &variations_policy()),
+                correct(None, true),
+                "{}",
+                uci
+            );
+        }
+    }
+
+    #[test]
+    fn variations_are_cut_after_the_solver_moves() {
+        let exercise = exercise(VARIATIONS);
+        let board = exercise.start_position();
+        let lengths =
+            |variation: &[PgnMove]| variation_moves(&board, variation).map(|moves| moves.len());
+        let variations = &exercise.game.moves[0].variations;
+        assert_eq!(lengths(&variations[0]), Some(3));
+        assert_eq!(lengths(&variations[1]), Some(1));
+        // Moves after an illegal one are dropped.
+        assert_eq!(lengths(&variations[2]), Some(1));
+        assert_eq!(lengths(&[]), None);
+        assert_eq!(lengths(&[PgnMove::new("Ke2")]), None);
+    }
+
+    #[test]
+    fn moves_after_the_end_of_the_solution_are_wrong() {
+        let exercise = exercise(VARIATIONS);
+        let mut attempt = ExerciseAttempt::new();
+        let policy = AcceptancePolicy {
+            variations: true,
+            same_mate: true,
+            eval_tolerance: Some(50),
+        };
+        play(&exercise, &mut attempt, "e2e4", &policy);
+        assert_eq!(
+            play(&exercise, &mut attempt, "g1f3", &policy),
+            correct(None, true)
+        );
+        // Neither the variations nor the engine have a move to compare with.
+        assert_eq!(
+            play(&exercise, &mut attempt, "b8c6", &policy),
+            MoveVerdict::Wrong
+        );
+    }
+
+    #[test]
+    fn other_mates_are_accepted_without_the_engine() {
+        let exercise = exercise(TWO_MATES);
+        let policies = [
+            AcceptancePolicy {
+                same_mate: true,
+                ..AcceptancePolicy::default()
+            },
+            tolerance_policy(),
+        ];
+        for policy in &policies {
+            let mut attempt = ExerciseAttempt::new();
+            assert_eq!(
+                play(&exercise, &mut attempt, "e1e8", policy),
+                correct(None, true)
+            );
+        }
+        let mut attempt = ExerciseAttempt::new();
+        assert_eq!(
+            play(&exercise, &mut attempt, "e1e8", &variations_policy()),
+            MoveVerdict::Wrong
+        );
+    }
+
+    #[test]
+    fn other_moves_wait_for_the_engine_when_the_policy_needs_it() {
+        let exercise = exercise(VARIATIONS);
+        let mut attempt = ExerciseAttempt::new();
+        let c3 = uci_move(&exercise, &attempt, "c2c3");
+        let judging = match attempt.check_move(&exercise, c3, &tolerance_policy()) {
+            MoveVerdict::Judging(judging) => judging,
+            verdict => panic!("{:?} is not a judging", verdict),
+        };
+        assert_eq!(judging.fen(), exercise.game.start_fen());
+        assert_eq!(judging.chess_move(), c3);
+        assert_eq!(attempt.mistakes, 0);
+        assert_eq!(attempt.ply, 0);
+
+        let mut refused = attempt.clone();
+        let judged = JudgedMove {
+            fen: judging.fen().to_string(),
+            chess_move: c3,
+            alternative: None,
+        };
+        assert_eq!(refused.conclude(&exercise, &judged), MoveVerdict::Wrong);
+        assert_eq!(refused.mistakes, 1);
+
+        let alternative = vec![c3, exercise.solution[1], exercise.solution[2]];
+        let judged = JudgedMove {
+            alternative: Some(alternative.clone()),
+            ..judged
+        };
+        assert_eq!(
+            attempt.conclude(&exercise, &judged),
+            correct(Some(exercise.solution[1]), false)
+        );
+        assert_eq!(attempt.expected_line(&exercise), alternative.as_slice());
+    }
+
+    #[test]
+    fn mates_must_come_as_quickly_as_in_the_expected_line() {
+        // A mate on the next move of the solver, two plies after their move.
+        assert!(mates_in_time(Score::Mate(1), 2));
+        assert!(!mates_in_time(Score::Mate(2), 2));
+        assert!(!mates_in_time(Score::Mate(2), 3));
+        assert!(mates_in_time(Score::Mate(2), 4));
+        assert!(!mates_in_time(Score::Mate(1), 0));
+        assert!(!mates_in_time(Score::Mate(-1), 4));
+        assert!(!mates_in_time(Score::Centipawns(5_000), 4));
+    }
+
+    #[test]
+    fn scores_must_come_within_the_tolerance() {
+        let expected = Score::Centipawns(150);
+        assert!(within_tolerance(Score::Centipawns(100), expected, 50));
+        assert!(!within_tolerance(Score::Centipawns(99), expected, 50));
+        assert!(within_tolerance(Score::Centipawns(200), expected, 0));
+        assert!(!within_tolerance(Score::Centipawns(149), expected, 0));
+        // Only a mate comes close to a mate.
+        assert!(!within_tolerance(
+            Score::Centipawns(2_000),
+            Score::Mate(3),
+            100
+        ));
+        assert!(within_tolerance(Score::Mate(4), Score::Mate(3), 100));
+        assert!(!within_tolerance(Score::Mate(-3), expected, 100));
+    }
+
+    #[test]
+    fn engine_lines_are_cut_to_the_expected_length() {
+        let exercise = exercise(VARIATIONS);
+        let moves = exercise.solution.clone();
+        assert_eq!(cut_continuation(moves.clone(), 2).len(), 2);
+        assert_eq!(cut_continuation(moves.clone(), 1).len(), 0);
+        assert_eq!(cut_continuation(moves.clone(), 10).len(), 2);
+        assert_eq!(cut_continuation(moves[..1].to_vec(), 4).len(), 0);
+        assert!(cut_continuation(Vec::new(), 4).is_empty());
+    }
+}
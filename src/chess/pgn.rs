@@ -0,0 +1,851 @@
+use pleco::Player;
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::board_logic::{BoardLogic, STANDARD_START_FEN};
+use super::clock::{format_clock, ChessClock};
+use super::san::{move_to_san, PieceLetters};
+use super::variant::{is_chess960_tag, Variant};
+
+/// Lines of exported games are kept under the 80 characters advised by the PGN standard.
+const MAX_LINE_LENGTH: usize = 79;
+/// Tag of the rules the game follows, the standard ones when it is missing.
+pub const VARIANT_TAG: &str = "Variant";
+/// Value of the `Variant` tag written for Chess960 games.
+pub const CHESS960_VARIANT: &str = "Chess960";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric Annotation Glyphs, `!` and `?` suffixes being read as glyphs 1 to 6.
+    pub nags: Vec<u8>,
+    /// Comment written after the move, without the braces.
+    pub comment: Option<String>,
+    /// Other moves which could have been played instead of this one, with their continuations.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(san: impl Into<String>) -> Self {
+        PgnMove {
+            san: san.into(),
+            nags: Vec::new(),
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+
+    /// Adds text to the comment of the move, after what it already says.
+    pub fn add_comment(&mut self, text: &str) {
+        self.comment = Some(match self.comment.take() {
+            Some(previous) => format!("{} {}", previous, text),
+            None => text.to_string(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PgnParseError {
+    UnterminatedTag(usize),
+    UnterminatedComment(usize),
+    UnbalancedVariation(usize),
+    /// The movetext of a game has a variation opened before any move.
+    OrphanVariation(usize),
+}
+
+impl fmt::Display for PgnParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnParseError::UnterminatedTag(line) => write!(f, "Unterminated tag on line {}.", line),
+            PgnParseError::UnterminatedComment(line) => {
+                write!(f, "Unterminated comment starting on line {}.", line)
+            }
+            PgnParseError::UnbalancedVariation(line) => {
+                write!(f, "Unbalanced variation parenthesis on line {}.", line)
+            }
+            PgnParseError::OrphanVariation(line) => {
+                write!(f, "Variation without a preceding move on line {}.", line)
+            }
+        }
+    }
+}
+
+/// A game ready to be written in the PGN format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    /// Tags in the order they are written, the Seven Tag Roster first.
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    /// Number of the first move, and whether Black plays it.
+    pub first_move_number: u32,
+    pub black_starts: bool,
+    /// "1-0", "0-1", "1/2-1/2" or "*".
+    pub result: String,
+}
+
+impl PgnGame {
+    /// The moves played on the board, in English algebraic notation as required by PGN.
+    pub fn from_board_logic(logic: &BoardLogic, result: &str) -> Self {
+        let mut board = logic.start_position();
+        let moves = logic
+            .history
+            .iter()
+            .map(|chess_move| {
+                let san = move_to_san(&board, *chess_move, &PieceLetters::ENGLISH);
+                board.play_move(*chess_move);
+                PgnMove::new(san)
+            })
+            .collect();
+
+        let (first_move_number, black_starts) = first_move_of_fen(&logic.start_fen);
+        let mut tags = vec![
+            ("Event", "?".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", today()),
+            ("Round", "-".to_string()),
+            ("White", "?".to_string()),
+            ("Black", "?".to_string()),
+            ("Result", result.to_string()),
+        ];
+        if logic.is_chess960() {
+            tags.push((VARIANT_TAG, CHESS960_VARIANT.to_string()));
+        } else if let Some(variant) = logic.variant.tag_value() {
+            tags.push((VARIANT_TAG, variant.to_string()));
+        }
+        if logic.start_fen != STANDARD_START_FEN {
+            tags.push(("SetUp", "1".to_string()));
+            tags.push(("FEN", logic.start_fen.clone()));
+        }
+
+        PgnGame {
+            tags: tags
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            moves,
+            first_move_number,
+            black_starts,
+            result: result.to_string(),
+        }
+    }
+
+    /// Value of the tag with this name, if the game has it.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of a tag, adding it after the others if the game does not have it yet.
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    /// Removes the tag with this name, if the game has it.
+    pub fn remove_tag(&mut self, name: &str) {
+        self.tags.retain(|(tag_name, _)| tag_name != name);
+    }
+
+    /// Position the game starts from : the `FEN` tag, or the standard start position.
+    pub fn start_fen(&self) -> &str {
+        self.tag("FEN").unwrap_or(STANDARD_START_FEN)
+    }
+
+    /// Whether the `Variant` tag tells a Chess960 game, also known as Fischer Random Chess.
+    pub fn is_chess960(&self) -> bool {
+        self.tag(VARIANT_TAG).map_or(false, is_chess960_tag)
+    }
+
+    /// The rules the game follows according to its `Variant` tag, none for a variant
+    /// this application does not play.
+    pub fn variant(&self) -> Option<Variant> {
+        Variant::from_tag(self.tag(VARIANT_TAG))
+    }
+
+    /// The board set up in the start position, following the rules of the `Variant` tag,
+    /// with the castling of Chess960 when the tag tells so or the castling rights of the
+    /// position need it. None for an invalid position or a variant which is not played.
+    pub fn start_board(&self) -> Option<BoardLogic> {
+        if self.is_chess960() {
+            BoardLogic::from_chess960_fen(self.start_fen())
+        } else {
+            BoardLogic::from_variant_fen(self.variant()?, self.start_fen())
+        }
+    }
+
+    /// Whether the engines, books and tablebases know the rules of the game.
+    pub fn is_known_to_engines(&self) -> bool {
+        self.variant()
+            .map_or(false, |variant| variant.is_known_to_engines())
+    }
+
+    /// The `Event` tag, or the players names when it is unknown.
+    pub fn title(&self) -> String {
+        match self.tag("Event") {
+            Some(event) if event != "?" && !event.is_empty() => event.to_string(),
+            _ => format!(
+                "{} - {}",
+                self.tag("White").unwrap_or("?"),
+                self.tag("Black").unwrap_or("?")
+            ),
+        }
+    }
+
+    /// Adds the `TimeControl` tag and a `[%clk]` comment after each move timed by the clock.
+    /// The clock may have been set during the game, so its times are those of the last moves.
+    pub fn add_clock_times(&mut self, clock: &ChessClock) {
+        self.set_tag("TimeControl", clock.control().pgn_tag());
+        let timed_moves = clock.move_clocks().len().min(self.moves.len());
+        let first_timed_move = self.moves.len() - timed_moves;
+        let times = &clock.move_clocks()[clock.move_clocks().len() - timed_moves..];
+        for (pgn_move, time) in self.moves[first_timed_move..].iter_mut().zip(times) {
+            if let Some(time) = time {
+                pgn_move.add_comment(&format!("[%clk {}]", format_clock(*time)));
+            }
+        }
+    }
+
+    pub fn to_pgn_string(&self) -> String {
+        let mut text = String::new();
+        for (name, value) in &self.tags {
+            text.push_str(&format!("[{} \"{}\"]\n", name, escape_tag_value(value)));
+        }
+        text.push('\n');
+
+        let mut tokens = Vec::new();
+        let first_ply = 2 * self.first_move_number as usize + usize::from(self.black_starts);
+        write_moves(&self.moves, first_ply, &mut tokens);
+        tokens.push(self.result.clone());
+
+        let mut line_length = 0;
+        let mut previous_token = String::new();
+        for token in tokens {
+            let glued = previous_token == "(" || token == ")";
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                text.push('\n');
+                line_length = 0;
+            } else if line_length > 0 && !glued {
+                text.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            text.push_str(&token);
+            previous_token = token;
+        }
+        text.push('\n');
+        text
+    }
+}
+
+/// Move number tokens, SAN, glyphs, comments and variations of a line whose first move
+/// is played at the given ply, counted from `2 * move number`, plus one for Black.
+fn write_moves(moves: &[PgnMove], first_ply: usize, tokens: &mut Vec<String>) {
+    let mut needs_number = true;
+    for (index, pgn_move) in moves.iter().enumerate() {
+        let ply = first_ply + index;
+        if ply % 2 == 0 {
+            tokens.push(format!("{}.", ply / 2));
+        } else if needs_number {
+            tokens.push(format!("{}...", ply / 2));
+        }
+        tokens.push(pgn_move.san.clone());
+        for nag in &pgn_move.nags {
+            tokens.push(format!("${}", nag));
+        }
+        needs_number = false;
+        if let Some(comment) = &pgn_move.comment {
+            tokens.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+        for variation in &pgn_move.variations {
+            tokens.push(String::from("("));
+            write_moves(variation, ply, tokens);
+            tokens.push(String::from(")"));
+            needs_number = true;
+        }
+    }
+}
+
+/// Reads all the games of a PGN file. Move texts are kept as written, their legality being
+/// checked by whoever plays them.
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnParseError> {
+    let mut games = Vec::new();
+    let mut tags = Vec::new();
+    // Lines being read, the main line at the bottom and the innermost variation on top.
+    let mut lines: Vec<Vec<PgnMove>> = vec![Vec::new()];
+    let mut first_move_number = None;
+    let mut in_movetext = false;
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut position = 0;
+    let mut line = 1;
+    while position < chars.len() {
+        let character = chars[position];
+        let token_line = line;
+        match character {
+            '\n' => {
+                line += 1;
+                position += 1;
+                if chars.get(position) == Some(&'%') {
+                    position = skip_line(&chars, position);
+                }
+            }
+            _ if character.is_whitespace() => position += 1,
+            '[' => {
+                if in_movetext {
+                    games.push(finish_game(&mut tags, &mut lines, first_move_number, "*"));
+                    first_move_number = None;
+                    in_movetext = false;
+                }
+                let end = find_char(&chars, position, ']')
+                    .ok_or(PgnParseError::UnterminatedTag(token_line))?;
+                let content: String = chars[position + 1..end].iter().collect();
+                line += content.matches('\n').count();
+                if let Some(tag) = parse_tag(&content) {
+                    tags.push(tag);
+                }
+                position = end + 1;
+            }
+            '{' => {
+                let end = find_char(&chars, position, '}')
+                    .ok_or(PgnParseError::UnterminatedComment(token_line))?;
+                let comment: String = chars[position + 1..end].iter().collect();
+                line += comment.matches('\n').count();
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                if let Some(last_move) = lines.last_mut().and_then(|moves| moves.last_mut()) {
+                    last_move.add_comment(&comment);
+                }
+                position = end + 1;
+            }
+            ';' => position = skip_line(&chars, position),
+            '(' => {
+                in_movetext = true;
+                if lines.last().map(|moves| moves.is_empty()).unwrap_or(true) {
+                    return Err(PgnParseError::OrphanVariation(token_line));
+                }
+                lines.push(Vec::new());
+                position += 1;
+            }
+            ')' => {
+                if lines.len() < 2 {
+                    return Err(PgnParseError::UnbalancedVariation(token_line));
+                }
+                let variation = lines.pop().unwrap();
+                if let Some(varied_move) = lines.last_mut().and_then(|moves| moves.last_mut()) {
+                    varied_move.variations.push(variation);
+                }
+                position += 1;
+            }
+            _ => {
+                in_movetext = true;
+                let start = position;
+                while position < chars.len()
+                    && !chars[position].is_whitespace()
+                    && !"[]{}();".contains(chars[position])
+                {
+                    position += 1;
+                }
+                if position == start {
+                    // A closing bracket or brace without its opening one.
+                    position += 1;
+                    continue;
+                }
+                let token: String = chars[start..position].iter().collect();
+                match token.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        if lines.len() > 1 {
+                            return Err(PgnParseError::UnbalancedVariation(token_line));
+                        }
+                        games.push(finish_game(
+                            &mut tags,
+                            &mut lines,
+                            first_move_number,
+                            &token,
+                        ));
+                        first_move_number = None;
+                        in_movetext = false;
+                    }
+                    _ => read_move_token(&token, &mut lines, &mut first_move_number),
+                }
+            }
+        }
+    }
+    if lines.len() > 1 {
+        return Err(PgnParseError::UnbalancedVariation(line));
+    }
+    if in_movetext || !tags.is_empty() {
+        games.push(finish_game(&mut tags, &mut lines, first_move_number, "*"));
+    }
+    Ok(games)
+}
+
+/// Reads a move number, a glyph, or a move followed by `!` and `?` suffixes.
+fn read_move_token(token: &str, lines: &mut [Vec<PgnMove>], first_move_number: &mut Option<u32>) {
+    let current_line = lines.last_mut().unwrap();
+    if let Some(glyph) = token.strip_prefix('$') {
+        if let (Ok(nag), Some(last_move)) = (glyph.parse(), current_line.last_mut()) {
+            last_move.nags.push(nag);
+        }
+        return;
+    }
+    let number_end = token
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(token.len());
+    let notation = if number_end > 0 && token[number_end..].starts_with('.') {
+        if first_move_number.is_none() {
+            *first_move_number = token[..number_end].parse().ok();
+        }
+        token[number_end..].trim_start_matches('.')
+    } else {
+        token
+    };
+    if notation.is_empty() {
+        return;
+    }
+    let san = notation.trim_end_matches(['!', '?']);
+    let suffix_nag = match &notation[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    let mut pgn_move = PgnMove::new(san);
+    pgn_move.nags.extend(suffix_nag);
+    current_line.push(pgn_move);
+}
+
+fn finish_game(
+    tags: &mut Vec<(String, String)>,
+    lines: &mut Vec<Vec<PgnMove>>,
+    first_move_number: Option<u32>,
+    result: &str,
+) -> PgnGame {
+    let mut game = PgnGame {
+        tags: std::mem::take(tags),
+        moves: std::mem::replace(lines, vec![Vec::new()]).remove(0),
+        first_move_number: 1,
+        black_starts: false,
+        result: result.to_string(),
+    };
+    let (fen_move_number, black_starts) = first_move_of_fen(game.start_fen());
+    game.first_move_number = first_move_number.unwrap_or(fen_move_number);
+    game.black_starts = black_starts;
+    game
+}
+
+fn parse_tag(content: &str) -> Option<(String, String)> {
+    let content = content.trim();
+    let name_end = content.find(char::is_whitespace)?;
+    let value = content[name_end..].trim();
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some((
+        content[..name_end].to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Index of the next occurrence of the character after `start`.
+fn find_char(chars: &[char], start: usize, searched: char) -> Option<usize> {
+    chars[start + 1..]
+        .iter()
+        .position(|character| *character == searched)
+        .map(|offset| start + 1 + offset)
+}
+
+/// Index of the end of the line, where the new line character is.
+fn skip_line(chars: &[char], start: usize) -> usize {
+    find_char(chars, start, '\n').unwrap_or(chars.len())
+}
+
+/// Number of the first move and whether Black plays it, from the last fields of a FEN.
+fn first_move_of_fen(fen: &str) -> (u32, bool) {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let number = fields
+        .get(5)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(1);
+    (number, fields.get(1) == Some(&"b"))
+}
+
+/// The result of the game as written in PGN : a checkmate, a stalemate, a win by the rules
+/// of its variant, or a flag fall, which is a draw when the opponent has only a king left
+/// but in Antichess.
+pub fn game_result(logic: &BoardLogic, flagged: Option<Player>) -> &'static str {
+    let winner = if let Some(win) = logic.variant_win() {
+        Some(win.winner)
+    } else if logic.variant == Variant::Antichess {
+        flagged.map(|player| player.other_player())
+    } else if logic.is_checkmate() {
+        Some(logic.turn().other_player())
+    } else if logic.is_stalemate() {
+        return "1/2-1/2";
+    } else if let Some(player) = flagged {
+        let opponent = player.other_player();
+        if logic.pieces_count(opponent) == 1 {
+            return "1/2-1/2";
+        }
+        Some(opponent)
+    } else {
+        None
+    };
+    match winner {
+        Some(Player::White) => "1-0",
+        Some(Player::Black) => "0-1",
+        None => "*",
+    }
+}
+
+fn escape_tag_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Today's date in the `YYYY.MM.DD` format of the `Date` tag.
+pub fn today() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::default());
+    let (year, month, day) = civil_from_days((since_epoch.as_secs() / 86_400) as i64);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+/// Gregorian date of a number of days since 1970-01-01 (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::clock::TimeControl;
+    use super::super::san::find_uci_move;
+
+    use std::time::Instant;
+
+    fn pgn_move(san: &str) -> PgnMove {
+        PgnMove::new(san)
+    }
+
+    fn game(moves: Vec<PgnMove>, black_starts: bool) -> PgnGame {
+        PgnGame {
+            tags: vec![("Event".to_string(), "?".to_string())],
+            moves,
+            first_move_number: 1,
+            black_starts,
+            result: "*".to_string(),
+        }
+    }
+
+    fn play(logic: &mut BoardLogic, uci_moves: &[&str]) {
+        for uci in uci_moves {
+            let chess_move = find_uci_move(logic, uci).unwrap();
+            logic.play_move(chess_move);
+        }
+    }
+
+    #[test]
+    fn moves_are_numbered_from_the_board_history() {
+        let mut logic = BoardLogic::new();
+        play(&mut logic, &["e2e4", "e7e5", "g1f3"]);
+        let game = PgnGame::from_board_logic(&logic, "*");
+        let tag_names: Vec<&str> = game.tags.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            tag_names,
+            ["Event", "Site", "Date", "Round", "White", "Black", "Result"]
+        );
+        assert!(game.to_pgn_string().ends_with("\n\n1. e4 e5 2. Nf3 *\n"));
+    }
+
+    #[test]
+    fn black_first_move_is_numbered_with_an_ellipsis() {
+        let mut game = game(vec![pgn_move("e5"), pgn_move("Nf3")], true);
+        game.first_move_number = 12;
+        assert_eq!(
+            game.to_pgn_string(),
+            "[Event \"?\"]\n\n12... e5 13. Nf3 *\n"
+        );
+    }
+
+    #[test]
+    fn tag_values_are_escaped() {
+        let mut game = game(vec![], false);
+        game.tags = vec![("White".to_string(), "A \"B\" \\ C".to_string())];
+        assert_eq!(
+            game.to_pgn_string(),
+            "[White \"A \\\"B\\\" \\\\ C\"]\n\n*\n"
+        );
+    }
+
+    #[test]
+    fn long_games_are_wrapped_under_80_characters() {
+        let moves = (0..60).map(|_| pgn_move("Nf3")).collect();
+        let text = game(moves, false).to_pgn_string();
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(text.lines().count() > 3);
+        assert!(text
+            .lines()
+            .all(|line| !line.starts_with(' ') && !line.ends_with(' ')));
+    }
+
+    #[test]
+    fn clock_times_are_written_after_the_timed_moves() {
+        let mut logic = BoardLogic::new();
+        play(&mut logic, &["e2e4", "e7e5", "g1f3"]);
+        // The clock is set after the first move : only the last two moves are timed.
+        let start = Instant::now();
+        let mut clock = ChessClock::new(
+            TimeControl::sudden_death(Duration::from_secs(60)),
+            Player::Black,
+        );
+        clock.start(start);
+        assert!(clock.press(start + Duration::from_secs(10)));
+        assert!(clock.press(start + Duration::from_secs(12)));
+
+        let mut game = PgnGame::from_board_logic(&logic, "*");
+        game.add_clock_times(&clock);
+        assert_eq!(
+            game.tags.last(),
+            Some(&("TimeControl".to_string(), "60".to_string()))
+        );
+        assert_eq!(game.moves[0].comment, None);
+        assert_eq!(game.moves[1].comment.as_deref(), Some("[%clk 0:00:50]"));
+        assert_eq!(game.moves[2].comment.as_deref(), Some("[%clk 0:00:58]"));
+        assert!(game
+            .to_pgn_string()
+            .ends_with("\n1. e4 e5 {[%clk 0:00:50]} 2. Nf3 {[%clk 0:00:58]} *\n"));
+    }
+
+    #[test]
+    fn moves_played_without_the_clock_running_have_no_time() {
+        let mut logic = BoardLogic::new();
+        play(&mut logic, &["e2e4", "e7e5"]);
+        let start = Instant::now();
+        let mut clock = ChessClock::new(
+            TimeControl::sudden_death(Duration::from_secs(60)),
+            Player::White,
+        );
+        assert!(clock.press(start));
+        clock.start(start);
+        assert!(clock.press(start + Duration::from_secs(5)));
+
+        let mut game = PgnGame::from_board_logic(&logic, "*");
+        game.add_clock_times(&clock);
+        assert_eq!(game.moves[0].comment, None);
+        assert_eq!(game.moves[1].comment.as_deref(), Some("[%clk 0:00:55]"));
+        assert!(game
+            .to_pgn_string()
+            .ends_with("\n1. e4 e5 {[%clk 0:00:55]} *\n"));
+    }
+
+    #[test]
+    fn move_after_a_comment_repeats_its_number() {
+        let mut first = pgn_move("e4");
+        first.comment = Some("[%clk 0:01:00]".to_string());
+        let game = game(vec![first, pgn_move("e5")], false);
+        assert!(game
+            .to_pgn_string()
+            .ends_with("\n1. e4 {[%clk 0:01:00]} 1... e5 *\n"));
+    }
+
+    #[test]
+    fn results_follow_the_end_of_the_game() {
+        let mut logic = BoardLogic::new();
+        play(&mut logic, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(game_result(&logic, None), "0-1");
+        assert_eq!(game_result(&BoardLogic::new(), None), "*");
+        assert_eq!(game_result(&BoardLogic::new(), Some(Player::White)), "0-1");
+        let lone_king = BoardLogic::from_fen("4k3/8/8/8/8/8/8/QQQQK3 w - - 0 1").unwrap();
+        assert_eq!(game_result(&lone_king, Some(Player::White)), "1/2-1/2");
+        let stalemate = BoardLogic::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game_result(&stalemate, None), "1/2-1/2");
+    }
+
+    const ANNOTATED_GAME: &str = "[Event \"Test \\\"quoted\\\"\"]
+[White \"A\"]
+[Result \"1-0\"]
+
+% An escaped line
+1. e4 $1 {Best
+by test} e5?! 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2... Nc6 ; the rest of the line
+3. Bb5!! 1-0
+";
+
+    fn parse_one(text: &str) -> PgnGame {
+        let mut games = parse_pgn(text).unwrap();
+        assert_eq!(games.len(), 1);
+        games.remove(0)
+    }
+
+    fn sans(moves: &[PgnMove]) -> Vec<&str> {
+        moves.iter().map(|pgn_move| pgn_move.san.as_str()).collect()
+    }
+
+    #[test]
+    fn tags_are_read_and_unescaped() {
+        let game = parse_one(ANNOTATED_GAME);
+        assert_eq!(game.tag("Event"), Some("Test \"quoted\""));
+        assert_eq!(game.tag("White"), Some("A"));
+        assert_eq!(game.tag("Black"), None);
+        assert_eq!(game.tags.len(), 3);
+    }
+
+    #[test]
+    fn comments_and_glyphs_are_attached_to_their_move() {
+        let game = parse_one(ANNOTATED_GAME);
+        assert_eq!(sans(&game.moves), ["e4", "e5", "Nf3", "Nc6", "Bb5"]);
+        assert_eq!(game.moves[0].nags, [1]);
+        assert_eq!(game.moves[0].comment.as_deref(), Some("Best by test"));
+        assert_eq!(game.moves[1].nags, [6]);
+        assert_eq!(game.moves[1].comment, None);
+        assert_eq!(game.moves[4].nags, [3]);
+        assert!(game.moves[3].nags.is_empty());
+    }
+
+    #[test]
+    fn suffixes_are_read_as_glyphs() {
+        for (suffix, nag) in &[
+            ("!", 1),
+            ("?", 2),
+            ("!!", 3),
+            ("??", 4),
+            ("!?", 5),
+            ("?!", 6),
+        ] {
+            let game = parse_one(&format!("1. e4{} *", suffix));
+            assert_eq!(game.moves[0].san, "e4");
+            assert_eq!(game.moves[0].nags, [*nag]);
+        }
+    }
+
+    #[test]
+    fn variations_are_nested_under_the_move_they_replace() {
+        let game = parse_one(ANNOTATED_GAME);
+        assert!(game.moves[1].variations.is_empty());
+        assert_eq!(game.moves[2].variations.len(), 1);
+        let variation = &game.moves[2].variations[0];
+        assert_eq!(sans(variation), ["f4", "exf4", "Nf3"]);
+        assert_eq!(variation[1].variations.len(), 1);
+        assert_eq!(sans(&variation[1].variations[0]), ["d5"]);
+    }
+
+    #[test]
+    fn results_end_the_games() {
+        let games = parse_pgn(
+            "[Event \"First\"]\n\n1. d4 d5 1/2-1/2\n\n[Event \"Second\"]\n\n1. c4 0-1\n\
+             [Event \"Third\"]\n\n1. Nf3\n\n[Event \"Fourth\"]\n\n1. g3\n",
+        )
+        .unwrap();
+        let results: Vec<&str> = games.iter().map(|game| game.result.as_str()).collect();
+        assert_eq!(results, ["1/2-1/2", "0-1", "*", "*"]);
+        assert_eq!(sans(&games[2].moves), ["Nf3"]);
+        assert_eq!(games[3].tag("Event"), Some("Fourth"));
+    }
+
+    #[test]
+    fn first_move_comes_from_the_fen_or_the_move_number() {
+        let game = parse_one(
+            "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\n12... Kd7 13. e4 *",
+        );
+        assert_eq!(game.first_move_number, 12);
+        assert!(game.black_starts);
+        assert_eq!(game.start_fen(), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12");
+        let game = parse_one("5. e4 *");
+        assert_eq!(game.first_move_number, 5);
+        assert!(!game.black_starts);
+        assert_eq!(game.start_fen(), STANDARD_START_FEN);
+    }
+
+    #[test]
+    fn malformed_games_are_located() {
+        assert_eq!(
+            parse_pgn("[Event \"?\"]\n\n1. e4 {unterminated\n"),
+            Err(PgnParseError::UnterminatedComment(3))
+        );
+        assert_eq!(
+            parse_pgn("[Event \"?\"\n"),
+            Err(PgnParseError::UnterminatedTag(1))
+        );
+        assert_eq!(
+            parse_pgn("1. e4\n(1. d4) *"),
+            Ok(parse_pgn("1. e4 (1. d4) *").unwrap())
+        );
+        assert_eq!(
+            parse_pgn("(1. d4) 1. e4 *"),
+            Err(PgnParseError::OrphanVariation(1))
+        );
+        assert_eq!(
+            parse_pgn("1. e4 e5)\n*"),
+            Err(PgnParseError::UnbalancedVariation(1))
+        );
+        assert_eq!(
+            parse_pgn("1. e4 (1. d4\n*"),
+            Err(PgnParseError::UnbalancedVariation(2))
+        );
+    }
+
+    #[test]
+    fn written_games_are_read_back_identically() {
+        let game = parse_one(ANNOTATED_GAME);
+        let text = game.to_pgn_string();
+        assert!(text.ends_with(
+            "\n1. e4 $1 {Best by test} 1... e5 $6 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) 2...\n\
+             Nc6 3. Bb5 $3 1-0\n"
+        ));
+        assert_eq!(parse_one(&text), game);
+        assert_eq!(parse_one(&text).to_pgn_string(), text);
+    }
+
+    #[test]
+    fn clock_comments_are_read_back() {
+        let mut logic = BoardLogic::new();
+        play(&mut logic, &["e2e4", "e7e5"]);
+        let start = Instant::now();
+        let mut clock = ChessClock::new(
+            TimeControl::sudden_death(Duration::from_secs(60)),
+            Player::White,
+        );
+        clock.start(start);
+        assert!(clock.press(start + Duration::from_secs(1)));
+        assert!(clock.press(start + Duration::from_secs(3)));
+        let mut game = PgnGame::from_board_logic(&logic, "*");
+        game.add_clock_times(&clock);
+
+        let read_game = parse_one(&game.to_pgn_string());
+        assert_eq!(read_game, game);
+        assert_eq!(read_game.tag("TimeControl"), Some("60"));
+        assert_eq!(
+            read_game.moves[1].comment.as_deref(),
+            Some("[%clk 0:00:58]")
+        );
+    }
+
+    #[test]
+    fn dates_are_computed_from_days_since_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+}
@@ -0,0 +1,569 @@
+use druid::Data;
+use log::error;
+
+use pleco::{BitMove, Player};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use super::pgn::{PgnGame, PgnMove};
+use super::san::{parse_move, PieceLetters};
+use super::BoardLogic;
+
+/// A move of the repertoire, with the number of lines of the file going on with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepertoireMove {
+    pub chess_move: BitMove,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepertoireError {
+    InvalidFen(String),
+    /// None of the games has a move.
+    Empty,
+    /// The first game is of Antichess, whose positions the tree cannot hold.
+    Antichess,
+}
+
+impl fmt::Display for RepertoireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepertoireError::InvalidFen(fen) => write!(f, "'{}' is not a valid position.", fen),
+            RepertoireError::Empty => write!(f, "The repertoire has no moves."),
+            RepertoireError::Antichess => write!(f, "Antichess games cannot make a repertoire."),
+        }
+    }
+}
+
+/// The tree of the prepared lines of a PGN file, main lines and variations of all its games,
+/// as the moves known in each position. Lines reaching the same position by different
+/// move orders share its moves.
+#[derive(Debug, Clone)]
+pub struct Repertoire {
+    /// Position of the first game, where every drilled line starts.
+    start: BoardLogic,
+    moves: HashMap<String, Vec<RepertoireMove>>,
+}
+
+impl Repertoire {
+    /// Reads the lines of the games. Games whose position is invalid are skipped, and a line
+    /// stops at its first move which cannot be played.
+    pub fn from_games(games: &[PgnGame]) -> Result<Self, RepertoireError> {
+        let first_game = games.first().ok_or(RepertoireError::Empty)?;
+        let start = first_game
+            .start_board()
+            .ok_or_else(|| RepertoireError::InvalidFen(first_game.start_fen().to_string()))?;
+        if start.antichess.is_some() {
+            return Err(RepertoireError::Antichess);
+        }
+        let mut repertoire = Repertoire {
+            start,
+            moves: HashMap::new(),
+        };
+        for (index, game) in games.iter().enumerate() {
+            match game.start_board() {
+                Some(board) if board.antichess.is_some() => {
+                    error!(
+                        "Skipping game {} of the repertoire : it is of Antichess.",
+                        index + 1
+                    )
+                }
+                Some(board) => {
+                    repertoire.add_line(&board, &game.moves);
+                }
+                None => error!(
+                    "Skipping game {} of the repertoire : '{}' is not a valid position.",
+                    index + 1,
+                    game.start_fen()
+                ),
+            }
+        }
+        if repertoire.moves.is_empty() {
+            return Err(RepertoireError::Empty);
+        }
+        Ok(repertoire)
+    }
+
+    /// Adds the moves of a line and of its variations, giving the number of lines
+    /// which go on from the position before the line.
+    fn add_line(&mut self, board: &BoardLogic, line: &[PgnMove]) -> u32 {
+        let pgn_move = match line.first() {
+            Some(pgn_move) => pgn_move,
+            None => return 1,
+        };
+        let mut lines: u32 = pgn_move
+            .variations
+            .iter()
+            .map(|variation| self.add_line(board, variation))
+            .sum();
+        match parse_move(board, &pgn_move.san, &PieceLetters::ENGLISH) {
+            Ok(chess_move) => {
+                let mut next_board = board.clone();
+                next_board.play_move(chess_move);
+                let weight = self.add_line(&next_board, &line[1..]);
+                self.add_move(board, chess_move, weight);
+                lines += weight;
+            }
+            Err(err) => {
+                error!("Repertoire line cut at {} : {}", pgn_move.san, err);
+                lines += 1;
+            }
+        }
+        lines
+    }
+
+    fn add_move(&mut self, board: &BoardLogic, chess_move: BitMove, weight: u32) {
+        let moves = self.moves.entry(board.position_key()).or_default();
+        match moves
+            .iter_mut()
+            .find(|known| known.chess_move == chess_move)
+        {
+            Some(known) => known.weight += weight,
+            None => moves.push(RepertoireMove { chess_move, weight }),
+        }
+    }
+
+    /// The start position, with the castling of Chess960 if the first game follows its rules.
+    pub fn start_board(&self) -> BoardLogic {
+        self.start.clone()
+    }
+
+    /// The moves of the repertoire in the position, none when it is out of the repertoire.
+    pub fn moves(&self, board: &BoardLogic) -> &[RepertoireMove] {
+        self.moves
+            .get(&board.position_key())
+            .map(|moves| moves.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The keys of the positions where the side has moves in the repertoire.
+    pub fn positions_of(&self, side: Player) -> Vec<&str> {
+        let turn = match side {
+            Player::White => "w",
+            Player::Black => "b",
+        };
+        self.moves
+            .keys()
+            .filter(|key| key.split_whitespace().nth(1) == Some(turn))
+            .map(|key| key.as_str())
+            .collect()
+    }
+
+    /// Whether a position where the side has to answer, due for review, can be reached
+    /// from the position.
+    fn leads_to_due(
+        &self,
+        board: &BoardLogic,
+        side: Player,
+        is_due: &dyn Fn(&str) -> bool,
+        visited: &mut HashSet<String>,
+    ) -> bool {
+        let key = board.position_key();
+        if !visited.insert(key.clone()) {
+            return false;
+        }
+        let moves = self.moves(board);
+        if board.turn() == side && !moves.is_empty() && is_due(&key) {
+            return true;
+        }
+        moves.iter().any(|known| {
+            let mut next_board = board.clone();
+            next_board.play_move(known.chess_move);
+            self.leads_to_due(&next_board, side, is_due, visited)
+        })
+    }
+}
+
+/// What a move of the user was, in a position of the repertoire.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrillStep {
+    /// A move of the repertoire, found at the first try of the position when the flag is set.
+    Correct { first_try: bool },
+    /// Not a move of the repertoire, which are given. The user tries again the position,
+    /// missed for the first time when the flag is set.
+    Wrong {
+        expected: Vec<BitMove>,
+        first_miss: bool,
+    },
+}
+
+/// Drills one side of a repertoire : the opponent plays moves of the tree, the ones leading
+/// to positions due for review first, and the user must answer with a repertoire move.
+#[derive(Debug, Clone)]
+pub struct RepertoireDrill {
+    repertoire: Arc<Repertoire>,
+    side: Player,
+    /// Whether the opponent prefers the moves played by more lines of the file.
+    weighted: bool,
+    /// Whether a wrong move was played in the current position.
+    missed: bool,
+    correct: u32,
+    mistakes: u32,
+}
+
+impl RepertoireDrill {
+    pub fn new(repertoire: Arc<Repertoire>, side: Player, weighted: bool) -> Self {
+        RepertoireDrill {
+            repertoire,
+            side,
+            weighted,
+            missed: false,
+            correct: 0,
+            mistakes: 0,
+        }
+    }
+
+    pub fn repertoire(&self) -> &Repertoire {
+        &self.repertoire
+    }
+
+    pub fn side(&self) -> Player {
+        self.side
+    }
+
+    /// Positions answered at the first try.
+    pub fn correct(&self) -> u32 {
+        self.correct
+    }
+
+    pub fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    /// Whether the user has a repertoire move to find in the position.
+    pub fn expects_answer(&self, board: &BoardLogic) -> bool {
+        board.turn() == self.side && !self.repertoire.moves(board).is_empty()
+    }
+
+    /// Checks a move of the user in the position.
+    pub fn play(&mut self, board: &BoardLogic, chess_move: BitMove) -> DrillStep {
+        let expected: Vec<BitMove> = self
+            .repertoire
+            .moves(board)
+            .iter()
+            .map(|known| known.chess_move)
+            .collect();
+        if expected.contains(&chess_move) {
+            let first_try = !self.missed;
+            if first_try {
+                self.correct += 1;
+            }
+            self.missed = false;
+            DrillStep::Correct { first_try }
+        } else {
+            let first_miss = !self.missed;
+            if first_miss {
+                self.mistakes += 1;
+            }
+            self.missed = true;
+            DrillStep::Wrong {
+                expected,
+                first_miss,
+            }
+        }
+    }
+
+    /// A move of the opponent from the tree, picked from a random number among the moves
+    /// leading to positions due for review, or among all of them when none does.
+    pub fn opponent_move(
+        &self,
+        board: &BoardLogic,
+        random: u64,
+        is_due: &dyn Fn(&str) -> bool,
+    ) -> Option<BitMove> {
+        if board.turn() == self.side {
+            return None;
+        }
+        let moves = self.repertoire.moves(board);
+        let due_moves: Vec<RepertoireMove> = moves
+            .iter()
+            .filter(|known| {
+                let mut next_board = board.clone();
+                next_board.play_move(known.chess_move);
+                self.repertoire
+                    .leads_to_due(&next_board, self.side, is_due, &mut HashSet::new())
+            })
+            .cloned()
+            .collect();
+        let candidates = if due_moves.is_empty() {
+            moves
+        } else {
+            &due_moves
+        };
+        pick_move(candidates, self.weighted, random)
+    }
+}
+
+impl Data for RepertoireDrill {
+    fn same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.repertoire, &other.repertoire)
+            && self.side == other.side
+            && self.weighted == other.weighted
+            && self.missed == other.missed
+            && self.correct == other.correct
+            && self.mistakes == other.mistakes
+    }
+}
+
+/// Picks a move from a random number, each one as likely as its weight when weighted.
+fn pick_move(moves: &[RepertoireMove], weighted: bool, random: u64) -> Option<BitMove> {
+    if moves.is_empty() {
+        return None;
+    }
+    if !weighted {
+        return Some(moves[(random % moves.len() as u64) as usize].chess_move);
+    }
+    let total: u64 = moves.iter().map(|known| u64::from(known.weight)).sum();
+    let mut remaining = random % total.max(1);
+    for known in moves {
+        let weight = u64::from(known.weight);
+        if remaining < weight {
+            return Some(known.chess_move);
+        }
+        remaining -= weight;
+    }
+    moves.last().map(|known| known.chess_move)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    /// Two games sharing `1. e4`, a variation, and two move orders reaching the same position.
+    const REPERTOIRE: &str = "1. e4 e5 (1... c5 2. Nf3) 2. Nf3 Nc6 *\n\n\
+                              1. e4 c5 2. Nf3 d6 *\n\n\
+                              1. Nf3 Nf6 2. g3 g6 3. Bg2 *\n\n\
+                              1. g3 g6 2. Nf3 Nf6 3. Bg2 *\n";
+
+    fn repertoire() -> Repertoire {
+        Repertoire::from_games(&parse_pgn(REPERTOIRE).unwrap()).unwrap()
+    }
+
+    fn board_after(uci_moves: &[&str]) -> BoardLogic {
+        let mut board = BoardLogic::new();
+        for uci in uci_moves {
+            let chess_move = find_uci_move(&board, uci).unwrap();
+            board.play_move(chess_move);
+        }
+        board
+    }
+
+    fn uci_move(board: &BoardLogic, uci: &str) -> BitMove {
+        find_uci_move(board, uci).unwrap()
+    }
+
+    /// The moves of the position in UCI notation, with their weights.
+    fn weights(repertoire: &Repertoire, board: &BoardLogic) -> Vec<(String, u32)> {
+        repertoire
+            .moves(board)
+            .iter()
+            .map(|known| (known.chess_move.stringify(), known.weight))
+            .collect()
+    }
+
+    fn weighted(uci: &str, weight: u32) -> (String, u32) {
+        (uci.to_string(), weight)
+    }
+
+    #[test]
+    fn weights_count_the_lines_going_on_with_a_move() {
+        let repertoire = repertoire();
+        assert_eq!(
+            weights(&repertoire, &BoardLogic::new()),
+            [
+                weighted("e2e4", 3),
+                weighted("g1f3", 1),
+                weighted("g2g3", 1)
+            ]
+        );
+        // The variation is read before the move it replaces.
+        assert_eq!(
+            weights(&repertoire, &board_after(&["e2e4"])),
+            [weighted("c7c5", 2), weighted("e7e5", 1)]
+        );
+        assert_eq!(
+            weights(&repertoire, &board_after(&["e2e4", "c7c5"])),
+            [weighted("g1f3", 2)]
+        );
+        assert_eq!(
+            weights(&repertoire, &board_after(&["e2e4", "c7c5", "g1f3"])),
+            [weighted("d7d6", 1)]
+        );
+        assert!(repertoire
+            .moves(&board_after(&["e2e4", "c7c5", "g1f3", "d7d6"]))
+            .is_empty());
+    }
+
+    #[test]
+    fn transpositions_share_their_moves() {
+        let repertoire = repertoire();
+        let first_order = board_after(&["g1f3", "g8f6", "g2g3", "g7g6"]);
+        let second_order = board_after(&["g2g3", "g7g6", "g1f3", "g8f6"]);
+        assert_ne!(first_order.fen(), second_order.fen());
+        assert_eq!(first_order.position_key(), second_order.position_key());
+        assert_eq!(weights(&repertoire, &first_order), [weighted("f1g2", 2)]);
+    }
+
+    #[test]
+    fn positions_are_listed_by_side_to_move() {
+        let repertoire = repertoire();
+        let white_positions = repertoire.positions_of(Player::White);
+        let black_positions = repertoire.positions_of(Player::Black);
+        assert!(white_positions.contains(&BoardLogic::new().position_key().as_str()));
+        assert!(black_positions.contains(&board_after(&["e2e4"]).position_key().as_str()));
+        // Start, 1. e4 e5, 1. e4 c5, 1. Nf3 Nf6, 1. g3 g6 and the transposition.
+        assert_eq!(white_positions.len(), 6);
+        assert_eq!(
+            white_positions.len() + black_positions.len(),
+            repertoire.moves.len()
+        );
+    }
+
+    #[test]
+    fn lines_stop_at_their_first_illegal_move() {
+        let games = parse_pgn("1. e4 Ke7 2. Nf3 *").unwrap();
+        let repertoire = Repertoire::from_games(&games).unwrap();
+        assert_eq!(
+            weights(&repertoire, &BoardLogic::new()),
+            [weighted("e2e4", 1)]
+        );
+        assert!(repertoire.moves(&board_after(&["e2e4"])).is_empty());
+    }
+
+    #[test]
+    fn repertoires_need_moves_and_a_valid_start() {
+        assert_eq!(
+            Repertoire::from_games(&[]).unwrap_err(),
+            RepertoireError::Empty
+        );
+        let no_moves = parse_pgn("[Event \"?\"]\n\n*").unwrap();
+        assert_eq!(
+            Repertoire::from_games(&no_moves).unwrap_err(),
+            RepertoireError::Empty
+        );
+        let invalid = parse_pgn("[FEN \"not a position\"]\n\n1. e4 *").unwrap();
+        assert_eq!(
+            Repertoire::from_games(&invalid).unwrap_err(),
+            RepertoireError::InvalidFen(String::from("not a position"))
+        );
+    }
+
+    #[test]
+    fn moves_are_picked_by_weight_or_evenly() {
+        let board = BoardLogic::new();
+        let e4 = uci_move(&board, "e2e4");
+        let d4 = uci_move(&board, "d2d4");
+        let moves = [
+            RepertoireMove {
+                chess_move: e4,
+                weight: 3,
+            },
+            RepertoireMove {
+                chess_move: d4,
+                weight: 1,
+            },
+        ];
+        let weighted_picks: Vec<_> = (0..8)
+            .map(|random| pick_move(&moves, true, random))
+            .collect();
+        assert_eq!(
+            weighted_picks,
+            [e4, e4, e4, d4, e4, e4, e4, d4]
+                .iter()
+                .map(|chess_move| Some(*chess_move))
+                .collect::<Vec<_>>()
+        );
+        let even_picks: Vec<_> = (0..4)
+            .map(|random| pick_move(&moves, false, random))
+            .collect();
+        assert_eq!(even_picks, [Some(e4), Some(d4), Some(e4), Some(d4)]);
+        assert_eq!(pick_move(&[], true, 7), None);
+        assert_eq!(pick_move(&[], false, 7), None);
+    }
+
+    #[test]
+    fn answers_count_once_per_position() {
+        let mut drill = RepertoireDrill::new(Arc::new(repertoire()), Player::White, true);
+        let board = BoardLogic::new();
+        assert!(drill.expects_answer(&board));
+        assert_eq!(
+            drill.play(&board, uci_move(&board, "e2e4")),
+            DrillStep::Correct { first_try: true }
+        );
+        let d4 = uci_move(&board, "d2d4");
+        let expected = drill
+            .repertoire()
+            .moves(&board)
+            .iter()
+            .map(|known| known.chess_move)
+            .collect();
+        assert_eq!(
+            drill.play(&board, d4),
+            DrillStep::Wrong {
+                expected,
+                first_miss: true
+            }
+        );
+        assert!(matches!(
+            drill.play(&board, d4),
+            DrillStep::Wrong {
+                first_miss: false,
+                ..
+            }
+        ));
+        assert_eq!(
+            drill.play(&board, uci_move(&board, "g2g3")),
+            DrillStep::Correct { first_try: false }
+        );
+        assert_eq!((drill.correct(), drill.mistakes()), (1, 1));
+        assert!(!drill.expects_answer(&board_after(&["e2e4"])));
+    }
+
+    #[test]
+    fn opponent_leads_to_the_positions_due_for_review() {
+        let drill = RepertoireDrill::new(Arc::new(repertoire()), Player::White, true);
+        let board = board_after(&["e2e4"]);
+        let e5 = uci_move(&board, "e7e5");
+        let c5 = uci_move(&board, "c7c5");
+        let due_key = board_after(&["e2e4", "e7e5"]).position_key();
+        let is_due = |key: &str| key == due_key;
+        for random in 0..6 {
+            assert_eq!(drill.opponent_move(&board, random, &is_due), Some(e5));
+        }
+        // A position due deeper in the tree is reached as well.
+        let deep_key = board_after(&["e2e4", "c7c5", "g1f3", "d7d6"]).position_key();
+        let deep_due = |key: &str| key == deep_key;
+        assert_eq!(drill.opponent_move(&board, 0, &deep_due), Some(c5));
+        // Positions of the opponent are never due for the side drilled.
+        let black_key = board_after(&["e2e4", "c7c5", "g1f3"]).position_key();
+        let black_due = |key: &str| key == black_key;
+        let picks: Vec<_> = (0..3)
+            .map(|random| drill.opponent_move(&board, random, &black_due))
+            .collect();
+        assert_eq!(picks, [Some(c5), Some(c5), Some(e5)]);
+    }
+
+    #[test]
+    fn opponent_only_plays_in_its_positions_of_the_tree() {
+        let drill = RepertoireDrill::new(Arc::new(repertoire()), Player::White, false);
+        let nothing_due = |_: &str| false;
+        assert_eq!(
+            drill.opponent_move(&BoardLogic::new(), 0, &nothing_due),
+            None
+        );
+        assert_eq!(
+            drill.opponent_move(&board_after(&["d2d4"]), 0, &nothing_due),
+            None
+        );
+        let board = board_after(&["e2e4"]);
+        assert_eq!(
+            drill.opponent_move(&board, 1, &nothing_due),
+            Some(uci_move(&board, "e7e5"))
+        );
+    }
+}
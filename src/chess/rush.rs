@@ -0,0 +1,296 @@
+use druid::Data;
+
+use pleco::BitMove;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::engine::EngineKind;
+use super::exercise::{
+    AcceptancePolicy, Exercise, ExerciseAttempt, JudgedMove, MoveJudging, MoveVerdict,
+};
+
+/// Number of wrong moves which end a rush.
+pub const ALLOWED_STRIKES: u32 = 3;
+
+/// What happened after a move of the solver during a rush.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RushStep {
+    /// The expected move, the exercise going on with the reply of the opponent, if any.
+    Continue(Option<BitMove>),
+    /// The exercise is solved, and the next one is set up unless the rush is over.
+    Solved,
+    /// A strike : the next exercise is set up unless the rush is over.
+    Failed,
+    /// The engine judges the move, which is neither played nor counted until its judgement
+    /// is given to `PuzzleRush::conclude`.
+    Judging,
+}
+
+/// A sequence of exercises to solve before a global countdown ends,
+/// with at most `ALLOWED_STRIKES` mistakes. Each mistake moves on to the next exercise.
+#[derive(Debug, Clone)]
+pub struct PuzzleRush {
+    /// Name of the collection the exercises come from.
+    collection: String,
+    exercises: Arc<Vec<Exercise>>,
+    current: usize,
+    attempt: ExerciseAttempt,
+    /// Moves accepted besides the ones of the solutions, and the engine judging them.
+    policy: AcceptancePolicy,
+    engine: EngineKind,
+    /// The move being judged by the engine, during which no other move is checked.
+    judging: Option<MoveJudging>,
+    duration: Duration,
+    started: Instant,
+    elapsed: Duration,
+    score: u32,
+    strikes: u32,
+    /// Peeks of the previous exercises, those of the current one being counted by its attempt.
+    past_peeks: u32,
+    finished: bool,
+}
+
+impl PuzzleRush {
+    /// Starts a rush over the exercises of the collection, which must not be empty.
+    pub fn new(
+        collection: String,
+        exercises: Arc<Vec<Exercise>>,
+        policy: AcceptancePolicy,
+        engine: EngineKind,
+        duration: Duration,
+        now: Instant,
+    ) -> Self {
+        PuzzleRush {
+            collection,
+            exercises,
+            current: 0,
+            attempt: ExerciseAttempt::new(),
+            policy,
+            engine,
+            judging: None,
+            duration,
+            started: now,
+            elapsed: Duration::default(),
+            score: 0,
+            strikes: 0,
+            past_peeks: 0,
+            finished: false,
+        }
+    }
+
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    pub fn current_exercise(&self) -> &Exercise {
+        &self.exercises[self.current]
+    }
+
+    /// Number of the current exercise, starting from 1, and number of exercises.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.current + 1, self.exercises.len())
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn strikes(&self) -> u32 {
+        self.strikes
+    }
+
+    /// Number of times the hidden pieces were revealed during the rush.
+    pub fn peeks(&self) -> u32 {
+        self.past_peeks + self.attempt.peeks
+    }
+
+    pub fn record_peek(&mut self) {
+        if !self.finished {
+            self.attempt.peeks += 1;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn engine(&self) -> &EngineKind {
+        &self.engine
+    }
+
+    /// The move waiting for the judgement of the engine, if any.
+    pub fn judging(&self) -> Option<&MoveJudging> {
+        self.judging.as_ref()
+    }
+
+    /// Time left, as of the last tick.
+    pub fn remaining(&self) -> Duration {
+        self.duration.checked_sub(self.elapsed).unwrap_or_default()
+    }
+
+    /// Updates the countdown, and returns true if the time has just run out.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.finished {
+            return false;
+        }
+        self.elapsed = now.saturating_duration_since(self.started);
+        if self.elapsed >= self.duration {
+            self.finished = true;
+            return true;
+        }
+        false
+    }
+
+    /// Checks a move of the solver in the current exercise, moving on to the next exercise
+    /// once it is solved or failed. Moves left to the engine are judged in another thread.
+    pub fn play(&mut self, chess_move: BitMove, now: Instant) -> RushStep {
+        if self.tick(now) || self.finished {
+            return RushStep::Failed;
+        }
+        if self.judging.is_some() {
+            return RushStep::Judging;
+        }
+        let exercise = &self.exercises[self.current];
+        let verdict = self.attempt.check_move(exercise, chess_move, &self.policy);
+        self.apply_verdict(verdict)
+    }
+
+    /// Ends the check of the move judged by the engine. Gives none when the judgement is not
+    /// about the move waiting for it, or when the rush ended meanwhile.
+    pub fn conclude(&mut self, judged: &JudgedMove, now: Instant) -> Option<RushStep> {
+        let waiting = self.judging.as_ref().map_or(false, |judging| {
+            judging.fen() == judged.fen && judging.chess_move() == judged.chess_move
+        });
+        if !waiting {
+            return None;
+        }
+        self.judging = None;
+        if self.tick(now) || self.finished {
+            return None;
+        }
+        let exercise = &self.exercises[self.current];
+        let verdict = self.attempt.conclude(exercise, judged);
+        Some(self.apply_verdict(verdict))
+    }
+
+    fn apply_verdict(&mut self, verdict: MoveVerdict) -> RushStep {
+        let step = match verdict {
+            MoveVerdict::Wrong => {
+                self.strikes += 1;
+                RushStep::Failed
+            }
+            MoveVerdict::Correct { solved: true, .. } => {
+                self.score += 1;
+                RushStep::Solved
+            }
+            MoveVerdict::Correct { reply, .. } => return RushStep::Continue(reply),
+            MoveVerdict::Judging(judging) => {
+                self.judging = Some(judging);
+                return RushStep::Judging;
+            }
+        };
+        self.current += 1;
+        self.past_peeks += self.attempt.peeks;
+        self.attempt = ExerciseAttempt::new();
+        if self.strikes >= ALLOWED_STRIKES || self.current >= self.exercises.len() {
+            // The last exercise stays the current one, to be shown once the rush is over.
+            self.current -= 1;
+            self.finished = true;
+        }
+        step
+    }
+}
+
+/// Compares what is shown : the exercise, the countdown, the score and the strikes,
+/// and the move being judged.
+impl Data for PuzzleRush {
+    fn same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.exercises, &other.exercises)
+            && self.current == other.current
+            && self.attempt == other.attempt
+            && self.judging == other.judging
+            && self.elapsed == other.elapsed
+            && self.score == other.score
+            && self.strikes == other.strikes
+            && self.past_peeks == other.past_peeks
+            && self.finished == other.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    fn rush(now: Instant) -> PuzzleRush {
+        let games = parse_pgn("1. e4 e5 2. Nf3 *\n\n1. d4 d5 *").unwrap();
+        let exercises = games
+            .into_iter()
+            .map(|game| Exercise::from_pgn_game(game).unwrap())
+            .collect();
+        let policy = AcceptancePolicy {
+            eval_tolerance: Some(50),
+            ..AcceptancePolicy::default()
+        };
+        PuzzleRush::new(
+            String::from("Test"),
+            Arc::new(exercises),
+            policy,
+            EngineKind::Internal,
+            Duration::from_secs(60),
+            now,
+        )
+    }
+
+    #[test]
+    fn moves_wait_for_their_judgement() {
+        let now = Instant::now();
+        let mut rush = rush(now);
+        let board = rush.current_exercise().start_position();
+        let c3 = find_uci_move(&board, "c2c3").unwrap();
+        let e4 = find_uci_move(&board, "e2e4").unwrap();
+
+        assert_eq!(rush.play(c3, now), RushStep::Judging);
+        assert_eq!(rush.judging().map(|judging| judging.chess_move()), Some(c3));
+        // No other move is checked meanwhile.
+        assert_eq!(rush.play(e4, now), RushStep::Judging);
+        assert_eq!(rush.judging().map(|judging| judging.chess_move()), Some(c3));
+
+        let judged = JudgedMove {
+            fen: board.fen(),
+            chess_move: e4,
+            alternative: None,
+        };
+        assert_eq!(rush.conclude(&judged, now), None);
+        let judged = JudgedMove {
+            chess_move: c3,
+            ..judged
+        };
+        assert_eq!(rush.conclude(&judged, now), Some(RushStep::Failed));
+        assert_eq!(rush.strikes(), 1);
+        assert!(rush.judging().is_none());
+        assert_eq!(rush.progress(), (2, 2));
+        // A judgement given twice is ignored.
+        assert_eq!(rush.conclude(&judged, now), None);
+    }
+
+    #[test]
+    fn judgements_coming_after_the_end_are_ignored() {
+        let now = Instant::now();
+        let mut rush = rush(now);
+        let board = rush.current_exercise().start_position();
+        let c3 = find_uci_move(&board, "c2c3").unwrap();
+        assert_eq!(rush.play(c3, now), RushStep::Judging);
+        let judged = JudgedMove {
+            fen: board.fen(),
+            chess_move: c3,
+            alternative: None,
+        };
+        assert_eq!(rush.conclude(&judged, now + Duration::from_secs(61)), None);
+        assert!(rush.is_finished());
+        assert_eq!(rush.strikes(), 0);
+    }
+}
@@ -0,0 +1,543 @@
+use pleco::core::piece_move::BitMove;
+use pleco::core::{sq::SQ, PieceType};
+
+use super::antichess::{is_capture, promotion_type};
+use super::variant::Variant;
+use super::BoardLogic;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveParseError {
+    Empty,
+    InvalidSyntax(String),
+    Illegal(String),
+    Ambiguous(String, Vec<String>),
+    MissingPromotion(String),
+}
+
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveParseError::Empty => write!(f, "Please type a move."),
+            MoveParseError::InvalidSyntax(input) => {
+                write!(f, "'{}' is not a valid move notation.", input)
+            }
+            MoveParseError::Illegal(input) => {
+                write!(f, "'{}' is not a legal move in this position.", input)
+            }
+            MoveParseError::Ambiguous(input, candidates) => write!(
+                f,
+                "'{}' is ambiguous : it could be {}.",
+                input,
+                candidates.join(" or ")
+            ),
+            MoveParseError::MissingPromotion(input) => write!(
+                f,
+                "'{}' needs a promotion piece, for example {}=Q.",
+                input, input
+            ),
+        }
+    }
+}
+
+/// Letters naming the king, queen, rook, bishop and knight in algebraic notation,
+/// which depend on the language : `KQRBN` in English, `RDTFC` in French.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PieceLetters {
+    letters: [char; 5],
+}
+
+const LETTERED_PIECES_TYPES: [PieceType; 5] = [
+    PieceType::K,
+    PieceType::Q,
+    PieceType::R,
+    PieceType::B,
+    PieceType::N,
+];
+
+impl PieceLetters {
+    /// The letters of the PGN standard, whatever the language of the user.
+    pub const ENGLISH: PieceLetters = PieceLetters {
+        letters: ['K', 'Q', 'R', 'B', 'N'],
+    };
+
+    /// Reads five distinct uppercase letters, given for the king, queen, rook, bishop and knight.
+    pub fn from_letters(letters: &str) -> Option<Self> {
+        let chars: Vec<char> = letters.trim().chars().collect();
+        let valid = chars.len() == 5
+            && chars.iter().all(|letter| letter.is_uppercase())
+            && chars
+                .iter()
+                .enumerate()
+                .all(|(index, letter)| !chars[index + 1..].contains(letter));
+        if !valid {
+            return None;
+        }
+        let mut result = [' '; 5];
+        result.copy_from_slice(&chars);
+        Some(PieceLetters { letters: result })
+    }
+
+    pub fn letter(&self, piece_type: PieceType) -> char {
+        LETTERED_PIECES_TYPES
+            .iter()
+            .position(|lettered| *lettered == piece_type)
+            .map(|index| self.letters[index])
+            .unwrap_or_else(|| piece_type.char_upper())
+    }
+
+    fn piece_type(&self, letter: char) -> Option<PieceType> {
+        match self.letters.iter().position(|known| *known == letter) {
+            Some(index) => Some(LETTERED_PIECES_TYPES[index]),
+            None if letter == 'P' => Some(PieceType::P),
+            None => None,
+        }
+    }
+}
+
+/// Formats a legal move of the game on the board in Standard Algebraic Notation, naming
+/// the pieces with the given letters and following the rules of its variant : Antichess
+/// has no check, so its moves have no check suffix.
+pub fn move_to_san(logic: &BoardLogic, chess_move: BitMove, letters: &PieceLetters) -> String {
+    let moves = logic.legal_moves();
+    let piece_type_at = |square: SQ| logic.piece_at(square).type_of();
+    let mut san = san_without_suffix(chess_move, &moves, piece_type_at, letters);
+
+    if logic.variant != Variant::Antichess {
+        let mut logic_after_move = logic.clone();
+        logic_after_move.play_move(chess_move);
+        san.push_str(check_suffix(&logic_after_move));
+    }
+    san
+}
+
+/// The move without its check or checkmate suffix, the other legal moves telling
+/// whether its origin must be given, and the pieces standing on the squares their type.
+fn san_without_suffix(
+    chess_move: BitMove,
+    moves: &[BitMove],
+    piece_type_at: impl Fn(SQ) -> PieceType,
+    letters: &PieceLetters,
+) -> String {
+    if chess_move.is_king_castle() {
+        return String::from("O-O");
+    } else if chess_move.is_queen_castle() {
+        return String::from("O-O-O");
+    }
+    let piece_type = piece_type_at(chess_move.get_src());
+    let origin = chess_move.get_src();
+    let destination = chess_move.get_dest();
+    let mut san = String::new();
+
+    if piece_type == PieceType::P {
+        if is_capture(chess_move) {
+            san.push(file_char(origin));
+            san.push('x');
+        }
+        san.push_str(&destination.to_string());
+        if let Some(promotion) = promotion_type(chess_move) {
+            san.push('=');
+            san.push(letters.letter(promotion));
+        }
+    } else {
+        san.push(letters.letter(piece_type));
+        let rivals: Vec<SQ> = moves
+            .iter()
+            .filter(|other| {
+                !other.is_castle()
+                    && other.get_dest() == destination
+                    && other.get_src() != origin
+                    && piece_type_at(other.get_src()) == piece_type
+            })
+            .map(|other| other.get_src())
+            .collect();
+        san.push_str(&disambiguation(origin, &rivals));
+        if is_capture(chess_move) {
+            san.push('x');
+        }
+        san.push_str(&destination.to_string());
+    }
+    san
+}
+
+fn check_suffix(board_after_move: &BoardLogic) -> &'static str {
+    if board_after_move.is_checkmate() {
+        "#"
+    } else if board_after_move.in_check() {
+        "+"
+    } else {
+        ""
+    }
+}
+
+/// Origin file and/or rank needed to tell the move apart from the moves
+/// of the other pieces of the same type going to the same cell.
+fn disambiguation(origin: SQ, rivals: &[SQ]) -> String {
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|rival| rival.file() != origin.file()) {
+        file_char(origin).to_string()
+    } else if rivals.iter().all(|rival| rival.rank() != origin.rank()) {
+        rank_char(origin).to_string()
+    } else {
+        origin.to_string()
+    }
+}
+
+/// Parses a move typed by the user, either in Standard Algebraic Notation
+/// (`Nf3`, `exd5`, `e8=Q+`, `O-O`) or in UCI notation (`e2e4`, `e7e8q`),
+/// and finds the matching legal move. SAN pieces are named with the given letters.
+/// In UCI notation, a castle goes from the king to its rook or to its castling square.
+pub fn parse_move(
+    board: &BoardLogic,
+    input: &str,
+    letters: &PieceLetters,
+) -> Result<BitMove, MoveParseError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(MoveParseError::Empty);
+    }
+    if is_uci_notation(input) {
+        let uci_move = input.to_lowercase();
+        let chars: Vec<char> = uci_move.chars().collect();
+        let castle = || {
+            let origin = square_from_chars(chars[0], chars[1])?;
+            let destination = square_from_chars(chars[2], chars[3])?;
+            board
+                .dragged_move(origin, destination)
+                .filter(|chess_move| chess_move.is_castle() && chars.len() == 4)
+        };
+        return find_uci_move(board, &uci_move)
+            .or_else(castle)
+            .ok_or_else(|| MoveParseError::Illegal(input.to_string()));
+    }
+    parse_san(board, input, letters)
+}
+
+/// The UCI notation of a move. A castle of Chess960 is written as the king taking its rook,
+/// as engines expect with their `UCI_Chess960` option, since the king may not move at all.
+pub fn move_to_uci(board: &BoardLogic, chess_move: BitMove) -> String {
+    if board.is_chess960() && chess_move.is_castle() {
+        format!("{}{}", chess_move.get_src(), chess_move.get_dest())
+    } else if promotion_type(chess_move) == Some(PieceType::K) {
+        format!("{}{}k", chess_move.get_src(), chess_move.get_dest())
+    } else {
+        chess_move.stringify()
+    }
+}
+
+/// The legal move which has the given UCI notation, if any.
+pub fn find_uci_move(board: &BoardLogic, uci_move: &str) -> Option<BitMove> {
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|chess_move| move_to_uci(board, *chess_move) == uci_move)
+}
+
+fn is_uci_notation(input: &str) -> bool {
+    let chars: Vec<char> = input.chars().collect();
+    let is_square =
+        |file: char, rank: char| ('a'..='h').contains(&file) && ('1'..='8').contains(&rank);
+    match chars.len() {
+        4 => is_square(chars[0], chars[1]) && is_square(chars[2], chars[3]),
+        5 => {
+            is_square(chars[0], chars[1])
+                && is_square(chars[2], chars[3])
+                && "qrbnQRBN".contains(chars[4])
+        }
+        _ => false,
+    }
+}
+
+fn parse_san(
+    logic: &BoardLogic,
+    input: &str,
+    letters: &PieceLetters,
+) -> Result<BitMove, MoveParseError> {
+    let invalid = || MoveParseError::InvalidSyntax(input.to_string());
+    let notation = input.trim_end_matches(|c| "+#!?".contains(c));
+
+    match notation {
+        "O-O" | "0-0" | "o-o" => return find_castle(logic, input, true),
+        "O-O-O" | "0-0-0" | "o-o-o" => return find_castle(logic, input, false),
+        _ => {}
+    }
+
+    let mut chars: Vec<char> = notation.chars().collect();
+
+    let piece_type = match chars.first().and_then(|first| letters.piece_type(*first)) {
+        Some(piece_type) => {
+            chars.remove(0);
+            piece_type
+        }
+        None => PieceType::P,
+    };
+
+    let mut promotion = None;
+    if piece_type == PieceType::P {
+        if let Some(last) = chars.last() {
+            if let Some(promotion_type) = letters.piece_type(last.to_ascii_uppercase()) {
+                // A promotion to a king is legal in Antichess only.
+                if promotion_type != PieceType::P {
+                    promotion = Some(promotion_type);
+                    chars.pop();
+                    if chars.last() == Some(&'=') {
+                        chars.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    if chars.len() < 2 {
+        return Err(invalid());
+    }
+    let rank = chars.pop().unwrap();
+    let file = chars.pop().unwrap();
+    let destination = square_from_chars(file, rank).ok_or_else(invalid)?;
+
+    let mut origin_file = None;
+    let mut origin_rank = None;
+    for character in chars {
+        match character {
+            'a'..='h' => origin_file = Some(character as u8 - b'a'),
+            '1'..='8' => origin_rank = Some(character as u8 - b'1'),
+            'x' | 'X' | ':' | '-' => {}
+            _ => return Err(invalid()),
+        }
+    }
+
+    let candidates: Vec<BitMove> = logic
+        .legal_moves()
+        .into_iter()
+        .filter(|chess_move| {
+            let origin = chess_move.get_src();
+            !chess_move.is_castle()
+                && chess_move.get_dest() == destination
+                && logic.piece_at(origin).type_of() == piece_type
+                && origin_file.map_or(true, |file| origin.file_idx_of_sq() == file)
+                && origin_rank.map_or(true, |rank| origin.rank_idx_of_sq() == rank)
+        })
+        .collect();
+
+    let is_promotion = candidates
+        .iter()
+        .any(|chess_move| promotion_type(*chess_move).is_some());
+    if is_promotion && promotion.is_none() {
+        return Err(MoveParseError::MissingPromotion(notation.to_string()));
+    }
+    let candidates: Vec<BitMove> = candidates
+        .into_iter()
+        .filter(|chess_move| promotion_type(*chess_move) == promotion)
+        .collect();
+
+    match candidates.len() {
+        0 => Err(MoveParseError::Illegal(input.to_string())),
+        1 => Ok(candidates[0]),
+        _ => Err(MoveParseError::Ambiguous(
+            input.to_string(),
+            candidates
+                .iter()
+                .map(|chess_move| move_to_san(logic, *chess_move, letters))
+                .collect(),
+        )),
+    }
+}
+
+fn find_castle(
+    board: &BoardLogic,
+    input: &str,
+    king_side: bool,
+) -> Result<BitMove, MoveParseError> {
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|chess_move| {
+            if king_side {
+                chess_move.is_king_castle()
+            } else {
+                chess_move.is_queen_castle()
+            }
+        })
+        .ok_or_else(|| MoveParseError::Illegal(input.to_string()))
+}
+
+fn square_from_chars(file: char, rank: char) -> Option<SQ> {
+    if ('a'..='h').contains(&file) && ('1'..='8').contains(&rank) {
+        Some(SQ((file as u8 - b'a') + 8 * (rank as u8 - b'1')))
+    } else {
+        None
+    }
+}
+
+fn file_char(square: SQ) -> char {
+    (b'a' + square.file_idx_of_sq()) as char
+}
+
+fn rank_char(square: SQ) -> char {
+    (b'1' + square.rank_idx_of_sq()) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CASTLES_FEN: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+    fn board(fen: &str) -> BoardLogic {
+        BoardLogic::from_fen(fen).unwrap()
+    }
+
+    fn uci_move(board: &BoardLogic, uci: &str) -> BitMove {
+        find_uci_move(board, uci).unwrap()
+    }
+
+    fn san(board: &BoardLogic, chess_move: BitMove) -> String {
+        move_to_san(board, chess_move, &PieceLetters::ENGLISH)
+    }
+
+    fn parse(board: &BoardLogic, input: &str) -> Result<BitMove, MoveParseError> {
+        parse_move(board, input, &PieceLetters::ENGLISH)
+    }
+
+    #[test]
+    fn moves_round_trip_through_san_and_uci() {
+        let board = BoardLogic::new();
+        for (uci, notation) in &[("e2e4", "e4"), ("g1f3", "Nf3"), ("b1c3", "Nc3")] {
+            let chess_move = uci_move(&board, uci);
+            assert_eq!(san(&board, chess_move), *notation);
+            assert_eq!(parse(&board, notation), Ok(chess_move));
+            assert_eq!(parse(&board, uci), Ok(chess_move));
+        }
+    }
+
+    #[test]
+    fn pieces_are_told_apart_by_their_file() {
+        let board = board("4k3/8/8/8/8/2N3N1/8/4K3 w - - 0 1");
+        let from_c3 = uci_move(&board, "c3e4");
+        let from_g3 = uci_move(&board, "g3e4");
+        assert_eq!(san(&board, from_c3), "Nce4");
+        assert_eq!(san(&board, from_g3), "Nge4");
+        assert_eq!(parse(&board, "Nce4"), Ok(from_c3));
+        assert_eq!(parse(&board, "Nge4"), Ok(from_g3));
+    }
+
+    #[test]
+    fn pieces_are_told_apart_by_their_rank() {
+        let board = board("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1");
+        let from_a1 = uci_move(&board, "a1a3");
+        let from_a5 = uci_move(&board, "a5a3");
+        assert_eq!(san(&board, from_a1), "R1a3");
+        assert_eq!(san(&board, from_a5), "R5a3");
+        assert_eq!(parse(&board, "R1a3"), Ok(from_a1));
+        assert_eq!(parse(&board, "R5a3"), Ok(from_a5));
+    }
+
+    #[test]
+    fn ambiguous_moves_list_their_candidates() {
+        let board = board("4k3/8/8/8/8/2N3N1/8/4K3 w - - 0 1");
+        match parse(&board, "Ne4") {
+            Err(MoveParseError::Ambiguous(input, mut candidates)) => {
+                candidates.sort();
+                assert_eq!(input, "Ne4");
+                assert_eq!(candidates, vec!["Nce4", "Nge4"]);
+            }
+            other => panic!("Ne4 should be ambiguous, not {:?}", other),
+        }
+    }
+
+    #[test]
+    fn promotions_need_their_piece() {
+        let board = board("8/4P3/8/8/8/8/k7/4K3 w - - 0 1");
+        let queen = uci_move(&board, "e7e8q");
+        let knight = uci_move(&board, "e7e8n");
+        assert_eq!(san(&board, queen), "e8=Q");
+        assert_eq!(parse(&board, "e8=Q"), Ok(queen));
+        assert_eq!(parse(&board, "e8Q"), Ok(queen));
+        assert_eq!(parse(&board, "e8=N"), Ok(knight));
+        assert_eq!(parse(&board, "e7e8n"), Ok(knight));
+        assert_eq!(
+            parse(&board, "e8"),
+            Err(MoveParseError::MissingPromotion(String::from("e8")))
+        );
+    }
+
+    #[test]
+    fn antichess_pawns_promote_to_a_king() {
+        let fen = "8/4P3/8/8/8/8/k7/8 w - - 0 1";
+        let antichess = BoardLogic::from_variant_fen(Variant::Antichess, fen).unwrap();
+        let king = uci_move(&antichess, "e7e8k");
+        assert_eq!(san(&antichess, king), "e8=K");
+        assert_eq!(parse(&antichess, "e8=K"), Ok(king));
+        assert_eq!(parse(&antichess, "e8K"), Ok(king));
+        assert_eq!(
+            parse(&board("8/4P3/8/8/8/8/k7/4K3 w - - 0 1"), "e8=K"),
+            Err(MoveParseError::Illegal(String::from("e8=K")))
+        );
+    }
+
+    #[test]
+    fn castles_are_read_with_letters_or_zeros() {
+        let board = board(CASTLES_FEN);
+        let king_side = uci_move(&board, "e1g1");
+        let queen_side = uci_move(&board, "e1c1");
+        assert!(king_side.is_king_castle() && queen_side.is_queen_castle());
+        assert_eq!(san(&board, king_side), "O-O");
+        assert_eq!(san(&board, queen_side), "O-O-O");
+        assert_eq!(parse(&board, "O-O"), Ok(king_side));
+        assert_eq!(parse(&board, "0-0"), Ok(king_side));
+        assert_eq!(parse(&board, "O-O-O"), Ok(queen_side));
+        assert_eq!(parse(&board, "0-0-0"), Ok(queen_side));
+    }
+
+    #[test]
+    fn chess960_castles_are_read_with_letters_or_from_the_king_to_its_rook() {
+        // The king castling on its side does not move, only its rook does.
+        let board = board("1r4kr/8/8/8/8/8/8/1R4KR w KQkq - 0 1");
+        assert!(board.is_chess960());
+        let king_side = uci_move(&board, "g1h1");
+        let queen_side = uci_move(&board, "g1b1");
+        assert!(king_side.is_king_castle() && queen_side.is_queen_castle());
+        assert_eq!(san(&board, king_side), "O-O");
+        assert_eq!(san(&board, queen_side), "O-O-O");
+        assert_eq!(parse(&board, "O-O"), Ok(king_side));
+        assert_eq!(parse(&board, "0-0"), Ok(king_side));
+        assert_eq!(parse(&board, "O-O-O"), Ok(queen_side));
+        assert_eq!(parse(&board, "g1c1"), Ok(queen_side));
+        assert_eq!(move_to_uci(&board, king_side), "g1h1");
+    }
+
+    #[test]
+    fn checks_mates_and_annotations_are_suffixes() {
+        let board_with_rooks = board(CASTLES_FEN);
+        let capture = uci_move(&board_with_rooks, "a1a8");
+        assert_eq!(san(&board_with_rooks, capture), "Rxa8+");
+        assert_eq!(parse(&board_with_rooks, "Rxa8+"), Ok(capture));
+        assert_eq!(parse(&board_with_rooks, "Rxa8"), Ok(capture));
+
+        let fools_mate = board("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2");
+        let mate = uci_move(&fools_mate, "d8h4");
+        assert_eq!(san(&fools_mate, mate), "Qh4#");
+        assert_eq!(parse(&fools_mate, "Qh4#"), Ok(mate));
+        assert_eq!(parse(&fools_mate, "Qh4#!?"), Ok(mate));
+
+        let start = BoardLogic::new();
+        assert_eq!(parse(&start, "e4!?"), Ok(uci_move(&start, "e2e4")));
+    }
+
+    #[test]
+    fn illegal_and_invalid_moves_are_rejected() {
+        let board = BoardLogic::new();
+        let illegal = |input: &str| Err(MoveParseError::Illegal(String::from(input)));
+        assert_eq!(parse(&board, "e5"), illegal("e5"));
+        assert_eq!(parse(&board, "Ke2"), illegal("Ke2"));
+        assert_eq!(parse(&board, "O-O"), illegal("O-O"));
+        assert_eq!(parse(&board, "e2e5"), illegal("e2e5"));
+        assert_eq!(
+            parse(&board, "Zz9"),
+            Err(MoveParseError::InvalidSyntax(String::from("Zz9")))
+        );
+        assert_eq!(parse(&board, "  "), Err(MoveParseError::Empty));
+    }
+}
@@ -0,0 +1,1498 @@
+use pleco::core::{sq::SQ, PieceType};
+use pleco::{BitMove, Board, Player};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const WDL_EXTENSION: &str = "rtbw";
+const DTZ_EXTENSION: &str = "rtbz";
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+/// Tables exist for up to 7 pieces, kings included.
+const MAX_PIECES: usize = 7;
+
+/// Bytes first read from a table, doubled until its compressed blocks are reached.
+const HEADER_READ_SIZE: u64 = 1 << 16;
+
+/// Flags of the compressed data of a table.
+const STM_FLAG: u8 = 1;
+const MAPPED_FLAG: u8 = 2;
+const WIN_PLIES_FLAG: u8 = 4;
+const LOSS_PLIES_FLAG: u8 = 8;
+const WIDE_FLAG: u8 = 16;
+const SINGLE_VALUE_FLAG: u8 = 128;
+
+/// Letters of the pieces in the names of the tables, in their order.
+const TABLE_PIECES: [(PieceType, char); 6] = [
+    (PieceType::K, 'K'),
+    (PieceType::Q, 'Q'),
+    (PieceType::R, 'R'),
+    (PieceType::B, 'B'),
+    (PieceType::N, 'N'),
+    (PieceType::P, 'P'),
+];
+
+#[derive(Debug)]
+pub enum TablebaseError {
+    Io(PathBuf, io::Error),
+    /// The directory has no Syzygy table.
+    NoTables(PathBuf),
+    /// The position has castling rights, or more pieces than the tables.
+    NotCovered,
+    /// The table of the material, such as `KRPvKR.rtbw`, is not in the directory.
+    MissingTable(String),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablebaseError::Io(path, err) => write!(f, "Could not read {:?} : {}", path, err),
+            TablebaseError::NoTables(dir) => write!(f, "No Syzygy table in {:?}.", dir),
+            TablebaseError::NotCovered => write!(f, "The position is not in the tablebases."),
+            TablebaseError::MissingTable(name) => write!(f, "The table {} is missing.", name),
+            TablebaseError::Invalid(path, reason) => {
+                write!(f, "Invalid Syzygy table {:?} : {}", path, reason)
+            }
+        }
+    }
+}
+
+/// Outcome of a position for the side to move with perfect play. Cursed wins and blessed
+/// losses are wins and losses which the fifty-move rule turns into draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Self {
+        match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            1 => Wdl::CursedWin,
+            2 => Wdl::Win,
+            _ => Wdl::Draw,
+        }
+    }
+}
+
+/// Whether the best move found while probing zeroes the fifty-move counter, in which case
+/// the DTZ tables do not store the position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ProbeState {
+    Stored,
+    ZeroingBestMove,
+}
+
+/// What a table gives for a position : DTZ tables only store one side to move.
+enum TableValue {
+    Value(i32),
+    ChangeSideToMove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TableKind {
+    Wdl,
+    Dtz,
+}
+
+/// The Syzygy tables of a directory : win, draw or loss (WDL) tables, and distance to zeroing
+/// (DTZ) tables giving the number of plies before the next capture or pawn move.
+/// The start of a table is read the first time a position needs it, then only the compressed
+/// blocks holding the positions probed are read from its file.
+pub struct Tablebases {
+    dir: PathBuf,
+    wdl_names: HashSet<String>,
+    dtz_names: HashSet<String>,
+    max_pieces: usize,
+    indexes: IndexTables,
+    tables: Mutex<HashMap<String, Arc<Table>>>,
+}
+
+impl fmt::Debug for Tablebases {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tablebases {{ dir: {:?}, wdl: {}, dtz: {} }}",
+            self.dir,
+            self.wdl_names.len(),
+            self.dtz_names.len()
+        )
+    }
+}
+
+impl Tablebases {
+    /// Lists the tables of the directory.
+    pub fn open(dir: &Path) -> Result<Self, TablebaseError> {
+        let entries =
+            fs::read_dir(dir).map_err(|err| TablebaseError::Io(dir.to_path_buf(), err))?;
+        let mut wdl_names = HashSet::new();
+        let mut dtz_names = HashSet::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let (stem, extension) = match (path.file_stem(), path.extension()) {
+                (Some(stem), Some(extension)) => (stem.to_string_lossy(), extension),
+                _ => continue,
+            };
+            if extension == WDL_EXTENSION {
+                wdl_names.insert(stem.to_string());
+            } else if extension == DTZ_EXTENSION {
+                dtz_names.insert(stem.to_string());
+            }
+        }
+        let max_pieces = wdl_names
+            .iter()
+            .map(|name| name.len() - 1)
+            .filter(|count| *count <= MAX_PIECES)
+            .max()
+            .ok_or_else(|| TablebaseError::NoTables(dir.to_path_buf()))?;
+        Ok(Tablebases {
+            dir: dir.to_path_buf(),
+            wdl_names,
+            dtz_names,
+            max_pieces,
+            indexes: IndexTables::new(),
+            tables: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Whether the tables may have the position : it must have no castling rights,
+    /// and no more pieces than the largest table.
+    pub fn covers(&self, board: &Board) -> bool {
+        board.castling_bits() == 0 && board.count_all_pieces() as usize <= self.max_pieces
+    }
+
+    /// The outcome of the position for the side to move.
+    pub fn probe_wdl(&self, board: &Board) -> Result<Wdl, TablebaseError> {
+        if !self.covers(board) {
+            return Err(TablebaseError::NotCovered);
+        }
+        Ok(Wdl::from_value(self.search(board, false)?.0))
+    }
+
+    /// The number of plies before a capture or a pawn move with the best play of both sides,
+    /// positive when the side to move wins, negative when it loses, and 0 for a draw.
+    /// Cursed wins and blessed losses are counted from 100, as they are draws before.
+    pub fn probe_dtz(&self, board: &Board) -> Result<i32, TablebaseError> {
+        if !self.covers(board) {
+            return Err(TablebaseError::NotCovered);
+        }
+        self.dtz(board)
+    }
+
+    /// The move keeping the best outcome for the side to move : the quickest way to
+    /// the next capture or pawn move when winning, and the longest one when losing.
+    /// None when the side to move has no legal move.
+    pub fn best_move(&self, board: &Board) -> Result<Option<BitMove>, TablebaseError> {
+        if !self.covers(board) {
+            return Err(TablebaseError::NotCovered);
+        }
+        let mut best: Option<(BitMove, (i32, i32))> = None;
+        for chess_move in board.generate_moves().iter() {
+            let mut next_board = board.shallow_clone();
+            next_board.apply_move(*chess_move);
+            let rank = if next_board.checkmate() {
+                (3, 0)
+            } else {
+                let wdl = -self.search(&next_board, false)?.0;
+                let dtz = if is_zeroing(board, *chess_move) {
+                    dtz_before_zeroing(wdl)
+                } else {
+                    let dtz = -self.dtz(&next_board)?;
+                    dtz + dtz.signum()
+                };
+                // The shortest win, and the longest loss.
+                (wdl, -dtz)
+            };
+            if best.map(|(_, best_rank)| rank > best_rank).unwrap_or(true) {
+                best = Some((*chess_move, rank));
+            }
+        }
+        Ok(best.map(|(chess_move, _)| chess_move))
+    }
+
+    /// Captures may win when the table stores a value improving its compression instead,
+    /// so they are searched as well as the table. Pawn moves are searched too for the DTZ
+    /// tables, which do not store the positions where a zeroing move is the best one.
+    fn search(
+        &self,
+        board: &Board,
+        with_pawn_moves: bool,
+    ) -> Result<(i32, ProbeState), TablebaseError> {
+        let moves = board.generate_moves();
+        let mut searched = 0;
+        let mut best_value = -2;
+        for chess_move in moves.iter() {
+            let pawn_move = board.moved_piece(*chess_move).type_of() == PieceType::P;
+            if !(board.is_capture(*chess_move) || with_pawn_moves && pawn_move) {
+                continue;
+            }
+            searched += 1;
+            let mut next_board = board.shallow_clone();
+            next_board.apply_move(*chess_move);
+            let value = -self.search(&next_board, false)?.0;
+            if value > best_value {
+                best_value = value;
+                if value >= 2 {
+                    return Ok((value, ProbeState::ZeroingBestMove));
+                }
+            }
+        }
+        // When every move was searched, the table may store anything for the position,
+        // such as a value ignoring en passant captures.
+        let no_more_moves = searched > 0 && searched == moves.len();
+        let value = if no_more_moves {
+            best_value
+        } else {
+            self.probe_wdl_table(board)?
+        };
+        if best_value >= value {
+            let state = if best_value > 0 || no_more_moves {
+                ProbeState::ZeroingBestMove
+            } else {
+                ProbeState::Stored
+            };
+            return Ok((best_value, state));
+        }
+        Ok((value, ProbeState::Stored))
+    }
+
+    fn dtz(&self, board: &Board) -> Result<i32, TablebaseError> {
+        let (wdl, state) = self.search(board, true)?;
+        if wdl == 0 {
+            return Ok(0);
+        }
+        if state == ProbeState::ZeroingBestMove {
+            return Ok(dtz_before_zeroing(wdl));
+        }
+        match self.probe_dtz_table(board, wdl)? {
+            TableValue::Value(dtz) => {
+                let fifty_moves = if wdl.abs() == 1 { 100 } else { 0 };
+                Ok((dtz + fifty_moves) * wdl.signum())
+            }
+            TableValue::ChangeSideToMove => {
+                // The table stores the other side to move : the best move is searched.
+                let mut min_dtz = 0xFFFF;
+                for chess_move in board.generate_moves().iter() {
+                    let zeroing = is_zeroing(board, *chess_move);
+                    let mut next_board = board.shallow_clone();
+                    next_board.apply_move(*chess_move);
+                    let mut dtz = if zeroing {
+                        -dtz_before_zeroing(self.search(&next_board, false)?.0)
+                    } else {
+                        -self.dtz(&next_board)?
+                    };
+                    if dtz == 1 && next_board.checkmate() {
+                        min_dtz = 1;
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                        min_dtz = dtz;
+                    }
+                }
+                Ok(if min_dtz == 0xFFFF { -1 } else { min_dtz })
+            }
+        }
+    }
+
+    fn probe_wdl_table(&self, board: &Board) -> Result<i32, TablebaseError> {
+        if board.count_all_pieces() == 2 {
+            return Ok(0);
+        }
+        let (table, black_stronger) = self.table_for(board, TableKind::Wdl)?;
+        match table.probe(&self.indexes, board, black_stronger, 0)? {
+            TableValue::Value(value) => Ok(value - 2),
+            TableValue::ChangeSideToMove => Ok(0),
+        }
+    }
+
+    fn probe_dtz_table(&self, board: &Board, wdl: i32) -> Result<TableValue, TablebaseError> {
+        let (table, black_stronger) = self.table_for(board, TableKind::Dtz)?;
+        table.probe(&self.indexes, board, black_stronger, wdl)
+    }
+
+    /// The table of the material of the position, and whether its colours are swapped
+    /// compared with the position : tables are named after the stronger side first.
+    fn table_for(
+        &self,
+        board: &Board,
+        kind: TableKind,
+    ) -> Result<(Arc<Table>, bool), TablebaseError> {
+        let (names, extension) = match kind {
+            TableKind::Wdl => (&self.wdl_names, WDL_EXTENSION),
+            TableKind::Dtz => (&self.dtz_names, DTZ_EXTENSION),
+        };
+        let white_first = material_name(board, Player::White);
+        let (name, black_stronger) = if names.contains(&white_first) {
+            (white_first, false)
+        } else {
+            let black_first = material_name(board, Player::Black);
+            if names.contains(&black_first) {
+                (black_first, true)
+            } else {
+                return Err(TablebaseError::MissingTable(format!(
+                    "{}.{}",
+                    white_first, extension
+                )));
+            }
+        };
+        let file_name = format!("{}.{}", name, extension);
+        let mut tables = self.tables.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(table) = tables.get(&file_name) {
+            return Ok((table.clone(), black_stronger));
+        }
+        let table = Table::open(self.dir.join(&file_name), kind, &name, &self.indexes)?;
+        let table = Arc::new(table);
+        tables.insert(file_name, table.clone());
+        Ok((table, black_stronger))
+    }
+}
+
+/// The name of the table of the material, such as `KRPvKR` with White first.
+fn material_name(board: &Board, first: Player) -> String {
+    let side = |player: Player| -> String {
+        TABLE_PIECES
+            .iter()
+            .map(|(piece_type, letter)| {
+                letter
+                    .to_string()
+                    .repeat(board.count_piece(player, *piece_type) as usize)
+            })
+            .collect()
+    };
+    format!("{}v{}", side(first), side(first.other_player()))
+}
+
+fn is_zeroing(board: &Board, chess_move: BitMove) -> bool {
+    board.is_capture(chess_move) || board.moved_piece(chess_move).type_of() == PieceType::P
+}
+
+/// The DTZ of a position whose best move zeroes the fifty-move counter.
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+fn file_of(square: usize) -> usize {
+    square % 8
+}
+
+fn rank_of(square: usize) -> usize {
+    square / 8
+}
+
+/// Positive above the a1-h8 diagonal, negative below.
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+/// Tables of the encoding of the positions into indexes, the same for every table.
+struct IndexTables {
+    /// Squares a2 to h7 numbered from the edges, the leading pawn having the highest number.
+    map_pawns: [usize; 64],
+    /// Squares below the a1-h8 diagonal, numbered from 0 to 27.
+    map_b1h1h7: [u64; 64],
+    /// Squares of the a1-d1-d4 triangle, those of the diagonal last.
+    map_a1d1d4: [usize; 64],
+    /// The 462 placements of two kings, the first one in the a1-d1-d4 triangle.
+    map_kk: [[u64; 64]; 10],
+    /// Ways to choose k squares among n.
+    binomial: [[u64; 64]; MAX_PIECES],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+impl IndexTables {
+    fn new() -> Self {
+        let mut map_b1h1h7 = [0; 64];
+        let mut code = 0;
+        for (square, value) in map_b1h1h7.iter_mut().enumerate() {
+            if off_diagonal(square) < 0 {
+                *value = code;
+                code += 1;
+            }
+        }
+
+        let mut map_a1d1d4 = [0; 64];
+        let mut diagonal = Vec::new();
+        let mut code = 0;
+        // From a1 to d4.
+        for (square, value) in map_a1d1d4.iter_mut().enumerate().take(28) {
+            if off_diagonal(square) < 0 && file_of(square) <= 3 {
+                *value = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && file_of(square) <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        let mut map_kk = [[0; 64]; 10];
+        let mut both_on_diagonal = Vec::new();
+        let mut code = 0;
+        for (index, codes) in map_kk.iter_mut().enumerate() {
+            // b1 is the only square of the triangle numbered 0.
+            for first in
+                (0..28).filter(|first| map_a1d1d4[*first] == index && (index > 0 || *first == 1))
+            {
+                for (second, value) in codes.iter_mut().enumerate() {
+                    let touching = (file_of(first) as i32 - file_of(second) as i32).abs() <= 1
+                        && (rank_of(first) as i32 - rank_of(second) as i32).abs() <= 1;
+                    if touching || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        *value = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            map_kk[index][second] = code;
+            code += 1;
+        }
+
+        let mut binomial = [[0; 64]; MAX_PIECES];
+        binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                binomial[k][n] = if k > 0 { binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut map_pawns = [0; 64];
+        let mut lead_pawn_idx = [[0; 64]; MAX_PIECES];
+        let mut lead_pawns_size = [[0; 4]; MAX_PIECES];
+        let mut available = 48;
+        for lead_pawns in 1..MAX_PIECES - 1 {
+            for (file, size) in lead_pawns_size[lead_pawns].iter_mut().enumerate() {
+                let mut index = 0;
+                for rank in 1..7 {
+                    let square = 8 * rank + file;
+                    if lead_pawns == 1 {
+                        available -= 1;
+                        map_pawns[square] = available;
+                        available -= 1;
+                        map_pawns[square ^ 7] = available;
+                    }
+                    lead_pawn_idx[lead_pawns][square] = index;
+                    index += binomial[lead_pawns - 1][map_pawns[square]];
+                }
+                *size = index;
+            }
+        }
+
+        IndexTables {
+            map_pawns,
+            map_b1h1h7,
+            map_a1d1d4,
+            map_kk,
+            binomial,
+            lead_pawn_idx,
+            lead_pawns_size,
+        }
+    }
+}
+
+/// The pieces of a table, read from its name.
+#[derive(Debug, Clone, Copy)]
+struct Material {
+    piece_count: usize,
+    has_pawns: bool,
+    /// Whether a piece other than a king is alone of its kind and colour.
+    has_unique_pieces: bool,
+    /// Pawns of the leading colour, then of the other one.
+    pawn_count: [usize; 2],
+    /// Whether both sides have the same pieces.
+    symmetric: bool,
+}
+
+impl Material {
+    fn from_name(name: &str) -> Option<Self> {
+        let mut sides = name.split('v');
+        let (first, second) = (sides.next()?, sides.next()?);
+        let count = |side: &str, letter: char| side.chars().filter(|c| *c == letter).count();
+        let has_unique_pieces = [first, second]
+            .iter()
+            .any(|side| "QRBNP".chars().any(|letter| count(side, letter) == 1));
+        let (first_pawns, second_pawns) = (count(first, 'P'), count(second, 'P'));
+        // The side with fewer pawns leads, for a better compression.
+        let first_leads = second_pawns == 0 || (first_pawns > 0 && second_pawns >= first_pawns);
+        Some(Material {
+            piece_count: first.len() + second.len(),
+            has_pawns: first_pawns + second_pawns > 0,
+            has_unique_pieces,
+            pawn_count: if first_leads {
+                [first_pawns, second_pawns]
+            } else {
+                [second_pawns, first_pawns]
+            },
+            symmetric: first == second,
+        })
+    }
+}
+
+/// How the values of one side to move and one file of the leading pawn are stored :
+/// the order of the pieces and groups in the index, and the Huffman-coded blocks
+/// of values compressed by recursive pairing. Positions are offsets in the table file.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    min_sym_len: u8,
+    block_size: u64,
+    span: u64,
+    num_blocks: u64,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: u64,
+    sparse_index: usize,
+    sparse_index_size: u64,
+    data: usize,
+    /// Lowest symbol of each length, left-aligned on 64 bits.
+    base64: Vec<u64>,
+    /// Number of values, minus one, that each symbol stands for.
+    symlen: Vec<u8>,
+    /// Pieces of the table, as 1 to 6 for the pawn to the king, plus 8 for the weaker side.
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    /// Number of pieces of each group, ended by 0.
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [u16; 4],
+}
+
+/// Why the start of a table could not be parsed.
+enum ParseError {
+    /// The bytes read end before the compressed blocks.
+    Truncated,
+    Invalid(String),
+}
+
+/// Where the data of a table are, read from its start.
+struct Layout {
+    /// Data for each side to move, then each file of the leading pawn.
+    items: Vec<Vec<PairsData>>,
+    /// Start of the maps from stored values to DTZ.
+    map: usize,
+    /// Start of the compressed blocks, and end of the bytes kept in memory.
+    blocks: usize,
+}
+
+struct Table {
+    kind: TableKind,
+    material: Material,
+    /// Data for each side to move, then each file of the leading pawn.
+    items: Vec<Vec<PairsData>>,
+    /// Start of the maps from stored values to DTZ.
+    map: usize,
+    /// The bytes before the compressed blocks : groups, Huffman codes, DTZ maps,
+    /// sparse indexes and lengths of the blocks.
+    header: Vec<u8>,
+    /// The table file, from which the blocks are read.
+    file: Mutex<File>,
+    path: PathBuf,
+}
+
+impl Table {
+    /// Reads the start of the table, longer and longer until the compressed blocks.
+    fn open(
+        path: PathBuf,
+        kind: TableKind,
+        name: &str,
+        indexes: &IndexTables,
+    ) -> Result<Self, TablebaseError> {
+        let io_error = |err| TablebaseError::Io(path.clone(), err);
+        let mut file = File::open(&path).map_err(io_error)?;
+        let file_len = file.metadata().map_err(io_error)?.len();
+        let material = Material::from_name(name)
+            .filter(|material| material.piece_count <= MAX_PIECES)
+            .ok_or_else(|| {
+                TablebaseError::Invalid(path.clone(), format!("{} is not a material", name))
+            })?;
+        let mut header = Vec::new();
+        let mut read_size = HEADER_READ_SIZE;
+        let layout = loop {
+            let missing = read_size.saturating_sub(header.len() as u64);
+            (&mut file)
+                .take(missing)
+                .read_to_end(&mut header)
+                .map_err(io_error)?;
+            match Table::parse(&header, file_len, kind, material, indexes) {
+                Ok(layout) => break layout,
+                Err(ParseError::Truncated) if (header.len() as u64) < file_len => read_size *= 2,
+                Err(ParseError::Truncated) => {
+                    return Err(TablebaseError::Invalid(
+                        path,
+                        String::from("truncated table"),
+                    ))
+                }
+                Err(ParseError::Invalid(reason)) => {
+                    return Err(TablebaseError::Invalid(path, reason))
+                }
+            }
+        };
+        header.truncate(layout.blocks);
+        Ok(Table {
+            kind,
+            material,
+            items: layout.items,
+            map: layout.map,
+            header,
+            file: Mutex::new(file),
+            path,
+        })
+    }
+
+    fn parse(
+        bytes: &[u8],
+        file_len: u64,
+        kind: TableKind,
+        material: Material,
+        indexes: &IndexTables,
+    ) -> Result<Layout, ParseError> {
+        let magic = match kind {
+            TableKind::Wdl => WDL_MAGIC,
+            TableKind::Dtz => DTZ_MAGIC,
+        };
+        if bytes.len() < 6 {
+            return Err(ParseError::Truncated);
+        }
+        if bytes[..4] != magic {
+            return Err(ParseError::Invalid(String::from("wrong magic number")));
+        }
+        let header = bytes[4];
+        if (header & 2 != 0) != material.has_pawns || (header & 1 == 0) != material.symmetric {
+            return Err(ParseError::Invalid(String::from(
+                "the header does not match the name",
+            )));
+        }
+        let layout = read_pairs(bytes, kind, &material, indexes).ok_or(ParseError::Truncated)?;
+        let end = layout
+            .items
+            .iter()
+            .flatten()
+            .map(|pairs| pairs.data as u64 + pairs.num_blocks * pairs.block_size)
+            .max();
+        if end.unwrap_or(0) > file_len {
+            return Err(ParseError::Invalid(String::from("truncated table")));
+        }
+        Ok(layout)
+    }
+
+    fn sides(&self) -> usize {
+        sides(self.kind, &self.material)
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &PairsData {
+        &self.items[side % self.sides()][if self.material.has_pawns { file } else { 0 }]
+    }
+
+    /// The stored value of the position : WDL plus 2 for WDL tables, DTZ for DTZ tables
+    /// given the WDL of the position.
+    fn probe(
+        &self,
+        indexes: &IndexTables,
+        board: &Board,
+        black_stronger: bool,
+        wdl: i32,
+    ) -> Result<TableValue, TablebaseError> {
+        let (pairs, file, index) = match self.locate(indexes, board, black_stronger) {
+            Some(location) => location,
+            None => return Ok(TableValue::ChangeSideToMove),
+        };
+        let value = if pairs.flags & SINGLE_VALUE_FLAG != 0 {
+            i32::from(pairs.min_sym_len)
+        } else {
+            let (block, offset) = locate_value(pairs, &self.header, index);
+            decode_block(pairs, &self.header, &self.read_block(pairs, block)?, offset)
+        };
+        Ok(match self.kind {
+            TableKind::Wdl => TableValue::Value(value),
+            TableKind::Dtz => TableValue::Value(self.map_dtz(file, value, wdl)),
+        })
+    }
+
+    fn read_block(&self, pairs: &PairsData, block: u64) -> Result<Vec<u8>, TablebaseError> {
+        let mut bytes = vec![0; pairs.block_size as usize];
+        let mut file = self.file.lock().unwrap_or_else(|err| err.into_inner());
+        file.seek(SeekFrom::Start(
+            pairs.data as u64 + block * pairs.block_size,
+        ))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|err| TablebaseError::Io(self.path.clone(), err))?;
+        Ok(bytes)
+    }
+
+    /// The data storing the position, the file of its leading pawn and its index in the data,
+    /// the same for the positions equal by symmetry. None when the table only stores the other
+    /// side to move.
+    fn locate(
+        &self,
+        indexes: &IndexTables,
+        board: &Board,
+        black_stronger: bool,
+    ) -> Option<(&PairsData, usize, u64)> {
+        let material = &self.material;
+        let black_to_move = board.turn() == Player::Black;
+        // Symmetric tables only store White to move.
+        let flip = black_stronger || (material.symmetric && black_to_move);
+        let flip_colour = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let side_to_move = (flip ^ black_to_move) as usize;
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0u64;
+        let mut lead_pawns_count = 0;
+        let mut file = 0;
+        if material.has_pawns {
+            let lead_colour = if self.pairs(0, 0).pieces[0] ^ flip_colour < 8 {
+                Player::White
+            } else {
+                Player::Black
+            };
+            for square in 0..64 {
+                let piece = board.piece_at_sq(SQ(square as u8));
+                if piece.type_of() == PieceType::P && piece.player() == Some(lead_colour) {
+                    lead_pawns |= 1 << square;
+                    squares[size] = square ^ flip_squares;
+                    size += 1;
+                }
+            }
+            lead_pawns_count = size;
+            let leading = (0..size)
+                .max_by_key(|index| indexes.map_pawns[squares[*index]])
+                .unwrap_or(0);
+            squares.swap(0, leading);
+            file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+        if self.kind == TableKind::Dtz {
+            let stored_side = (self.pairs(0, file).flags & STM_FLAG) as usize;
+            if stored_side != side_to_move && (material.has_pawns || !material.symmetric) {
+                return None;
+            }
+        }
+        for square in 0..64 {
+            let piece = board.piece_at_sq(SQ(square as u8));
+            if let Some(player) = piece.player() {
+                if lead_pawns & (1 << square) == 0 {
+                    squares[size] = square ^ flip_squares;
+                    let colour = if player == Player::Black { 8 } else { 0 };
+                    pieces[size] = (piece_code(piece.type_of()) | colour) ^ flip_colour;
+                    size += 1;
+                }
+            }
+        }
+        let pairs = self.pairs(side_to_move, file);
+
+        // Pieces in the order of the table.
+        for i in lead_pawns_count..size - 1 {
+            if let Some(j) = (i + 1..size).find(|j| pairs.pieces[i] == pieces[*j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+        // The leading piece on the queen side.
+        if file_of(squares[0]) > 3 {
+            for square in squares[..size].iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let mut index;
+        if material.has_pawns {
+            index = indexes.lead_pawn_idx[lead_pawns_count][squares[0]];
+            squares[1..lead_pawns_count].sort_by_key(|square| indexes.map_pawns[*square]);
+            for (i, square) in squares.iter().enumerate().take(lead_pawns_count).skip(1) {
+                index += indexes.binomial[i][indexes.map_pawns[*square]];
+            }
+        } else {
+            // The leading piece below the fifth rank.
+            if rank_of(squares[0]) > 3 {
+                for square in squares[..size].iter_mut() {
+                    *square ^= 56;
+                }
+            }
+            // The first piece of the leading group off the a1-h8 diagonal below it.
+            for i in 0..pairs.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for square in squares[i..size].iter_mut() {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+            index = if material.has_unique_pieces {
+                unique_pieces_index(indexes, &squares)
+            } else {
+                indexes.map_kk[indexes.map_a1d1d4[squares[0]]][squares[1]]
+            };
+        }
+
+        // The other groups, in ascending order of their squares.
+        index *= pairs.group_idx[0];
+        let mut group_start = pairs.group_len[0];
+        let mut remaining_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut group = 1;
+        while pairs.group_len[group] != 0 {
+            let group_end = group_start + pairs.group_len[group];
+            squares[group_start..group_end].sort_unstable();
+            let mut group_index = 0;
+            for i in group_start..group_end {
+                let square = squares[i];
+                // Squares of the previous groups are not available.
+                let taken = squares[..group_start]
+                    .iter()
+                    .filter(|previous| square > **previous)
+                    .count();
+                let rank_offset = if remaining_pawns { 8 } else { 0 };
+                group_index += indexes.binomial[i - group_start + 1][square - taken - rank_offset];
+            }
+            remaining_pawns = false;
+            index += group_index * pairs.group_idx[group];
+            group_start = group_end;
+            group += 1;
+        }
+
+        Some((pairs, file, index))
+    }
+
+    /// DTZ values are stored by decreasing frequency for each outcome, and in moves
+    /// rather than plies for some of them.
+    fn map_dtz(&self, file: usize, value: i32, wdl: i32) -> i32 {
+        // Maps are stored for wins, losses, cursed wins and blessed losses.
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+        let pairs = self.pairs(0, file);
+        let flags = pairs.flags;
+        let mut value = value;
+        if flags & MAPPED_FLAG != 0 {
+            let index = pairs.map_idx[WDL_MAP[(wdl + 2) as usize]] as usize + value as usize;
+            value = if flags & WIDE_FLAG != 0 {
+                read_u16_le(&self.header, self.map + 2 * index).unwrap_or(0) as i32
+            } else {
+                self.header.get(self.map + index).copied().unwrap_or(0) as i32
+            };
+        }
+        let in_moves = (wdl == 2 && flags & WIN_PLIES_FLAG == 0)
+            || (wdl == -2 && flags & LOSS_PLIES_FLAG == 0)
+            || wdl.abs() == 1;
+        if in_moves {
+            value *= 2;
+        }
+        value + 1
+    }
+}
+
+/// WDL tables store both sides to move, unless both sides have the same pieces.
+fn sides(kind: TableKind, material: &Material) -> usize {
+    if kind == TableKind::Wdl && !material.symmetric {
+        2
+    } else {
+        1
+    }
+}
+
+/// Tables with pawns store each file of the leading pawn apart.
+fn files(material: &Material) -> usize {
+    if material.has_pawns {
+        4
+    } else {
+        1
+    }
+}
+
+/// Reads the groups, the Huffman codes and the DTZ maps of a table, and the offsets
+/// of its sparse indexes, lengths of blocks and blocks. None when the bytes end before.
+fn read_pairs(
+    bytes: &[u8],
+    kind: TableKind,
+    material: &Material,
+    indexes: &IndexTables,
+) -> Option<Layout> {
+    let (sides, files) = (sides(kind, material), files(material));
+    // Pawns on both sides.
+    let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+    let byte = |offset: usize| bytes.get(offset).copied();
+
+    let mut items = vec![vec![PairsData::default(); files]; sides];
+    let mut data = 5;
+    for file in 0..files {
+        let second = if both_pawns { byte(data + 1)? } else { 0xFF };
+        let orders = [
+            [byte(data)? & 0xF, second & 0xF],
+            [byte(data)? >> 4, second >> 4],
+        ];
+        data += 1 + both_pawns as usize;
+        for piece in 0..material.piece_count {
+            let pieces = byte(data)?;
+            for (side, side_items) in items.iter_mut().enumerate() {
+                side_items[file].pieces[piece] = if side == 1 { pieces >> 4 } else { pieces & 0xF };
+            }
+            data += 1;
+        }
+        for (side, side_items) in items.iter_mut().enumerate() {
+            set_groups(material, &mut side_items[file], orders[side], file, indexes);
+        }
+    }
+    data += data & 1;
+
+    for file in 0..files {
+        for side_items in items.iter_mut() {
+            data = set_sizes(&mut side_items[file], bytes, data)?;
+        }
+    }
+    let map = data;
+    if kind == TableKind::Dtz {
+        for item in items[0].iter_mut() {
+            if item.flags & MAPPED_FLAG == 0 {
+                continue;
+            }
+            if item.flags & WIDE_FLAG != 0 {
+                data += data & 1;
+                for map_idx in item.map_idx.iter_mut() {
+                    *map_idx = ((data - map) / 2 + 1) as u16;
+                    data += 2 * read_u16_le(bytes, data)? as usize + 2;
+                }
+            } else {
+                for map_idx in item.map_idx.iter_mut() {
+                    *map_idx = (data - map + 1) as u16;
+                    data += byte(data)? as usize + 1;
+                }
+            }
+        }
+        data += data & 1;
+    }
+    for file in 0..files {
+        for side_items in items.iter_mut() {
+            side_items[file].sparse_index = data;
+            data += side_items[file].sparse_index_size as usize * 6;
+        }
+    }
+    for file in 0..files {
+        for side_items in items.iter_mut() {
+            side_items[file].block_length = data;
+            data += side_items[file].block_length_size as usize * 2;
+        }
+    }
+    // The sparse indexes and the lengths of the blocks are kept in memory.
+    if data > bytes.len() {
+        return None;
+    }
+    let blocks = (data + 0x3F) & !0x3F;
+    for file in 0..files {
+        for side_items in items.iter_mut() {
+            // Blocks start on 64 bytes boundaries.
+            data = (data + 0x3F) & !0x3F;
+            side_items[file].data = data;
+            data += (side_items[file].num_blocks * side_items[file].block_size) as usize;
+        }
+    }
+    Some(Layout { items, map, blocks })
+}
+
+/// Index of three different leading pieces, the first one below the a1-h8 diagonal
+/// or on it with the others below or on it.
+fn unique_pieces_index(indexes: &IndexTables, squares: &[usize]) -> u64 {
+    let (first, second, third) = (squares[0], squares[1], squares[2]);
+    let adjust1 = (second > first) as u64;
+    let adjust2 = (third > first) as u64 + (third > second) as u64;
+    let rank = |square: usize| rank_of(square) as u64;
+    if off_diagonal(first) != 0 {
+        (indexes.map_a1d1d4[first] as u64 * 63 + (second as u64 - adjust1)) * 62 + third as u64
+            - adjust2
+    } else if off_diagonal(second) != 0 {
+        (6 * 63 + rank(first) * 28 + indexes.map_b1h1h7[second]) * 62 + third as u64 - adjust2
+    } else if off_diagonal(third) != 0 {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + rank(first) * 7 * 28
+            + (rank(second) - adjust1) * 28
+            + indexes.map_b1h1h7[third]
+    } else {
+        6 * 63 * 62
+            + 4 * 28 * 62
+            + 4 * 7 * 28
+            + rank(first) * 7 * 6
+            + (rank(second) - adjust1) * 6
+            + (rank(third) - adjust2)
+    }
+}
+
+fn piece_code(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::P => 1,
+        PieceType::N => 2,
+        PieceType::B => 3,
+        PieceType::R => 4,
+        PieceType::Q => 5,
+        _ => 6,
+    }
+}
+
+/// Groups the pieces encoded together, and sets the factor of each group in the index.
+/// The leading group is made of the leading pawns, or of three different pieces, or else
+/// of the two kings. The other groups are pieces of the same kind and colour.
+fn set_groups(
+    material: &Material,
+    pairs: &mut PairsData,
+    order: [u8; 2],
+    file: usize,
+    indexes: &IndexTables,
+) {
+    let mut groups = 0;
+    let mut first_len: i32 = if material.has_pawns {
+        0
+    } else if material.has_unique_pieces {
+        3
+    } else {
+        2
+    };
+    pairs.group_len[0] = 1;
+    for i in 1..material.piece_count {
+        first_len -= 1;
+        if first_len > 0 || pairs.pieces[i] == pairs.pieces[i - 1] {
+            pairs.group_len[groups] += 1;
+        } else {
+            groups += 1;
+            pairs.group_len[groups] = 1;
+        }
+    }
+    groups += 1;
+    pairs.group_len[groups] = 0;
+
+    // The order of the groups in the index is given by the table.
+    let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+    let mut next = if both_pawns { 2 } else { 1 };
+    let mut free_squares =
+        64 - pairs.group_len[0] - if both_pawns { pairs.group_len[1] } else { 0 };
+    let mut index: u64 = 1;
+    let mut k = 0;
+    while next < groups || k == order[0] as usize || k == order[1] as usize {
+        if k == order[0] as usize {
+            pairs.group_idx[0] = index;
+            index *= if material.has_pawns {
+                indexes.lead_pawns_size[pairs.group_len[0]][file]
+            } else if material.has_unique_pieces {
+                31_332
+            } else {
+                462
+            };
+        } else if k == order[1] as usize {
+            pairs.group_idx[1] = index;
+            index *= indexes.binomial[pairs.group_len[1]][48 - pairs.group_len[0]];
+        } else {
+            pairs.group_idx[next] = index;
+            index *= indexes.binomial[pairs.group_len[next]][free_squares];
+            free_squares -= pairs.group_len[next];
+            next += 1;
+        }
+        k += 1;
+    }
+    pairs.group_idx[groups] = index;
+}
+
+/// Reads the sizes and the Huffman code of the compressed values, giving the offset after them.
+fn set_sizes(pairs: &mut PairsData, bytes: &[u8], mut data: usize) -> Option<usize> {
+    pairs.flags = *bytes.get(data)?;
+    data += 1;
+    if pairs.flags & SINGLE_VALUE_FLAG != 0 {
+        // The single value of the table.
+        pairs.min_sym_len = *bytes.get(data)?;
+        return Some(data + 1);
+    }
+    let groups = pairs.group_len.iter().position(|len| *len == 0)?;
+    let table_size = pairs.group_idx[groups];
+    pairs.block_size = 1 << bytes.get(data)?;
+    pairs.span = 1 << bytes.get(data + 1)?;
+    pairs.sparse_index_size = (table_size + pairs.span - 1) / pairs.span;
+    let padding = *bytes.get(data + 2)?;
+    pairs.num_blocks = u64::from(read_u32_le(bytes, data + 3)?);
+    pairs.block_length_size = pairs.num_blocks + u64::from(padding);
+    let max_sym_len = *bytes.get(data + 7)?;
+    pairs.min_sym_len = *bytes.get(data + 8)?;
+    data += 9;
+    if max_sym_len < pairs.min_sym_len || pairs.min_sym_len == 0 {
+        return None;
+    }
+    pairs.lowest_sym = data;
+    let lengths = (max_sym_len - pairs.min_sym_len + 1) as usize;
+    // Longer symbols have lower values, so that each length starts below the shorter ones.
+    let mut base64 = vec![0u64; lengths];
+    for i in (0..lengths - 1).rev() {
+        base64[i] = base64[i + 1]
+            .wrapping_add(u64::from(read_u16_le(bytes, data + 2 * i)?))
+            .wrapping_sub(u64::from(read_u16_le(bytes, data + 2 * i + 2)?))
+            / 2;
+    }
+    for (i, base) in base64.iter_mut().enumerate() {
+        *base = base
+            .checked_shl(64 - i as u32 - u32::from(pairs.min_sym_len))
+            .unwrap_or(0);
+    }
+    pairs.base64 = base64;
+    data += lengths * 2;
+    let symbols = read_u16_le(bytes, data)? as usize;
+    data += 2;
+    pairs.btree = data;
+    if data + 3 * symbols > bytes.len() {
+        return None;
+    }
+    pairs.symlen = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for symbol in 0..symbols {
+        if !visited[symbol] {
+            pairs.symlen[symbol] = set_symlen(pairs, bytes, symbol, &mut visited)?;
+        }
+    }
+    Some(data + 3 * symbols + (symbols & 1))
+}
+
+/// The number of values, minus one, that a symbol stands for, from its two children.
+fn set_symlen(
+    pairs: &mut PairsData,
+    bytes: &[u8],
+    symbol: usize,
+    visited: &mut Vec<bool>,
+) -> Option<u8> {
+    visited[symbol] = true;
+    let (left, right) = symbol_children(pairs, bytes, symbol);
+    if right == 0xFFF {
+        return Some(0);
+    }
+    for child in [left, right].iter() {
+        if *child >= visited.len() {
+            return None;
+        }
+        if !visited[*child] {
+            pairs.symlen[*child] = set_symlen(pairs, bytes, *child, visited)?;
+        }
+    }
+    Some(
+        pairs.symlen[left]
+            .wrapping_add(pairs.symlen[right])
+            .wrapping_add(1),
+    )
+}
+
+/// The two symbols a symbol expands into, on 12 bits each. A leaf stores its value on the left.
+fn symbol_children(pairs: &PairsData, bytes: &[u8], symbol: usize) -> (usize, usize) {
+    let offset = pairs.btree + 3 * symbol;
+    let byte = |offset: usize| bytes.get(offset).copied().unwrap_or(0) as usize;
+    let (low, middle, high) = (byte(offset), byte(offset + 1), byte(offset + 2));
+    ((middle & 0xF) << 8 | low, high << 4 | middle >> 4)
+}
+
+/// The block holding the value at the index, and the offset of the value in the block,
+/// found from the sparse index and the lengths of the blocks.
+fn locate_value(pairs: &PairsData, header: &[u8], index: u64) -> (u64, i64) {
+    let read_u16 = |offset: usize| read_u16_le(header, offset).unwrap_or(0) as i64;
+    let block_length = |block: i64| read_u16(pairs.block_length + 2 * block as usize);
+
+    // The sparse index gives the block and the offset of the middle value of every span.
+    let entry = pairs.sparse_index + 6 * (index / pairs.span) as usize;
+    let mut block = read_u32_le(header, entry).unwrap_or(0) as i64;
+    let mut offset = read_u16(entry + 4);
+    offset += (index % pairs.span) as i64 - (pairs.span / 2) as i64;
+    while offset < 0 && block > 0 {
+        block -= 1;
+        offset += block_length(block) + 1;
+    }
+    while offset > block_length(block) && (block as u64) < pairs.block_length_size {
+        offset -= block_length(block) + 1;
+        block += 1;
+    }
+    (block as u64, offset)
+}
+
+/// The value at the offset of a block : the symbols of the block are decoded until
+/// the one holding the value, which is then expanded.
+fn decode_block(pairs: &PairsData, header: &[u8], block: &[u8], offset: i64) -> i32 {
+    let read_u16 = |offset: usize| read_u16_le(header, offset).unwrap_or(0) as i64;
+    let mut offset = offset;
+    let mut position = 0;
+    let mut buffer = read_u64_be(block, position).unwrap_or(0);
+    position += 8;
+    let mut buffer_size = 64;
+    let min_sym_len = u32::from(pairs.min_sym_len);
+    let mut symbol;
+    loop {
+        let mut len = 0;
+        while len + 1 < pairs.base64.len() && buffer < pairs.base64[len] {
+            len += 1;
+        }
+        symbol = (buffer.wrapping_sub(pairs.base64[len]) >> (64 - len as u32 - min_sym_len)) as u16;
+        symbol = symbol.wrapping_add(read_u16(pairs.lowest_sym + 2 * len) as u16);
+        let symbol_values = i64::from(*pairs.symlen.get(symbol as usize).unwrap_or(&0)) + 1;
+        if offset < symbol_values {
+            break;
+        }
+        offset -= symbol_values;
+        let bits = len as u32 + min_sym_len;
+        buffer <<= bits;
+        buffer_size -= bits;
+        if buffer_size <= 32 {
+            buffer_size += 32;
+            buffer |= u64::from(read_u32_be(block, position).unwrap_or(0)) << (64 - buffer_size);
+            position += 4;
+        }
+    }
+
+    // Symbols expand into their left then right children, down to the value.
+    let mut symbol = symbol as usize;
+    while pairs.symlen.get(symbol).copied().unwrap_or(0) != 0 {
+        let (left, right) = symbol_children(pairs, header, symbol);
+        let left_values = i64::from(pairs.symlen[left]) + 1;
+        if offset < left_values {
+            symbol = left;
+        } else {
+            offset -= left_values;
+            symbol = right;
+        }
+    }
+    symbol_children(pairs, header, symbol).0 as i32
+}
+
+fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([
+        *bytes.get(offset)?,
+        *bytes.get(offset + 1)?,
+    ]))
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    let mut word = [0; 4];
+    word.copy_from_slice(bytes.get(offset..offset + 4)?);
+    Some(u32::from_le_bytes(word))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> Option<u32> {
+    let mut word = [0; 4];
+    word.copy_from_slice(bytes.get(offset..offset + 4)?);
+    Some(u32::from_be_bytes(word))
+}
+
+fn read_u64_be(bytes: &[u8], offset: usize) -> Option<u64> {
+    let mut word = [0; 8];
+    word.copy_from_slice(bytes.get(offset..offset + 8)?);
+    Some(u64::from_be_bytes(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    /// The two children of a symbol, as stored in a table.
+    fn node(left: usize, right: usize) -> [u8; 3] {
+        [
+            (left & 0xFF) as u8,
+            ((left >> 8) | (right & 0xF) << 4) as u8,
+            (right >> 4) as u8,
+        ]
+    }
+
+    /// Values 0, 1 and 2, the pair 0 1 and the pair (0 1) 2, coded on 3 bits but for the last
+    /// one coded "1". Two blocks of 32 bytes hold 15 values, indexed every 8 values.
+    fn handcrafted_pairs() -> (PairsData, Vec<u8>) {
+        let mut header = vec![0, 5, 3, 0, 2, 0, 0, 0, 3, 1, 4, 0, 4, 0, 0, 0, 5, 0];
+        for (left, right) in [(0, 0xFFF), (1, 0xFFF), (2, 0xFFF), (0, 1), (3, 2)].iter() {
+            header.extend_from_slice(&node(*left, *right));
+        }
+        header.push(0);
+        // Block and offset of the values 4 and 12, then the number of values of each block
+        // minus one.
+        header.extend_from_slice(&[0, 0, 0, 0, 4, 0, 1, 0, 0, 0, 2, 0]);
+        header.extend_from_slice(&[9, 0, 4, 0]);
+
+        let mut pairs = PairsData::default();
+        pairs.group_len[0] = 1;
+        pairs.group_idx[1] = 15;
+        assert_eq!(set_sizes(&mut pairs, &header, 0), Some(34));
+        pairs.sparse_index = 34;
+        pairs.block_length = 46;
+        (pairs, header)
+    }
+
+    #[test]
+    fn binomials_count_the_ways_to_choose_squares() {
+        let indexes = IndexTables::new();
+        assert_eq!(indexes.binomial[0][10], 1);
+        assert_eq!(indexes.binomial[1][48], 48);
+        assert_eq!(indexes.binomial[2][62], 1891);
+        assert_eq!(indexes.binomial[3][61], 35_990);
+        assert_eq!(indexes.binomial[6][63], 67_945_521);
+        assert_eq!(indexes.binomial[4][3], 0);
+    }
+
+    #[test]
+    fn squares_are_numbered_below_the_diagonal_and_in_the_triangle() {
+        let indexes = IndexTables::new();
+        let below: Vec<u64> = (0..64)
+            .filter(|square| off_diagonal(*square) < 0)
+            .map(|square| indexes.map_b1h1h7[square])
+            .collect();
+        assert_eq!(below, (0..28).collect::<Vec<u64>>());
+        // b1, c1, d1, c2, d2 and d3, then the diagonal from a1 to d4.
+        let triangle = [1, 2, 3, 10, 11, 19, 0, 9, 18, 27];
+        for (code, square) in triangle.iter().enumerate() {
+            assert_eq!(indexes.map_a1d1d4[*square], code);
+        }
+    }
+
+    #[test]
+    fn two_kings_have_462_placements() {
+        let indexes = IndexTables::new();
+        let codes: HashSet<u64> = indexes.map_kk.iter().flatten().copied().collect();
+        assert_eq!(codes.len(), 462);
+        assert_eq!(codes.iter().max(), Some(&461));
+    }
+
+    #[test]
+    fn pawn_squares_are_numbered_once() {
+        let indexes = IndexTables::new();
+        let mut codes: Vec<usize> = (8..56).map(|square| indexes.map_pawns[square]).collect();
+        codes.sort_unstable();
+        assert_eq!(codes, (0..48).collect::<Vec<usize>>());
+        for file in 0..4 {
+            assert_eq!(indexes.lead_pawns_size[1][file], 6);
+        }
+    }
+
+    #[test]
+    fn groups_multiply_the_size_of_the_index() {
+        let indexes = IndexTables::new();
+        let material = Material::from_name("KNNvK").unwrap();
+        let mut pairs = PairsData::default();
+        pairs.pieces[..4].copy_from_slice(&[6, 14, 2, 2]);
+        set_groups(&material, &mut pairs, [0, 0xF], 0, &indexes);
+        assert_eq!(pairs.group_len[..3], [2, 2, 0]);
+        assert_eq!(pairs.group_idx[..3], [1, 462, 462 * 1891]);
+
+        let material = Material::from_name("KRvK").unwrap();
+        let mut pairs = PairsData::default();
+        pairs.pieces[..3].copy_from_slice(&[6, 4, 14]);
+        set_groups(&material, &mut pairs, [0, 0xF], 0, &indexes);
+        assert_eq!(pairs.group_len[..2], [3, 0]);
+        assert_eq!(pairs.group_idx[1], 31_332);
+    }
+
+    #[test]
+    fn material_is_read_from_the_name() {
+        let material = Material::from_name("KRPvKP").unwrap();
+        assert_eq!(material.piece_count, 5);
+        assert!(material.has_pawns && material.has_unique_pieces && !material.symmetric);
+        assert_eq!(material.pawn_count, [1, 1]);
+        let material = Material::from_name("KPPvK").unwrap();
+        assert!(!material.has_unique_pieces);
+        assert_eq!(material.pawn_count, [2, 0]);
+        assert!(Material::from_name("KvK").unwrap().symmetric);
+    }
+
+    #[test]
+    fn symbols_expand_into_their_values() {
+        let (pairs, _) = handcrafted_pairs();
+        assert_eq!(pairs.symlen, vec![0, 0, 0, 1, 2]);
+        assert_eq!(pairs.block_size, 32);
+        assert_eq!(pairs.span, 8);
+        assert_eq!(pairs.sparse_index_size, 2);
+    }
+
+    #[test]
+    fn blocks_decode_into_the_values_of_their_symbols() {
+        let (pairs, header) = handcrafted_pairs();
+        // The codes 1 001 1 010 011, then 010 1 000.
+        let blocks = [[0x9A, 0x60], [0x50, 0x00]];
+        let values: Vec<i32> = (0..15)
+            .map(|index| {
+                let (block, offset) = locate_value(&pairs, &header, index);
+                let mut bytes = vec![0; 32];
+                bytes[..2].copy_from_slice(&blocks[block as usize]);
+                decode_block(&pairs, &header, &bytes, offset)
+            })
+            .collect();
+        assert_eq!(values, vec![0, 1, 2, 1, 0, 1, 2, 2, 0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn single_value_tables_are_read_from_their_file() {
+        let dir = std::env::temp_dir().join(format!("syzygy-tables-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // A win for White to move and a loss for Black to move, whatever the position.
+        let mut bytes = WDL_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0, 0x66, 0x55, 0xEE, 0, 0x80, 4, 0x80, 0]);
+        bytes.resize(64, 0);
+        fs::write(dir.join("KQvK.rtbw"), &bytes).unwrap();
+
+        let tablebases = Tablebases::open(&dir).unwrap();
+        let white_to_move = board("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1");
+        let black_to_move = board("4k3/8/8/8/8/8/8/Q3K3 b - - 0 1");
+        assert_eq!(tablebases.probe_wdl(&white_to_move).unwrap(), Wdl::Win);
+        assert_eq!(tablebases.probe_wdl(&black_to_move).unwrap(), Wdl::Loss);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Needs the 3-piece tables in the directory named by `SYZYGY_PATH` :
+    /// `SYZYGY_PATH=/path/to/syzygy cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn three_piece_tables_give_known_outcomes() {
+        let dir = std::env::var_os("SYZYGY_PATH").expect("SYZYGY_PATH is not set");
+        let tablebases = Tablebases::open(Path::new(&dir)).unwrap();
+        let expected = [
+            // Ra8 mates.
+            ("6k1/8/6K1/8/8/8/8/R7 w - - 0 1", Wdl::Win, 1),
+            // Kb8 then Rh8 mates.
+            ("k7/8/1K6/8/8/8/8/7R b - - 0 1", Wdl::Loss, -2),
+            // The rook is taken.
+            ("8/8/8/8/8/8/1k6/R6K b - - 0 1", Wdl::Draw, 0),
+            // The pawn promotes.
+            ("8/P7/1K6/8/8/8/8/7k w - - 0 1", Wdl::Win, 1),
+            // The king stands in front of the rook pawn.
+            ("k7/8/8/P7/8/8/8/7K w - - 0 1", Wdl::Draw, 0),
+        ];
+        for (fen, wdl, dtz) in expected.iter() {
+            let position = board(fen);
+            assert_eq!(tablebases.probe_wdl(&position).unwrap(), *wdl, "{}", fen);
+            assert_eq!(tablebases.probe_dtz(&position).unwrap(), *dtz, "{}", fen);
+        }
+        let mate = tablebases
+            .best_move(&board("6k1/8/6K1/8/8/8/8/R7 w - - 0 1"))
+            .unwrap();
+        assert_eq!(
+            mate.map(|chess_move| chess_move.stringify()),
+            Some(String::from("a1a8"))
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use pleco::{Player, SQ};
+
+/// The four central squares of King of the Hill : d4, e4, d5 and e5.
+pub const HILL_SQUARES: [SQ; 4] = [SQ(27), SQ(28), SQ(35), SQ(36)];
+/// Number of checks which wins a game of Three-check.
+pub const WINNING_CHECKS: u8 = 3;
+
+/// Rules of the game played on the board, which change how it is won and, for Antichess,
+/// which moves are legal. The castling of Chess960 is told by the board itself, being
+/// the only rule it changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    Standard,
+    /// The king reaching one of the four central squares wins.
+    KingOfTheHill,
+    /// The third check given wins.
+    ThreeCheck,
+    /// Captures are compulsory, the king is a piece like the others, and the player losing
+    /// all their pieces or having no move left wins.
+    Antichess,
+}
+
+impl Variant {
+    /// Reads the value of the `Variant` tag of a PGN game, written as lichess does or in
+    /// a close spelling. A missing tag, and Chess960 which only changes the castling,
+    /// are the standard rules. None for the variants this application does not play.
+    pub fn from_tag(value: Option<&str>) -> Option<Self> {
+        let value = match value {
+            Some(value) => normalized_tag(value),
+            None => return Some(Variant::Standard),
+        };
+        match value.as_str() {
+            "" | "standard" | "normal" | "chess" => Some(Variant::Standard),
+            _ if is_chess960_tag(&value) => Some(Variant::Standard),
+            "kingofthehill" | "koth" => Some(Variant::KingOfTheHill),
+            "threecheck" | "3check" => Some(Variant::ThreeCheck),
+            "antichess" => Some(Variant::Antichess),
+            _ => None,
+        }
+    }
+
+    /// The value of the `Variant` tag written for this variant, none for the standard rules.
+    pub fn tag_value(self) -> Option<&'static str> {
+        match self {
+            Variant::Standard => None,
+            Variant::KingOfTheHill => Some("King of the Hill"),
+            Variant::ThreeCheck => Some("Three-check"),
+            Variant::Antichess => Some("Antichess"),
+        }
+    }
+
+    /// Whether the UCI engines, the opening books and the tablebases follow these rules.
+    pub fn is_known_to_engines(self) -> bool {
+        self == Variant::Standard
+    }
+}
+
+/// Whether the value of the `Variant` tag tells a Chess960 game, also known as Fischer
+/// Random Chess.
+pub fn is_chess960_tag(value: &str) -> bool {
+    matches!(
+        normalized_tag(value).as_str(),
+        "chess960" | "fischerandom" | "fischerrandom" | "fischerrandomchess" | "960"
+    )
+}
+
+/// The tag value in lowercase, without its spaces and punctuation.
+fn normalized_tag(value: &str) -> String {
+    value
+        .chars()
+        .filter(|character| character.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// How a game of a variant ends, besides the checkmates and stalemates of the standard rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariantEnd {
+    /// The king of the winner reached the hill.
+    KingOnHill,
+    /// The winner gave their third check.
+    ThirdCheck,
+    /// The winner, to move, lost all their pieces or has no move left.
+    NoMoveLeft,
+}
+
+/// The end of a game of a variant, and who won it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariantWin {
+    pub end: VariantEnd,
+    pub winner: Player,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::board_logic::BoardLogic;
+    use super::*;
+
+    fn square(name: &str) -> SQ {
+        let bytes = name.as_bytes();
+        SQ((bytes[1] - b'1') * 8 + (bytes[0] - b'a'))
+    }
+
+    /// Plays the legal move going from a square to another.
+    fn play(board: &mut BoardLogic, origin: &str, destination: &str) {
+        let chess_move = board
+            .dragged_move(square(origin), square(destination))
+            .unwrap();
+        board.play_move(chess_move);
+    }
+
+    #[test]
+    fn tags_are_read_in_close_spellings() {
+        assert_eq!(Variant::from_tag(None), Some(Variant::Standard));
+        assert_eq!(Variant::from_tag(Some("Chess960")), Some(Variant::Standard));
+        assert_eq!(
+            Variant::from_tag(Some("King of the Hill")),
+            Some(Variant::KingOfTheHill)
+        );
+        assert_eq!(
+            Variant::from_tag(Some("3-check")),
+            Some(Variant::ThreeCheck)
+        );
+        assert_eq!(
+            Variant::from_tag(Some("Antichess")),
+            Some(Variant::Antichess)
+        );
+        assert_eq!(Variant::from_tag(Some("Crazyhouse")), None);
+    }
+
+    #[test]
+    fn the_king_on_the_hill_wins() {
+        let fen = "4k3/8/8/8/8/4K3/8/8 w - - 0 1";
+        let mut board = BoardLogic::from_variant_fen(Variant::KingOfTheHill, fen).unwrap();
+        let mut beside_the_hill = board.clone();
+        play(&mut beside_the_hill, "e3", "f3");
+        assert_eq!(beside_the_hill.variant_win(), None);
+        play(&mut board, "e3", "e4");
+        assert_eq!(
+            board.variant_win(),
+            Some(VariantWin {
+                end: VariantEnd::KingOnHill,
+                winner: Player::White,
+            })
+        );
+        assert!(board.is_game_over());
+    }
+
+    #[test]
+    fn the_third_check_wins() {
+        // White gave two checks already.
+        let fen = "4k3/8/8/8/8/8/8/4K2R w - - 0 1 +2+0";
+        let mut board = BoardLogic::from_variant_fen(Variant::ThreeCheck, fen).unwrap();
+        assert_eq!(board.checks, [2, 0]);
+        assert_eq!(board.variant_win(), None);
+        play(&mut board, "h1", "h8");
+        assert_eq!(board.checks, [3, 0]);
+        assert_eq!(
+            board.variant_win(),
+            Some(VariantWin {
+                end: VariantEnd::ThirdCheck,
+                winner: Player::White,
+            })
+        );
+        assert_eq!(board.fen(), "4k2R/8/8/8/8/8/8/4K3 b - - 0+3 1 1");
+    }
+}
@@ -0,0 +1,311 @@
+use pleco::{BitMove, Player};
+
+use super::engine::{analyse_position, AnalysisLine, EngineKind, Score, SearchLimit};
+use super::exercise::Exercise;
+use super::pgn::PgnGame;
+use super::san::{move_to_san, PieceLetters};
+use super::BoardLogic;
+
+/// Tag summing up the verification : "ok", or its findings.
+pub const VERIFICATION_TAG: &str = "Verification";
+/// Tag detailing the findings, move by move.
+pub const VERIFICATION_DETAILS_TAG: &str = "VerificationDetails";
+
+/// Advantage, in centipawns for the solver, from which a move wins.
+pub const WINNING_SCORE: i32 = 300;
+/// A key move scored this much less than the best move is not the best one.
+const NOT_BEST_MARGIN: i32 = 50;
+/// Number of best lines searched for other winning moves.
+const VERIFIED_LINES: usize = 3;
+
+/// Something wrong with a move of the solver in the solution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// The engine finds a clearly better move than the one of the solution at this ply.
+    NotBest { ply: usize, best: BitMove },
+    /// Other moves win as well as the one of the solution at this ply.
+    Alternatives { ply: usize, moves: Vec<BitMove> },
+}
+
+/// Searches every position where the solver is to move, and compares the moves of the solution
+/// with the best lines of the engine.
+pub fn verify_exercise(
+    engine: &EngineKind,
+    exercise: &Exercise,
+    limit: SearchLimit,
+) -> Result<Vec<Finding>, String> {
+    let solver = exercise.solver();
+    let mut board = exercise.start_position();
+    let mut findings = Vec::new();
+    for (ply, key_move) in exercise.solution.iter().enumerate() {
+        if ply % 2 == 0 {
+            let lines = analyse_position(engine, &board.fen(), VERIFIED_LINES, limit)?;
+            findings.extend(check_key_move(ply, *key_move, &lines, solver));
+        }
+        board.play_move(*key_move);
+    }
+    Ok(findings)
+}
+
+fn check_key_move(
+    ply: usize,
+    key_move: BitMove,
+    lines: &[AnalysisLine],
+    solver: Player,
+) -> Vec<Finding> {
+    let lines: Vec<&AnalysisLine> = lines.iter().filter(|line| !line.moves.is_empty()).collect();
+    let best = match lines.first() {
+        Some(best) => best,
+        None => return Vec::new(),
+    };
+    let best_value = best.score.for_player(solver).as_centipawns();
+    let key_score = lines
+        .iter()
+        .find(|line| line.moves[0] == key_move)
+        .map(|line| line.score.for_player(solver));
+    let key_value = key_score.map(|score| score.as_centipawns());
+
+    let mut findings = Vec::new();
+    let far_from_best = key_value
+        .map(|key_value| key_value < best_value - NOT_BEST_MARGIN)
+        .unwrap_or(true);
+    if best.moves[0] != key_move && far_from_best {
+        findings.push(Finding::NotBest {
+            ply,
+            best: best.moves[0],
+        });
+    }
+    // When the key move mates, only other mates are other solutions.
+    let wins = |score: Score| match (key_score, score) {
+        (Some(Score::Mate(_)), Score::Mate(moves)) => moves > 0,
+        (Some(Score::Mate(_)), Score::Centipawns(_)) => false,
+        _ => score.as_centipawns() >= WINNING_SCORE,
+    };
+    let key_wins = key_score.map(wins).unwrap_or(false);
+    let other_winning_moves: Vec<BitMove> = lines
+        .iter()
+        .filter(|line| line.moves[0] != key_move && wins(line.score.for_player(solver)))
+        .map(|line| line.moves[0])
+        .collect();
+    if key_wins && !other_winning_moves.is_empty() {
+        findings.push(Finding::Alternatives {
+            ply,
+            moves: other_winning_moves,
+        });
+    }
+    findings
+}
+
+/// Writes the findings in the tags of the exercise game, replacing those of a previous
+/// verification : `Verification` sums them up, and `VerificationDetails` tells the moves.
+pub fn record_findings(game: &mut PgnGame, solution: &[BitMove], findings: &[Finding]) {
+    if findings.is_empty() {
+        game.set_tag(VERIFICATION_TAG, "ok");
+        game.remove_tag(VERIFICATION_DETAILS_TAG);
+        return;
+    }
+    let board = match game.start_board() {
+        Some(board) => board,
+        None => return,
+    };
+    let mut summary = Vec::new();
+    if findings
+        .iter()
+        .any(|finding| matches!(finding, Finding::NotBest { .. }))
+    {
+        summary.push("not best");
+    }
+    if findings
+        .iter()
+        .any(|finding| matches!(finding, Finding::Alternatives { .. }))
+    {
+        summary.push("multiple solutions");
+    }
+    let details: Vec<String> = findings
+        .iter()
+        .map(|finding| finding_text(&board, solution, finding))
+        .collect();
+    game.set_tag(VERIFICATION_TAG, summary.join(", "));
+    game.set_tag(VERIFICATION_DETAILS_TAG, details.join("; "));
+}
+
+/// A finding in English, such as "12. Qh5 is better than Qg4" or "12... Rxf7 also wins".
+fn finding_text(start_board: &BoardLogic, solution: &[BitMove], finding: &Finding) -> String {
+    let ply = match finding {
+        Finding::NotBest { ply, .. } | Finding::Alternatives { ply, .. } => *ply,
+    };
+    let mut board = start_board.clone();
+    for chess_move in &solution[..ply] {
+        board.play_move(*chess_move);
+    }
+    let fen = board.fen();
+    let move_number = fen.split_whitespace().nth(5).unwrap_or("1");
+    let dots = match board.turn() {
+        Player::White => ".",
+        Player::Black => "...",
+    };
+    let san = |chess_move: &BitMove| move_to_san(&board, *chess_move, &PieceLetters::ENGLISH);
+    match finding {
+        Finding::NotBest { best, .. } => format!(
+            "{}{} {} is better than {}",
+            move_number,
+            dots,
+            san(best),
+            san(&solution[ply])
+        ),
+        Finding::Alternatives { moves, .. } => {
+            let moves: Vec<String> = moves.iter().map(san).collect();
+            format!(
+                "{}{} {} also {}",
+                move_number,
+                dots,
+                moves.join(", "),
+                if moves.len() > 1 { "win" } else { "wins" }
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::pgn::parse_pgn;
+    use super::super::san::find_uci_move;
+
+    fn uci_move(board: &BoardLogic, uci: &str) -> BitMove {
+        find_uci_move(board, uci).unwrap()
+    }
+
+    /// A line of the engine starting with the move, its score being given for White.
+    fn line(board: &BoardLogic, uci: &str, score: Score) -> AnalysisLine {
+        AnalysisLine {
+            depth: 12,
+            score,
+            nodes: 0,
+            moves: vec![uci_move(board, uci)],
+        }
+    }
+
+    /// Findings for `e4` in the start position, White solving, with lines of the moves
+    /// and their centipawns.
+    fn e4_findings(lines: &[(&str, i32)]) -> Vec<Finding> {
+        let board = BoardLogic::new();
+        let lines: Vec<AnalysisLine> = lines
+            .iter()
+            .map(|(uci, centipawns)| line(&board, uci, Score::Centipawns(*centipawns)))
+            .collect();
+        check_key_move(0, uci_move(&board, "e2e4"), &lines, Player::White)
+    }
+
+    fn d4() -> BitMove {
+        uci_move(&BoardLogic::new(), "d2d4")
+    }
+
+    #[test]
+    fn best_key_moves_have_no_finding() {
+        assert!(e4_findings(&[("e2e4", 40), ("d2d4", 35), ("c2c4", 30)]).is_empty());
+        assert!(e4_findings(&[]).is_empty());
+    }
+
+    #[test]
+    fn key_moves_far_from_the_best_are_not_best() {
+        let not_best = vec![Finding::NotBest { ply: 0, best: d4() }];
+        let margin = NOT_BEST_MARGIN;
+        assert!(e4_findings(&[("d2d4", 100), ("e2e4", 100 - margin)]).is_empty());
+        assert_eq!(
+            e4_findings(&[("d2d4", 100), ("e2e4", 100 - margin - 1)]),
+            not_best
+        );
+        // Out of the best lines, the key move is worse than all of them.
+        assert_eq!(e4_findings(&[("d2d4", 100), ("c2c4", 90)]), not_best);
+    }
+
+    #[test]
+    fn other_winning_moves_are_alternatives() {
+        let alternatives = vec![Finding::Alternatives {
+            ply: 0,
+            moves: vec![d4()],
+        }];
+        let winning = WINNING_SCORE;
+        assert_eq!(
+            e4_findings(&[("e2e4", winning + 10), ("d2d4", winning)]),
+            alternatives
+        );
+        assert!(e4_findings(&[("e2e4", winning + 10), ("d2d4", winning - 1)]).is_empty());
+        // A key move which does not win is not told apart from others.
+        assert!(e4_findings(&[("d2d4", winning - 1), ("e2e4", winning - 1)]).is_empty());
+    }
+
+    #[test]
+    fn only_other_mates_are_alternatives_to_a_mate() {
+        let board = BoardLogic::new();
+        let findings = |other: Score| {
+            let lines = vec![
+                line(&board, "e2e4", Score::Mate(2)),
+                line(&board, "d2d4", other),
+            ];
+            check_key_move(0, uci_move(&board, "e2e4"), &lines, Player::White)
+        };
+        assert_eq!(
+            findings(Score::Mate(5)),
+            [Finding::Alternatives {
+                ply: 0,
+                moves: vec![d4()],
+            }]
+        );
+        assert!(findings(Score::Centipawns(5_000)).is_empty());
+        assert!(findings(Score::Mate(-3)).is_empty());
+    }
+
+    #[test]
+    fn scores_are_seen_from_the_solver() {
+        let board =
+            BoardLogic::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+                .unwrap();
+        let key_move = uci_move(&board, "e7e5");
+        let lines = vec![
+            line(&board, "c7c5", Score::Centipawns(-WINNING_SCORE)),
+            line(&board, "e7e5", Score::Centipawns(-WINNING_SCORE)),
+        ];
+        assert_eq!(
+            check_key_move(1, key_move, &lines, Player::Black),
+            [Finding::Alternatives {
+                ply: 1,
+                moves: vec![uci_move(&board, "c7c5")],
+            }]
+        );
+        assert!(check_key_move(1, key_move, &lines, Player::White).is_empty());
+    }
+
+    #[test]
+    fn findings_are_recorded_in_tags() {
+        let mut game = parse_pgn("[Event \"?\"]\n\n1. e4 e5 2. Nf3 *")
+            .unwrap()
+            .remove(0);
+        let exercise = Exercise::from_pgn_game(game.clone()).unwrap();
+        let mut board = BoardLogic::new();
+        board.play_move(exercise.solution[0]);
+        board.play_move(exercise.solution[1]);
+        let findings = vec![
+            Finding::NotBest { ply: 0, best: d4() },
+            Finding::Alternatives {
+                ply: 2,
+                moves: vec![uci_move(&board, "f1c4"), uci_move(&board, "b1c3")],
+            },
+        ];
+        record_findings(&mut game, &exercise.solution, &findings);
+        assert_eq!(
+            game.tag(VERIFICATION_TAG),
+            Some("not best, multiple solutions")
+        );
+        assert_eq!(
+            game.tag(VERIFICATION_DETAILS_TAG),
+            Some("1. d4 is better than e4; 2. Bc4, Nc3 also win")
+        );
+
+        record_findings(&mut game, &exercise.solution, &[]);
+        assert_eq!(game.tag(VERIFICATION_TAG), Some("ok"));
+        assert_eq!(game.tag(VERIFICATION_DETAILS_TAG), None);
+    }
+}
@@ -0,0 +1,200 @@
+use druid::Data;
+use log::error;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::chess::exercise::{exercises_from_games, AcceptancePolicy, Exercise};
+use crate::chess::pgn::{parse_pgn, PgnParseError};
+use crate::settings::config_dir;
+
+const COLLECTIONS_DIRECTORY_NAME: &str = "collections";
+const POLICIES_FILE_NAME: &str = "acceptance_policies.cfg";
+
+/// Exercises collections are the PGN files of the `collections` folder
+/// of the configuration directory, named after their file.
+pub fn collections_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(COLLECTIONS_DIRECTORY_NAME))
+}
+
+#[derive(Debug)]
+pub enum CollectionError {
+    NoConfigurationDirectory,
+    Io(PathBuf, io::Error),
+    Pgn(PathBuf, PgnParseError),
+    Write(PathBuf, io::Error),
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionError::NoConfigurationDirectory => {
+                write!(f, "No configuration directory to read collections from.")
+            }
+            CollectionError::Io(path, err) => write!(f, "Could not read {:?} : {}", path, err),
+            CollectionError::Pgn(path, err) => write!(f, "Invalid PGN file {:?} : {}", path, err),
+            CollectionError::Write(path, err) => write!(f, "Could not write {:?} : {}", path, err),
+        }
+    }
+}
+
+/// Names of the collections, sorted.
+pub fn available_collections() -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(dir) = collections_dir() {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_pgn = path
+                    .extension()
+                    .map(|extension| extension.eq_ignore_ascii_case("pgn"))
+                    .unwrap_or(false);
+                if let (true, Some(name)) =
+                    (is_pgn, path.file_stem().and_then(|stem| stem.to_str()))
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names
+}
+
+/// Reads the exercises of a collection. Games which are not valid exercises are skipped.
+pub fn load_collection(name: &str) -> Result<Vec<Exercise>, CollectionError> {
+    let path = collections_dir()
+        .ok_or(CollectionError::NoConfigurationDirectory)?
+        .join(format!("{}.pgn", name));
+    let content =
+        fs::read_to_string(&path).map_err(|err| CollectionError::Io(path.clone(), err))?;
+    let games = parse_pgn(&content).map_err(|err| CollectionError::Pgn(path.clone(), err))?;
+    let (exercises, errors) = exercises_from_games(games);
+    for (index, err) in errors {
+        error!("Skipping game {} of {:?} : {}", index + 1, path, err);
+    }
+    Ok(exercises)
+}
+
+/// Writes the exercises as a collection, replacing the one with the same name if any.
+pub fn save_collection(name: &str, exercises: &[Exercise]) -> Result<PathBuf, CollectionError> {
+    let dir = collections_dir().ok_or(CollectionError::NoConfigurationDirectory)?;
+    fs::create_dir_all(&dir).map_err(|err| CollectionError::Write(dir.clone(), err))?;
+    let path = dir.join(format!("{}.pgn", name));
+    let content: Vec<String> = exercises
+        .iter()
+        .map(|exercise| exercise.game.to_pgn_string())
+        .collect();
+    fs::write(&path, content.join("\n"))
+        .map_err(|err| CollectionError::Write(path.clone(), err))?;
+    Ok(path)
+}
+
+/// The acceptance policy chosen for each collection, the default one accepting only
+/// the moves of the solutions.
+#[derive(Data, Clone, Debug)]
+pub struct CollectionPolicies {
+    policies: Arc<Vec<(String, AcceptancePolicy)>>,
+}
+
+impl CollectionPolicies {
+    /// Reads the policies file from the configuration directory, a missing file giving
+    /// the default policies.
+    pub fn load() -> Self {
+        let mut policies = CollectionPolicies {
+            policies: Arc::new(Vec::new()),
+        };
+        let path = match config_dir() {
+            Some(dir) => dir.join(POLICIES_FILE_NAME),
+            None => return policies,
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => policies.policies = Arc::new(CollectionPolicies::parse(&content)),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!(
+                        "Could not read acceptance policies file {:?} : {}",
+                        path, err
+                    );
+                }
+            }
+        }
+        policies
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(POLICIES_FILE_NAME), self.serialize())
+    }
+
+    /// Policies are stored as `collection = variations mates tolerance:50` lines,
+    /// listing what is accepted.
+    fn parse(content: &str) -> Vec<(String, AcceptancePolicy)> {
+        let mut policies = Vec::new();
+        for line in content.lines() {
+            let mut parts = line.rsplitn(2, '=');
+            let value = parts.next().unwrap_or("").trim();
+            let collection = match parts.next() {
+                Some(collection) => collection.trim(),
+                None => continue,
+            };
+            let mut policy = AcceptancePolicy::default();
+            for word in value.split_whitespace() {
+                match word {
+                    "variations" => policy.variations = true,
+                    "mates" => policy.same_mate = true,
+                    _ => match word.strip_prefix("tolerance:").map(str::parse) {
+                        Some(Ok(tolerance)) => policy.eval_tolerance = Some(tolerance),
+                        _ => error!("Invalid acceptance {} in policies file.", word),
+                    },
+                }
+            }
+            policies.push((collection.to_string(), policy));
+        }
+        policies
+    }
+
+    fn serialize(&self) -> String {
+        let mut content = String::new();
+        for (collection, policy) in self.policies.iter() {
+            let mut words = Vec::new();
+            if policy.variations {
+                words.push(String::from("variations"));
+            }
+            if policy.same_mate {
+                words.push(String::from("mates"));
+            }
+            if let Some(tolerance) = policy.eval_tolerance {
+                words.push(format!("tolerance:{}", tolerance));
+            }
+            content.push_str(&format!("{} = {}\n", collection, words.join(" ")));
+        }
+        content
+    }
+
+    pub fn policy(&self, collection: &str) -> AcceptancePolicy {
+        self.policies
+            .iter()
+            .find(|(name, _)| name == collection)
+            .map(|(_, policy)| *policy)
+            .unwrap_or_default()
+    }
+
+    pub fn set_policy(&mut self, collection: &str, policy: AcceptancePolicy) {
+        let mut policies: Vec<(String, AcceptancePolicy)> = self
+            .policies
+            .iter()
+            .filter(|(name, _)| name != collection)
+            .cloned()
+            .collect();
+        if policy != AcceptancePolicy::default() {
+            policies.push((collection.to_string(), policy));
+        }
+        self.policies = Arc::new(policies);
+    }
+}
@@ -1,6 +1,34 @@
 mod main_window;
 pub use main_window::launch;
+pub use board_coordinates::{
+    CoordinatesPlacement, CoordinatesStyle, MAX_FONT_SCALE, MIN_FONT_SCALE,
+};
+pub use board_geometry::{DEFAULT_BORDER_WIDTH, MAX_BORDER_WIDTH};
+pub use board_theme::{color_from_hex, color_to_hex, BoardTheme};
 
+mod analysis_panel;
+mod app_data;
+mod app_delegate;
+mod board_coordinates;
+mod board_geometry;
+mod board_theme;
 mod board_zone;
+mod book_panel;
 mod chess_board;
+mod clock_panel;
+mod commands;
+mod computer_panel;
+mod endgame_panel;
+mod evaluation_bar;
+mod localization;
+mod move_entry;
+mod new_game_panel;
+mod piece_set;
+mod position_panel;
+mod repertoire_panel;
+mod review_panel;
+mod rush_panel;
+mod settings_window;
 mod svg_image_button;
+mod tablebase_panel;
+mod visibility_panel;
@@ -0,0 +1,156 @@
+use druid::widget::{
+    Checkbox, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, RadioGroup,
+};
+use druid::{
+    Data, Env, ExtEventSink, Lens, LifeCycle, LifeCycleCtx, LocalizedString, Target, UpdateCtx,
+    Widget, WidgetExt,
+};
+
+use std::sync::Arc;
+
+use super::app_data::AppData;
+use super::commands::ANALYSIS_EVENT;
+use super::localization::{analysis_text, localize};
+use crate::chess::engine::{Analysis, AnalysisUpdate};
+
+/// Numbers of lines the engine can be asked for.
+const LINES_COUNTS: [usize; 3] = [1, 3, 5];
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct AnalysisData {
+    pub enabled: bool,
+    /// Number of best lines shown.
+    pub multi_pv: usize,
+    /// The best lines found so far, possibly for a previous position.
+    pub latest: Option<Arc<AnalysisUpdate>>,
+    /// Why the engine does not answer.
+    pub error: Option<String>,
+}
+
+impl AnalysisData {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            multi_pv: LINES_COUNTS[0],
+            latest: None,
+            error: None,
+        }
+    }
+
+    /// The lines found for the given position, if the analysis has reached it.
+    pub fn update_for(&self, fen: &str) -> Option<&AnalysisUpdate> {
+        self.latest.as_deref().filter(|update| update.fen == fen)
+    }
+}
+
+/// Switch, number of lines, and the depth, score and best lines of the engine.
+pub fn analysis_panel_builder() -> impl Widget<AppData> {
+    let lines_counts = RadioGroup::new(LINES_COUNTS.iter().map(|count| {
+        let count = *count;
+        (
+            LocalizedString::new("analysis-lines")
+                .with_arg("count", move |_data: &usize, _env: &Env| count.into()),
+            count,
+        )
+    }))
+    .lens(AnalysisData::multi_pv)
+    .lens(AppData::analysis);
+    let lines = Label::dynamic(|data: &AppData, env| {
+        if !data.board.board.variant.is_known_to_engines() {
+            return localize(env, "variant-unknown-to-engines");
+        }
+        if !analysis_wanted(data) {
+            return String::new();
+        }
+        let analysis = &data.analysis;
+        let board = &data.board.board;
+        analysis_text(
+            env,
+            analysis.update_for(&board.fen()),
+            analysis.error.as_deref(),
+            board,
+            analysis.multi_pv,
+        )
+    })
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(
+            Checkbox::new(LocalizedString::new("analysis-enabled"))
+                .lens(AnalysisData::enabled)
+                .lens(AppData::analysis),
+        )
+        .with_child(lines_counts)
+        .with_spacer(4.0)
+        .with_child(lines)
+        .controller(AnalysisController { analysis: None })
+}
+
+/// Whether the engine should analyse the board : not during a rush, which it would spoil,
+/// nor in a variant it does not play.
+pub fn analysis_wanted(data: &AppData) -> bool {
+    data.analysis.enabled
+        && !data.rush.is_running()
+        && data.board.board.variant.is_known_to_engines()
+}
+
+/// Runs the engine on the position of the board, restarting it whenever the position
+/// or the analysis settings change. Results reach the delegate as commands.
+struct AnalysisController {
+    analysis: Option<Analysis>,
+}
+
+impl AnalysisController {
+    fn restart(&mut self, data: &AppData, sink: ExtEventSink) {
+        // Dropping the previous analysis stops it.
+        self.analysis = None;
+        if !analysis_wanted(data) {
+            return;
+        }
+        let fen = data.board.board.fen();
+        self.analysis = Some(Analysis::start(
+            &data.settings.engine(),
+            &fen,
+            data.analysis.multi_pv,
+            move |event| {
+                // The application may be closing, in which case the event is lost.
+                let _ = sink.submit_command(ANALYSIS_EVENT, event, Target::Auto);
+            },
+        ));
+    }
+}
+
+impl<W: Widget<AppData>> Controller<AppData, W> for AnalysisController {
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.restart(data, ctx.get_external_handle());
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        let changed = analysis_wanted(old_data) != analysis_wanted(data)
+            || old_data.analysis.multi_pv != data.analysis.multi_pv
+            || old_data.settings.engine_path != data.settings.engine_path
+            || !old_data.board.board.same(&data.board.board);
+        if changed {
+            self.restart(data, ctx.get_external_handle());
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,75 @@
+use druid::{Data, Lens};
+
+use super::analysis_panel::AnalysisData;
+use super::book_panel::BookData;
+use super::chess_board::ChessBoardData;
+use super::computer_panel::ComputerSide;
+use super::endgame_panel::EndgameData;
+use super::move_entry::MoveEntryData;
+use super::new_game_panel::NewGameData;
+use super::repertoire_panel::RepertoireData;
+use super::review_panel::ReviewData;
+use super::rush_panel::RushData;
+use super::tablebase_panel::TablebaseData;
+use crate::chess::clock::ChessClock;
+use crate::chess::pgn::{game_result, PgnGame};
+use crate::settings::Settings;
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct AppData {
+    pub board: ChessBoardData,
+    pub settings: Settings,
+    pub move_entry: MoveEntryData,
+    /// Last move played or position asked for, in words.
+    pub announcement: String,
+    /// Clocks of the game, none when it is not timed.
+    pub clock: Option<ChessClock>,
+    /// Index of the time control chosen for the next clocks.
+    pub clock_preset: usize,
+    pub new_game: NewGameData,
+    pub rush: RushData,
+    pub repertoire: RepertoireData,
+    pub endgame: EndgameData,
+    pub analysis: AnalysisData,
+    pub review: ReviewData,
+    /// Side played by the computer, which moves as soon as it is its turn.
+    pub computer: ComputerSide,
+    pub book: BookData,
+    pub tablebases: TablebaseData,
+}
+
+impl AppData {
+    pub fn new() -> Self {
+        let settings = Settings::load();
+        let book = BookData::load(settings.book().as_deref());
+        let tablebases = TablebaseData::load(settings.tablebases().as_deref());
+        Self {
+            board: ChessBoardData::new(),
+            settings,
+            move_entry: MoveEntryData::new(),
+            announcement: String::new(),
+            clock: None,
+            clock_preset: 0,
+            new_game: NewGameData::new(),
+            rush: RushData::new(),
+            repertoire: RepertoireData::new(),
+            endgame: EndgameData::new(),
+            analysis: AnalysisData::new(),
+            review: ReviewData::new(),
+            computer: ComputerSide::Nobody,
+            book,
+            tablebases,
+        }
+    }
+
+    /// The game played on the board, with its result and the times of its clocks.
+    pub fn game_pgn(&self) -> PgnGame {
+        let logic = &self.board.board;
+        let flagged = self.clock.as_ref().and_then(|clock| clock.flagged());
+        let mut game = PgnGame::from_board_logic(logic, game_result(logic, flagged));
+        if let Some(clock) = &self.clock {
+            game.add_clock_times(clock);
+        }
+        game
+    }
+}
@@ -0,0 +1,220 @@
+use druid::{commands, AppDelegate, Command, DelegateCtx, Env, Handled, Target};
+
+use log::error;
+
+use pleco::BitMove;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::app_data::AppData;
+use super::book_panel::BookData;
+use super::commands::{
+    ANALYSIS_EVENT, BLUNDERS_FOUND, BOOK_LOADED, COMPUTER_MOVE, DESCRIBE_POSITION, PEEK, PLAY_MOVE,
+    REVIEW_FINISHED, REVIEW_PROGRESS, RUSH_MOVE_JUDGED, TABLEBASES_LOADED, VERIFICATION_FINISHED,
+};
+use super::computer_panel::computer_to_move;
+use super::endgame_panel::judge_endgame_move;
+use super::localization::{
+    blunders_found_text, localize, localize_with_args, move_announcement_text, move_refused_text,
+    position_description_text, review_done_text, verification_done_text, Argument,
+};
+use super::repertoire_panel::play_repertoire_move;
+use super::rush_panel::{conclude_rush_move, play_rush_move};
+use super::tablebase_panel::TablebaseData;
+use crate::chess::description::{announce_move, describe_position};
+use crate::chess::engine::AnalysisEvent;
+
+pub struct MainDelegate;
+
+impl AppDelegate<AppData> for MainDelegate {
+    fn command(
+        &mut self,
+        _ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut AppData,
+        env: &Env,
+    ) -> Handled {
+        if let Some(chess_move) = cmd.get(PLAY_MOVE) {
+            if data.rush.is_running() {
+                play_rush_move(data, *chess_move, env);
+                return Handled::Yes;
+            }
+            if data.repertoire.is_drilling() {
+                play_repertoire_move(data, *chess_move, env);
+                return Handled::Yes;
+            }
+            if let Some(drill) = &data.endgame.drill {
+                if drill.is_attempt_over() {
+                    data.announcement = localize(env, "endgame-attempt-over");
+                    return Handled::Yes;
+                }
+                if !drill.solver_to_move(&data.board.board) {
+                    data.announcement = localize(env, "endgame-wait");
+                    return Handled::Yes;
+                }
+            }
+            play_move(data, *chess_move, env);
+            return Handled::Yes;
+        }
+        if let Some((fen, outcome)) = cmd.get(COMPUTER_MOVE) {
+            // Moves searched for a position left meanwhile are dropped.
+            if *fen == data.board.board.fen() && computer_to_move(data) {
+                match outcome {
+                    Ok(chess_move) => play_move(data, *chess_move, env),
+                    Err(message) => {
+                        error!("{}", message);
+                        data.announcement = localize_with_args(
+                            env,
+                            "computer-failed",
+                            vec![("error", Argument::Text(message.clone()))],
+                        );
+                    }
+                }
+            }
+            return Handled::Yes;
+        }
+        if let Some(judged) = cmd.get(RUSH_MOVE_JUDGED) {
+            conclude_rush_move(data, judged, env);
+            return Handled::Yes;
+        }
+        if let Some(outcome) = cmd.get(BOOK_LOADED) {
+            let (loaded, error) = match outcome {
+                Ok(loaded) => (loaded.clone(), None),
+                Err(message) => (None, Some(message.clone())),
+            };
+            data.book = BookData { loaded, error };
+            return Handled::Yes;
+        }
+        if let Some(outcome) = cmd.get(TABLEBASES_LOADED) {
+            let (loaded, error) = match outcome {
+                Ok(loaded) => (loaded.clone(), None),
+                Err(message) => (None, Some(message.clone())),
+            };
+            data.tablebases = TablebaseData { loaded, error };
+            return Handled::Yes;
+        }
+        if let Some(peeking) = cmd.get(PEEK) {
+            data.board.peeking = *peeking;
+            if *peeking {
+                record_peek(data);
+            }
+            return Handled::Yes;
+        }
+        if cmd.is(DESCRIBE_POSITION) {
+            // Describing hidden pieces reveals them as much as peeking.
+            if !data.board.shows_all_pieces() {
+                record_peek(data);
+            }
+            let description = describe_position(&data.board.board);
+            data.announcement = position_description_text(env, &description);
+            return Handled::Yes;
+        }
+        if let Some(event) = cmd.get(ANALYSIS_EVENT) {
+            match event {
+                AnalysisEvent::Update(update) => {
+                    // Lines of a position left meanwhile are dropped.
+                    if update.fen == data.board.board.fen() {
+                        data.analysis.latest = Some(Arc::new(update.clone()));
+                        data.analysis.error = None;
+                    }
+                }
+                AnalysisEvent::Error(message) => {
+                    error!("{}", message);
+                    data.analysis.error = Some(message.clone());
+                }
+            }
+            return Handled::Yes;
+        }
+        if let Some(progress) = cmd.get(REVIEW_PROGRESS) {
+            // Progress sent just before the end of the review arrives before it.
+            if data.review.progress.is_some() {
+                data.review.progress = Some(*progress);
+            }
+            return Handled::Yes;
+        }
+        if let Some(outcome) = cmd.get(REVIEW_FINISHED) {
+            data.review.progress = None;
+            data.announcement = match outcome {
+                Ok(summary) => review_done_text(env, summary),
+                Err(message) => review_failed_text(env, message),
+            };
+            return Handled::Yes;
+        }
+        if let Some(outcome) = cmd.get(BLUNDERS_FOUND) {
+            data.review.progress = None;
+            data.announcement = match outcome {
+                Ok(summary) => blunders_found_text(env, summary),
+                Err(message) => review_failed_text(env, message),
+            };
+            return Handled::Yes;
+        }
+        if let Some(outcome) = cmd.get(VERIFICATION_FINISHED) {
+            data.review.progress = None;
+            data.announcement = match outcome {
+                Ok(summary) => verification_done_text(env, summary),
+                Err(message) => review_failed_text(env, message),
+            };
+            return Handled::Yes;
+        }
+        if let Some(file_info) = cmd.get(commands::SAVE_FILE_AS) {
+            let game = data.game_pgn();
+            let path = file_info.path().display().to_string();
+            data.announcement = match std::fs::write(file_info.path(), game.to_pgn_string()) {
+                Ok(()) => {
+                    localize_with_args(env, "pgn-export-done", vec![("path", Argument::Text(path))])
+                }
+                Err(err) => {
+                    error!("Could not write the game to {} : {}", path, err);
+                    localize_with_args(
+                        env,
+                        "pgn-export-failed",
+                        vec![
+                            ("path", Argument::Text(path)),
+                            ("error", Argument::Text(err.to_string())),
+                        ],
+                    )
+                }
+            };
+            return Handled::Yes;
+        }
+        Handled::No
+    }
+}
+
+/// Counts a look at the hidden pieces in the exercise being solved.
+fn record_peek(data: &mut AppData) {
+    if let Some(session) = &mut data.rush.session {
+        session.record_peek();
+    }
+}
+
+/// Plays a move on the main board, pressing the clock if the game is timed,
+/// and judges the attempt of the endgame drill going on.
+fn play_move(data: &mut AppData, chess_move: BitMove, env: &Env) {
+    if let Some(clock) = &mut data.clock {
+        if !clock.press(Instant::now()) {
+            data.announcement = move_refused_text(env, clock.turn());
+            return;
+        }
+    }
+    let announcement = announce_move(&data.board.board, chess_move);
+    data.announcement = move_announcement_text(env, &announcement);
+    data.board.board.play_move(chess_move);
+    if data.board.board.is_game_over() {
+        if let Some(clock) = &mut data.clock {
+            clock.pause(Instant::now());
+        }
+    }
+    judge_endgame_move(data, env);
+}
+
+fn review_failed_text(env: &Env, message: &str) -> String {
+    error!("{}", message);
+    localize_with_args(
+        env,
+        "review-failed",
+        vec![("error", Argument::Text(message.to_string()))],
+    )
+}
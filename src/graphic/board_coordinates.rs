@@ -0,0 +1,80 @@
+use druid::{ArcStr, Data, Env, Key, Lens};
+
+pub const COORDINATES_PLACEMENT: Key<ArcStr> =
+    Key::new("chess-exercises-organizer.board.coordinates-placement");
+pub const COORDINATES_UPPERCASE: Key<bool> =
+    Key::new("chess-exercises-organizer.board.coordinates-uppercase");
+pub const COORDINATES_FONT_SCALE: Key<f64> =
+    Key::new("chess-exercises-organizer.board.coordinates-font-scale");
+
+pub const MIN_FONT_SCALE: f64 = 0.5;
+pub const MAX_FONT_SCALE: f64 = 2.0;
+
+/// Where the files letters and ranks numbers are written.
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub enum CoordinatesPlacement {
+    /// In the border, on the four sides of the board.
+    AllSides,
+    /// In the border, below and on the left of the board.
+    TwoSides,
+    /// In the corners of the bottom row and left column squares, as on lichess.
+    InsideSquares,
+    Hidden,
+}
+
+impl CoordinatesPlacement {
+    pub const ALL: [CoordinatesPlacement; 4] = [
+        CoordinatesPlacement::AllSides,
+        CoordinatesPlacement::TwoSides,
+        CoordinatesPlacement::InsideSquares,
+        CoordinatesPlacement::Hidden,
+    ];
+
+    /// Name used in the settings file and in the environment.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoordinatesPlacement::AllSides => "all_sides",
+            CoordinatesPlacement::TwoSides => "two_sides",
+            CoordinatesPlacement::InsideSquares => "inside_squares",
+            CoordinatesPlacement::Hidden => "hidden",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        CoordinatesPlacement::ALL
+            .iter()
+            .find(|placement| placement.name() == name)
+            .cloned()
+    }
+}
+
+/// How the coordinates are drawn around or on the board, their colour being part of the theme.
+#[derive(Lens, Data, Clone, Debug, PartialEq)]
+pub struct CoordinatesStyle {
+    pub placement: CoordinatesPlacement,
+    pub uppercase: bool,
+    /// Factor applied to the default font size, which depends on the board size.
+    pub font_scale: f64,
+}
+
+impl CoordinatesStyle {
+    pub fn new() -> Self {
+        Self {
+            placement: CoordinatesPlacement::AllSides,
+            uppercase: true,
+            font_scale: 1.0,
+        }
+    }
+
+    pub fn apply_to_env(&self, env: &mut Env) {
+        env.set(COORDINATES_PLACEMENT, ArcStr::from(self.placement.name()));
+        env.set(COORDINATES_UPPERCASE, self.uppercase);
+        env.set(COORDINATES_FONT_SCALE, self.font_scale);
+    }
+}
+
+/// The placement given by the environment, coordinates being on all sides by default.
+pub fn placement_from_env(env: &Env) -> CoordinatesPlacement {
+    CoordinatesPlacement::from_name(&env.get(COORDINATES_PLACEMENT))
+        .unwrap_or(CoordinatesPlacement::AllSides)
+}
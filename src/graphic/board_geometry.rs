@@ -0,0 +1,379 @@
+use druid::{Key, Point, Rect, Size};
+
+/// Width of the border around the cells, as a ratio of the cells size.
+pub const BORDER_WIDTH: Key<f64> = Key::new("chess-exercises-organizer.board.border-width");
+
+pub const DEFAULT_BORDER_WIDTH: f64 = 0.5;
+pub const MAX_BORDER_WIDTH: f64 = 1.0;
+
+/// A cell of the board, as the chess logic sees it : file 0 is the a file, rank 0 the first rank.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellCoordinates {
+    pub file: u8,
+    pub rank: u8,
+}
+
+/// Where the board, its border and its cells are drawn inside the widget.
+///
+/// Columns and rows are counted on screen, from the left and from the top, whereas files
+/// and ranks are the ones of the chess logic : the mapping between them depends on the
+/// board orientation. The board is the largest square fitting in the container, centered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardGeometry {
+    origin: Point,
+    cells_size: f64,
+    border_width: f64,
+    reversed: bool,
+}
+
+impl BoardGeometry {
+    /// `border_width` is given as a ratio of the cells size.
+    pub fn new(container: Size, border_width: f64, reversed: bool) -> Self {
+        let border_width = border_width.max(0.0);
+        let side = container.width.min(container.height).max(0.0);
+        let cells_size = side / (8.0 + 2.0 * border_width);
+        BoardGeometry {
+            origin: Point::new(
+                (container.width - side) / 2.0,
+                (container.height - side) / 2.0,
+            ),
+            cells_size,
+            border_width: cells_size * border_width,
+            reversed,
+        }
+    }
+
+    pub fn cells_size(&self) -> f64 {
+        self.cells_size
+    }
+
+    /// Width of the border, in pixels.
+    pub fn border_width(&self) -> f64 {
+        self.border_width
+    }
+
+    /// The whole board, border included.
+    pub fn board_rect(&self) -> Rect {
+        let side = 8.0 * self.cells_size + 2.0 * self.border_width;
+        Rect::from_origin_size(self.origin, (side, side))
+    }
+
+    /// The 64 cells, without the border.
+    pub fn cells_rect(&self) -> Rect {
+        self.board_rect().inset(-self.border_width)
+    }
+
+    /// The column and row where the cell is drawn.
+    pub fn column_row(&self, cell: CellCoordinates) -> (u8, u8) {
+        if self.reversed {
+            (7 - cell.file, cell.rank)
+        } else {
+            (cell.file, 7 - cell.rank)
+        }
+    }
+
+    /// The cell drawn at the given column and row.
+    pub fn cell(&self, col: u8, row: u8) -> CellCoordinates {
+        if self.reversed {
+            CellCoordinates {
+                file: 7 - col,
+                rank: row,
+            }
+        } else {
+            CellCoordinates {
+                file: col,
+                rank: 7 - row,
+            }
+        }
+    }
+
+    /// Top left corner of the cell at the given column and row.
+    pub fn column_row_origin(&self, col: u8, row: u8) -> Point {
+        let cells_origin = self.cells_rect().origin();
+        Point::new(
+            cells_origin.x + self.cells_size * (col as f64),
+            cells_origin.y + self.cells_size * (row as f64),
+        )
+    }
+
+    pub fn column_row_rect(&self, col: u8, row: u8) -> Rect {
+        Rect::from_origin_size(
+            self.column_row_origin(col, row),
+            (self.cells_size, self.cells_size),
+        )
+    }
+
+    /// Top left corner of the cell.
+    pub fn cell_origin(&self, cell: CellCoordinates) -> Point {
+        let (col, row) = self.column_row(cell);
+        self.column_row_origin(col, row)
+    }
+
+    pub fn cell_rect(&self, cell: CellCoordinates) -> Rect {
+        let (col, row) = self.column_row(cell);
+        self.column_row_rect(col, row)
+    }
+
+    /// The column and row under the point, if it is on a cell rather than on the border
+    /// or outside of the board.
+    pub fn column_row_at(&self, point: Point) -> Option<(u8, u8)> {
+        let cells_rect = self.cells_rect();
+        if self.cells_size <= 0.0 {
+            return None;
+        }
+        let col = ((point.x - cells_rect.x0) / self.cells_size).floor();
+        let row = ((point.y - cells_rect.y0) / self.cells_size).floor();
+        let in_bounds = (0.0..8.0).contains(&col) && (0.0..8.0).contains(&row);
+        if in_bounds {
+            Some((col as u8, row as u8))
+        } else {
+            None
+        }
+    }
+
+    /// The cell under the point, if any.
+    pub fn cell_at(&self, point: Point) -> Option<CellCoordinates> {
+        self.column_row_at(point)
+            .map(|(col, row)| self.cell(col, row))
+    }
+
+    /// Middle of the given column, horizontally.
+    pub fn column_center_x(&self, col: u8) -> f64 {
+        self.column_row_rect(col, 0).center().x
+    }
+
+    /// Middle of the given row, vertically.
+    pub fn row_center_y(&self, row: u8) -> f64 {
+        self.column_row_rect(0, row).center().y
+    }
+
+    /// Middle of the top border, vertically.
+    pub fn top_border_center_y(&self) -> f64 {
+        self.board_rect().y0 + self.border_width / 2.0
+    }
+
+    /// Middle of the bottom border, vertically.
+    pub fn bottom_border_center_y(&self) -> f64 {
+        self.board_rect().y1 - self.border_width / 2.0
+    }
+
+    /// Middle of the left border, horizontally.
+    pub fn left_border_center_x(&self) -> f64 {
+        self.board_rect().x0 + self.border_width / 2.0
+    }
+
+    /// Middle of the right border, horizontally.
+    pub fn right_border_center_x(&self) -> f64 {
+        self.board_rect().x1 - self.border_width / 2.0
+    }
+
+    /// Middle of the bottom right corner of the border.
+    pub fn bottom_right_corner_center(&self) -> Point {
+        Point::new(self.right_border_center_x(), self.bottom_border_center_y())
+    }
+
+    /// Middle of the bottom left corner of the border.
+    pub fn bottom_left_corner_center(&self) -> Point {
+        Point::new(self.left_border_center_x(), self.bottom_border_center_y())
+    }
+
+    /// Middle of the top left corner of the border.
+    pub fn top_left_corner_center(&self) -> Point {
+        Point::new(self.left_border_center_x(), self.top_border_center_y())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    fn assert_point_close(actual: Point, expected: Point) {
+        assert_close(actual.x, expected.x);
+        assert_close(actual.y, expected.y);
+    }
+
+    #[test]
+    fn cells_share_the_square_with_the_border() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, false);
+        assert_close(geometry.cells_size(), 100.0);
+        assert_close(geometry.border_width(), 50.0);
+        assert_eq!(geometry.board_rect(), Rect::new(0.0, 0.0, 900.0, 900.0));
+        assert_eq!(geometry.cells_rect(), Rect::new(50.0, 50.0, 850.0, 850.0));
+    }
+
+    #[test]
+    fn board_without_border_fills_the_square() {
+        let geometry = BoardGeometry::new(Size::new(400.0, 400.0), 0.0, false);
+        assert_close(geometry.cells_size(), 50.0);
+        assert_close(geometry.border_width(), 0.0);
+        assert_eq!(geometry.cells_rect(), geometry.board_rect());
+    }
+
+    #[test]
+    fn negative_border_is_no_border() {
+        let geometry = BoardGeometry::new(Size::new(400.0, 400.0), -1.0, false);
+        assert_close(geometry.border_width(), 0.0);
+        assert_close(geometry.cells_size(), 50.0);
+    }
+
+    #[test]
+    fn board_is_centered_in_wide_containers() {
+        let geometry = BoardGeometry::new(Size::new(1200.0, 900.0), 0.5, false);
+        assert_close(geometry.cells_size(), 100.0);
+        assert_eq!(geometry.board_rect(), Rect::new(150.0, 0.0, 1050.0, 900.0));
+        assert_point_close(geometry.column_row_origin(0, 0), Point::new(200.0, 50.0));
+    }
+
+    #[test]
+    fn board_is_centered_in_tall_containers() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 1000.0), 0.5, false);
+        assert_close(geometry.cells_size(), 100.0);
+        assert_eq!(geometry.board_rect(), Rect::new(0.0, 50.0, 900.0, 950.0));
+        assert_point_close(geometry.column_row_origin(7, 7), Point::new(750.0, 800.0));
+    }
+
+    #[test]
+    fn white_sees_a1_bottom_left() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, false);
+        let a1 = CellCoordinates { file: 0, rank: 0 };
+        let h8 = CellCoordinates { file: 7, rank: 7 };
+        assert_eq!(geometry.column_row(a1), (0, 7));
+        assert_eq!(geometry.column_row(h8), (7, 0));
+        assert_point_close(geometry.cell_origin(a1), Point::new(50.0, 750.0));
+        assert_eq!(geometry.cell_rect(h8), Rect::new(750.0, 50.0, 850.0, 150.0));
+    }
+
+    #[test]
+    fn black_sees_a1_top_right() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, true);
+        let a1 = CellCoordinates { file: 0, rank: 0 };
+        let h8 = CellCoordinates { file: 7, rank: 7 };
+        assert_eq!(geometry.column_row(a1), (7, 0));
+        assert_eq!(geometry.column_row(h8), (0, 7));
+        assert_point_close(geometry.cell_origin(a1), Point::new(750.0, 50.0));
+        assert_eq!(geometry.cell_rect(h8), Rect::new(50.0, 750.0, 150.0, 850.0));
+    }
+
+    #[test]
+    fn cells_and_columns_rows_round_trip() {
+        for reversed in [false, true].iter() {
+            let geometry = BoardGeometry::new(Size::new(640.0, 480.0), 0.3, *reversed);
+            for file in 0..8 {
+                for rank in 0..8 {
+                    let cell = CellCoordinates { file, rank };
+                    let (col, row) = geometry.column_row(cell);
+                    assert!(col < 8 && row < 8);
+                    assert_eq!(geometry.cell(col, row), cell);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_cell_center_hits_its_cell() {
+        for reversed in [false, true].iter() {
+            let geometry = BoardGeometry::new(Size::new(500.0, 700.0), 0.5, *reversed);
+            for file in 0..8 {
+                for rank in 0..8 {
+                    let cell = CellCoordinates { file, rank };
+                    let center = geometry.cell_rect(cell).center();
+                    assert_eq!(geometry.cell_at(center), Some(cell));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hit_testing_uses_the_cells_edges() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, false);
+        assert_eq!(geometry.column_row_at(Point::new(50.0, 50.0)), Some((0, 0)));
+        assert_eq!(
+            geometry.column_row_at(Point::new(149.9, 149.9)),
+            Some((0, 0))
+        );
+        assert_eq!(
+            geometry.column_row_at(Point::new(150.0, 150.0)),
+            Some((1, 1))
+        );
+        assert_eq!(
+            geometry.column_row_at(Point::new(849.9, 849.9)),
+            Some((7, 7))
+        );
+    }
+
+    #[test]
+    fn border_and_outside_are_not_cells() {
+        let geometry = BoardGeometry::new(Size::new(1200.0, 900.0), 0.5, false);
+        let outside_points = [
+            Point::new(160.0, 450.0),
+            Point::new(1040.0, 450.0),
+            Point::new(600.0, 20.0),
+            Point::new(600.0, 880.0),
+            Point::new(1000.0, 860.0),
+            Point::new(100.0, 450.0),
+            Point::new(600.0, -10.0),
+            Point::new(1300.0, 1000.0),
+        ];
+        for point in outside_points.iter() {
+            assert_eq!(geometry.cell_at(*point), None, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn empty_container_has_no_cells() {
+        let geometry = BoardGeometry::new(Size::ZERO, 0.5, false);
+        assert_close(geometry.cells_size(), 0.0);
+        assert_eq!(geometry.cell_at(Point::ZERO), None);
+    }
+
+    #[test]
+    fn hit_testing_honours_orientation() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, true);
+        assert_eq!(
+            geometry.cell_at(Point::new(60.0, 60.0)),
+            Some(CellCoordinates { file: 7, rank: 0 })
+        );
+        assert_eq!(
+            geometry.cell_at(Point::new(840.0, 840.0)),
+            Some(CellCoordinates { file: 0, rank: 7 })
+        );
+    }
+
+    #[test]
+    fn border_centers_are_in_the_middle_of_the_border() {
+        let geometry = BoardGeometry::new(Size::new(1000.0, 900.0), 0.5, false);
+        assert_close(geometry.top_border_center_y(), 25.0);
+        assert_close(geometry.bottom_border_center_y(), 875.0);
+        assert_close(geometry.left_border_center_x(), 75.0);
+        assert_close(geometry.right_border_center_x(), 925.0);
+        assert_point_close(
+            geometry.bottom_right_corner_center(),
+            Point::new(925.0, 875.0),
+        );
+        assert_point_close(
+            geometry.bottom_left_corner_center(),
+            Point::new(75.0, 875.0),
+        );
+        assert_point_close(geometry.top_left_corner_center(), Point::new(75.0, 25.0));
+    }
+
+    #[test]
+    fn columns_and_rows_centers() {
+        let geometry = BoardGeometry::new(Size::new(900.0, 900.0), 0.5, false);
+        assert_close(geometry.column_center_x(0), 100.0);
+        assert_close(geometry.column_center_x(7), 800.0);
+        assert_close(geometry.row_center_y(0), 100.0);
+        assert_close(geometry.row_center_y(3), 400.0);
+    }
+}
@@ -0,0 +1,152 @@
+use druid::text::format::{Formatter, Validation, ValidationError};
+use druid::text::Selection;
+use druid::{Color, Data, Env, Key, Lens};
+
+pub const BACKGROUND_COLOR: Key<Color> = Key::new("chess-exercises-organizer.board.background-color");
+pub const LIGHT_CELLS_COLOR: Key<Color> =
+    Key::new("chess-exercises-organizer.board.light-cells-color");
+pub const DARK_CELLS_COLOR: Key<Color> = Key::new("chess-exercises-organizer.board.dark-cells-color");
+pub const START_CELL_COLOR: Key<Color> = Key::new("chess-exercises-organizer.board.start-cell-color");
+pub const END_CELL_COLOR: Key<Color> = Key::new("chess-exercises-organizer.board.end-cell-color");
+pub const COORDINATES_COLOR: Key<Color> =
+    Key::new("chess-exercises-organizer.board.coordinates-color");
+pub const CURSOR_COLOR: Key<Color> = Key::new("chess-exercises-organizer.board.cursor-color");
+
+/// Colours used to paint the chess board.
+#[derive(Lens, Data, Clone, Debug, PartialEq)]
+pub struct BoardTheme {
+    pub name: String,
+    pub background: Color,
+    pub light_cells: Color,
+    pub dark_cells: Color,
+    pub start_cell: Color,
+    pub end_cell: Color,
+    pub coordinates: Color,
+    pub cursor: Color,
+}
+
+impl BoardTheme {
+    pub fn new() -> Self {
+        BoardTheme::presets().remove(0)
+    }
+
+    pub fn presets() -> Vec<BoardTheme> {
+        vec![
+            BoardTheme {
+                name: String::from("Classic"),
+                background: Color::rgb8(214, 59, 96),
+                light_cells: Color::rgb8(255, 206, 158),
+                dark_cells: Color::rgb8(209, 139, 71),
+                start_cell: Color::rgb8(178, 46, 230),
+                end_cell: Color::rgb8(112, 209, 35),
+                coordinates: Color::rgb8(255, 255, 0),
+                cursor: Color::rgb8(30, 144, 255),
+            },
+            BoardTheme {
+                name: String::from("Ocean"),
+                background: Color::rgb8(38, 70, 110),
+                light_cells: Color::rgb8(222, 227, 230),
+                dark_cells: Color::rgb8(140, 162, 173),
+                start_cell: Color::rgb8(244, 162, 97),
+                end_cell: Color::rgb8(233, 196, 106),
+                coordinates: Color::rgb8(230, 240, 250),
+                cursor: Color::rgb8(255, 255, 255),
+            },
+            BoardTheme {
+                name: String::from("Forest"),
+                background: Color::rgb8(52, 78, 65),
+                light_cells: Color::rgb8(238, 238, 210),
+                dark_cells: Color::rgb8(118, 150, 86),
+                start_cell: Color::rgb8(246, 246, 105),
+                end_cell: Color::rgb8(186, 202, 68),
+                coordinates: Color::rgb8(238, 238, 210),
+                cursor: Color::rgb8(255, 140, 0),
+            },
+            BoardTheme {
+                name: String::from("High contrast"),
+                background: Color::rgb8(0, 0, 0),
+                light_cells: Color::rgb8(255, 255, 255),
+                dark_cells: Color::rgb8(96, 96, 96),
+                start_cell: Color::rgb8(0, 120, 255),
+                end_cell: Color::rgb8(255, 0, 0),
+                coordinates: Color::rgb8(255, 255, 255),
+                cursor: Color::rgb8(255, 255, 0),
+            },
+            // Blue and orange stay distinguishable with the most common colour blindnesses.
+            BoardTheme {
+                name: String::from("Colour-blind friendly"),
+                background: Color::rgb8(51, 51, 51),
+                light_cells: Color::rgb8(240, 228, 200),
+                dark_cells: Color::rgb8(120, 120, 150),
+                start_cell: Color::rgb8(0, 114, 178),
+                end_cell: Color::rgb8(230, 159, 0),
+                coordinates: Color::rgb8(240, 228, 66),
+                cursor: Color::rgb8(204, 121, 167),
+            },
+        ]
+    }
+
+    pub fn preset(name: &str) -> Option<BoardTheme> {
+        BoardTheme::presets()
+            .into_iter()
+            .find(|theme| theme.name == name)
+    }
+
+    pub fn apply_to_env(&self, env: &mut Env) {
+        env.set(BACKGROUND_COLOR, self.background.clone());
+        env.set(LIGHT_CELLS_COLOR, self.light_cells.clone());
+        env.set(DARK_CELLS_COLOR, self.dark_cells.clone());
+        env.set(START_CELL_COLOR, self.start_cell.clone());
+        env.set(END_CELL_COLOR, self.end_cell.clone());
+        env.set(COORDINATES_COLOR, self.coordinates.clone());
+        env.set(CURSOR_COLOR, self.cursor.clone());
+    }
+}
+
+pub fn color_to_hex(color: &Color) -> String {
+    let (red, green, blue, _alpha) = color.as_rgba8();
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+pub fn color_from_hex(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    Color::from_hex_str(hex).ok()
+}
+
+/// Edits a colour as a `#rrggbb` string.
+pub struct HexColorFormatter;
+
+impl Formatter<Color> for HexColorFormatter {
+    fn format(&self, value: &Color) -> String {
+        color_to_hex(value)
+    }
+
+    fn validate_partial_input(&self, input: &str, _sel: &Selection) -> Validation {
+        let digits = input.strip_prefix('#').unwrap_or(input);
+        let valid =
+            digits.len() <= 6 && digits.chars().all(|character| character.is_ascii_hexdigit());
+        if valid {
+            Validation::success()
+        } else {
+            Validation::failure(InvalidHexColor)
+        }
+    }
+
+    fn value(&self, input: &str) -> Result<Color, ValidationError> {
+        color_from_hex(input).ok_or_else(|| ValidationError::new(InvalidHexColor))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InvalidHexColor;
+
+impl std::fmt::Display for InvalidHexColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected a colour such as #ffce9e")
+    }
+}
+
+impl std::error::Error for InvalidHexColor {}
@@ -1,21 +1,81 @@
+use super::analysis_panel::analysis_panel_builder;
+use super::app_data::AppData;
+use super::book_panel::book_panel_builder;
 use super::chess_board::{ChessBoard, ChessBoardData};
+use super::clock_panel::clock_panel_builder;
+use super::computer_panel::computer_panel_builder;
+use super::endgame_panel::endgame_panel_builder;
+use super::evaluation_bar::EvaluationBar;
+use super::move_entry::move_entry_builder;
+use super::new_game_panel::new_game_panel_builder;
+use super::position_panel::position_panel_builder;
+use super::repertoire_panel::repertoire_panel_builder;
+use super::review_panel::review_panel_builder;
+use super::rush_panel::rush_panel_builder;
+use super::settings_window::settings_window;
 use super::svg_image_button::SvgImageToggleButton;
+use super::tablebase_panel::tablebase_panel_builder;
+use super::visibility_panel::visibility_panel_builder;
 
-use druid::widget::Flex;
-use druid::{Widget, WidgetExt, Size};
+use druid::widget::{Button, Flex, Scroll};
+use druid::{LocalizedString, Size, Widget, WidgetExt};
 
-pub fn game_zone_builder() -> impl Widget<ChessBoardData> {
-    let chess_board = ChessBoard::new();
+pub fn game_zone_builder() -> impl Widget<AppData> {
+    let chess_board = ChessBoard::new().lens(AppData::board);
 
     let button_toggle_board_orientation = SvgImageToggleButton::new(
         Size::new(490.667, 490.667),
         String::from(include_str!("./vectors/reverseArrows.svg")),
         String::from(include_str!("./vectors/reverseArrows.svg")),
     )
-    .lens(ChessBoardData::reversed);
-    let buttons_zone = Flex::row().with_child(button_toggle_board_orientation).padding(1.0);
+    .lens(ChessBoardData::reversed)
+    .lens(AppData::board);
+    let button_settings = Button::new(LocalizedString::new("settings-button")).on_click(
+        |ctx, _data: &mut AppData, _env| {
+            ctx.new_window(settings_window());
+        },
+    );
+    let buttons_zone = Flex::row()
+        .with_child(button_toggle_board_orientation)
+        .with_spacer(4.0)
+        .with_child(button_settings)
+        .padding(1.0);
+
+    let side_zone = Flex::column()
+        .with_child(new_game_panel_builder())
+        .with_spacer(8.0)
+        .with_child(clock_panel_builder())
+        .with_spacer(8.0)
+        .with_child(computer_panel_builder())
+        .with_spacer(8.0)
+        .with_child(rush_panel_builder())
+        .with_spacer(8.0)
+        .with_child(repertoire_panel_builder())
+        .with_spacer(8.0)
+        .with_child(endgame_panel_builder())
+        .with_spacer(8.0)
+        .with_child(move_entry_builder())
+        .with_spacer(8.0)
+        .with_child(position_panel_builder())
+        .with_spacer(8.0)
+        .with_child(visibility_panel_builder())
+        .with_spacer(8.0)
+        .with_child(analysis_panel_builder())
+        .with_spacer(8.0)
+        .with_child(book_panel_builder())
+        .with_spacer(8.0)
+        .with_child(tablebase_panel_builder())
+        .with_spacer(8.0)
+        .with_child(review_panel_builder())
+        .padding(4.0);
+    let side_zone = Scroll::new(side_zone).vertical().fix_width(200.0);
+    let main_zone = Flex::row()
+        .with_child(EvaluationBar::new())
+        .with_spacer(2.0)
+        .with_flex_child(chess_board, 1.0)
+        .with_child(side_zone);
 
     Flex::column()
         .with_flex_child(buttons_zone, 0.1)
-        .with_flex_child(chess_board, 1.0)
+        .with_flex_child(main_zone, 1.0)
 }
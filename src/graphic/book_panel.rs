@@ -0,0 +1,101 @@
+use druid::widget::{Controller, CrossAxisAlignment, Flex, Label, LineBreaking};
+use druid::{Data, Env, LocalizedString, UpdateCtx, Widget, WidgetExt};
+use log::error;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use super::app_data::AppData;
+use super::commands::BOOK_LOADED;
+use super::localization::{book_moves_text, localize};
+use crate::chess::polyglot::{BookMove, OpeningBook};
+use crate::chess::BoardLogic;
+
+/// The opening book of the settings.
+#[derive(Data, Clone, Debug)]
+pub struct LoadedBook {
+    pub book: Arc<OpeningBook>,
+}
+
+impl LoadedBook {
+    pub fn moves(&self, board: &BoardLogic) -> Vec<BookMove> {
+        self.book.moves(board)
+    }
+}
+
+#[derive(Data, Clone, Debug)]
+pub struct BookData {
+    /// None when no book is chosen, or when it could not be read.
+    pub loaded: Option<LoadedBook>,
+    /// Why the book of the settings could not be read.
+    pub error: Option<String>,
+}
+
+impl BookData {
+    pub fn load(book_path: Option<&Path>) -> Self {
+        match load_book(book_path) {
+            Ok(loaded) => Self {
+                loaded,
+                error: None,
+            },
+            Err(error) => Self {
+                loaded: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Reads the book, if one is chosen.
+pub fn load_book(book_path: Option<&Path>) -> Result<Option<LoadedBook>, String> {
+    let book_path = match book_path {
+        Some(book_path) => book_path,
+        None => return Ok(None),
+    };
+    match OpeningBook::open(book_path) {
+        Ok(book) => Ok(Some(LoadedBook {
+            book: Arc::new(book),
+        })),
+        Err(err) => {
+            error!("{}", err);
+            Err(err.to_string())
+        }
+    }
+}
+
+/// The moves of the book for the position of the board, with their share of the weights.
+pub fn book_panel_builder() -> impl Widget<AppData> {
+    let moves = Label::dynamic(|data: &AppData, env| {
+        if !data.board.board.variant.is_known_to_engines() {
+            return localize(env, "variant-unknown-to-engines");
+        }
+        book_moves_text(env, &data.book, &data.board.board)
+    })
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("book-panel-title")))
+        .with_child(moves)
+        .controller(BookLoader)
+}
+
+/// Reads the book again whenever its path changes in the settings.
+struct BookLoader;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for BookLoader {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if old_data.settings.book_path != data.settings.book_path {
+            let outcome = load_book(data.settings.book().as_deref());
+            ctx.submit_command(BOOK_LOADED.with(outcome));
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -1,56 +1,92 @@
 use druid::kurbo::Circle;
 use druid::text::{ArcStr, FontDescriptor, TextLayout};
 use druid::widget::prelude::*;
-use druid::{widget::SvgData, Affine, Color, FontFamily, FontWeight, Lens, Rect};
-use log::error;
+use druid::{Affine, Color, FontFamily, FontWeight, KbKey, Lens, Point};
+
+use super::board_coordinates::{
+    placement_from_env, CoordinatesPlacement, COORDINATES_FONT_SCALE, COORDINATES_UPPERCASE,
+};
+use super::board_geometry::{BoardGeometry, CellCoordinates, BORDER_WIDTH, DEFAULT_BORDER_WIDTH};
+use super::board_theme::{
+    BACKGROUND_COLOR, COORDINATES_COLOR, CURSOR_COLOR, DARK_CELLS_COLOR, END_CELL_COLOR,
+    LIGHT_CELLS_COLOR, START_CELL_COLOR,
+};
+use super::piece_set::{PieceSet, DEFAULT_PIECE_SET, PIECE_SET};
 
 use pleco::core::{sq::SQ, Piece, Player};
-use pleco::Board;
 
-#[derive(Lens, Data, Clone, Debug)]
-pub struct ChessBoardData {
-    board: BoardLogic,
-    reversed: bool,
+use super::commands::{DESCRIBE_POSITION, PEEK, PLAY_MOVE};
+use super::localization::file_letters;
+use crate::chess::variant::{Variant, HILL_SQUARES};
+use crate::chess::BoardLogic;
+
+/// Which pieces are drawn, for visualisation training.
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub enum PiecesVisibility {
+    All,
+    /// Blindfold : only the empty board is drawn.
+    None,
+    HideWhite,
+    HideBlack,
+    /// Pieces which moved since the start position are hidden.
+    HideMoved,
 }
 
-#[derive(Clone, Debug)]
-struct BoardLogic {
-    inner_logic: Board,
+impl PiecesVisibility {
+    pub const ALL: [PiecesVisibility; 5] = [
+        PiecesVisibility::All,
+        PiecesVisibility::None,
+        PiecesVisibility::HideWhite,
+        PiecesVisibility::HideBlack,
+        PiecesVisibility::HideMoved,
+    ];
 }
 
-impl Data for BoardLogic {
-    fn same(&self, other: &Self) -> bool {
-        let this_fen = self.inner_logic.fen();
-        let other_fen = other.inner_logic.fen();
-        this_fen == other_fen
-    }
+#[derive(Lens, Data, Clone, Debug)]
+pub struct ChessBoardData {
+    pub board: BoardLogic,
+    pub reversed: bool,
+    pub visibility: PiecesVisibility,
+    /// Whether the peek key is held, revealing all the pieces.
+    pub peeking: bool,
 }
 
 impl ChessBoardData {
     pub fn new() -> Self {
         Self {
-            board: BoardLogic {
-                inner_logic: Board::from_fen(
-                    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
-                )
-                .unwrap(),
-            },
+            board: BoardLogic::new(),
             reversed: false,
+            visibility: PiecesVisibility::All,
+            peeking: false,
         }
     }
-}
 
-struct CellCoordinates {
-    file: u8,
-    rank: u8,
+    /// Whether all the pieces are drawn, either because none is hidden or while peeking.
+    pub fn shows_all_pieces(&self) -> bool {
+        self.visibility == PiecesVisibility::All || self.peeking
+    }
+
+    /// Whether the piece standing on the square is drawn, given the squares of the pieces
+    /// which moved since the start position.
+    fn is_piece_shown(&self, square: SQ, piece: Piece, moved_squares: &[SQ]) -> bool {
+        match self.visibility {
+            _ if self.peeking => true,
+            PiecesVisibility::All => true,
+            PiecesVisibility::None => false,
+            PiecesVisibility::HideWhite => piece.player() != Some(Player::White),
+            PiecesVisibility::HideBlack => piece.player() != Some(Player::Black),
+            PiecesVisibility::HideMoved => !moved_squares.contains(&square),
+        }
+    }
 }
 
 struct DragAndDropState {
     active: bool,
+    by_keyboard: bool,
     start_cell: Option<CellCoordinates>,
     end_cell: Option<CellCoordinates>,
     moved_piece_value: Option<pleco::Piece>,
-    moved_piece_location: Option<(f64, f64)>,
+    moved_piece_location: Option<Point>,
 }
 
 impl DragAndDropState {
@@ -60,21 +96,16 @@ impl DragAndDropState {
         self.moved_piece_location = None;
         self.moved_piece_value = None;
         self.active = false;
+        self.by_keyboard = false;
     }
 }
 
-fn coordinates_to_square_algebraic(coordinates: &CellCoordinates) -> String {
-    let file_str = (('a' as u8) + coordinates.file) as char;
-    let rank_str = (('1' as u8) + coordinates.rank) as char;
-    format!(
-        "{}{}",
-        file_str,
-        rank_str,
-    )
-}
-
 pub struct ChessBoard {
     dnd_state: DragAndDropState,
+    piece_set: PieceSet,
+    keyboard_cursor: CellCoordinates,
+    /// Letters of the files from a to h, in the user language.
+    file_letters: Vec<char>,
 }
 
 impl ChessBoard {
@@ -82,142 +113,152 @@ impl ChessBoard {
         ChessBoard {
             dnd_state: DragAndDropState {
                 active: false,
+                by_keyboard: false,
                 start_cell: None,
                 end_cell: None,
                 moved_piece_location: None,
                 moved_piece_value: None,
             },
+            piece_set: PieceSet::load(DEFAULT_PIECE_SET),
+            keyboard_cursor: CellCoordinates { file: 4, rank: 1 },
+            file_letters: "ABCDEFGH".chars().collect(),
         }
     }
 
-    fn draw_background(&self, ctx: &mut PaintCtx) {
-        let size = ctx.size();
-        let rect = size.to_rect();
-        ctx.fill(rect, &Color::rgb8(214, 59, 96));
+    fn update_piece_set(&mut self, env: &Env) {
+        let piece_set_name = env.get(PIECE_SET);
+        if self.piece_set.name() != &*piece_set_name {
+            self.piece_set = PieceSet::load(&piece_set_name);
+        }
+    }
+
+    fn draw_background(&self, ctx: &mut PaintCtx, geometry: &BoardGeometry, env: &Env) {
+        ctx.fill(geometry.board_rect(), &env.get(BACKGROUND_COLOR));
     }
 
-    fn draw_cells(&self, ctx: &mut PaintCtx, data: &ChessBoardData) {
-        let total_size = ctx.size().width;
-        let cells_size = total_size * 0.1111;
+    fn draw_cells(&self, ctx: &mut PaintCtx, geometry: &BoardGeometry, env: &Env) {
         for row in 0..8 {
             for col in 0..8 {
+                let cell = geometry.cell(col, row);
                 let is_white_cell = (row + col) % 2 > 0;
-                let is_start_cell = self.is_start_cell(data, col, row);
-                let is_end_cell = self.is_end_cell(data, col, row);
+                let is_start_cell = self.dnd_state.start_cell == Some(cell);
+                let is_end_cell = self.dnd_state.end_cell == Some(cell);
 
                 let color = if is_end_cell {
-                    Color::rgb8(112, 209, 35)
+                    env.get(END_CELL_COLOR)
                 } else if is_start_cell {
-                    Color::rgb8(178, 46, 230)
+                    env.get(START_CELL_COLOR)
                 } else if is_white_cell {
-                    Color::rgb8(255, 206, 158)
+                    env.get(LIGHT_CELLS_COLOR)
                 } else {
-                    Color::rgb8(209, 139, 71)
+                    env.get(DARK_CELLS_COLOR)
                 };
-                let x = cells_size * (0.5 + (col as f64));
-                let y = cells_size * (0.5 + (row as f64));
-
-                let rect = Rect::new(x, y, x + (cells_size as f64), y + (cells_size as f64));
-                ctx.fill(rect, &color);
+                ctx.fill(geometry.column_row_rect(col, row), &color);
             }
         }
     }
 
-    fn draw_coordinates(&self, ctx: &mut PaintCtx, data: &ChessBoardData, env: &Env) {
-        let total_size = ctx.size().width;
-        let cells_size = total_size * 0.1111;
-        let font_size = cells_size * 0.3;
-
-        let files_coordinates = if data.reversed {
-            "HGFEDCBA"
-        } else {
-            "ABCDEFGH"
-        };
-        let rank_coordinates = if data.reversed {
-            "12345678"
-        } else {
-            "87654321"
-        };
-
-        for (index, current_coord) in files_coordinates.chars().enumerate() {
-            let x = cells_size * ((index as f64) + 0.9);
-            let y1 = cells_size * 0.08;
-            let y2 = cells_size * 8.58;
-            let current_str = format!("{}", current_coord);
-
-            let color = Color::rgb(255.0, 199.0, 0.0);
-            let mut label = TextLayout::<ArcStr>::from_text(current_str);
-            label.set_font(
-                FontDescriptor::new(FontFamily::SANS_SERIF)
-                    .with_size(font_size)
-                    .with_weight(FontWeight::BOLD),
-            );
-            label.set_text_color(color);
-            label.rebuild_if_needed(ctx.text(), env);
+    fn draw_coordinates(&self, ctx: &mut PaintCtx, geometry: &BoardGeometry, env: &Env) {
+        let placement = placement_from_env(env);
+        if placement == CoordinatesPlacement::Hidden {
+            return;
+        }
+        let cells_size = geometry.cells_size();
+        let font_size = cells_size * 0.3 * env.get(COORDINATES_FONT_SCALE);
+        let uppercase = env.get(COORDINATES_UPPERCASE);
+        // Room left between the text and the border of the square, when written inside.
+        let inside_padding = cells_size * 0.04;
+
+        for col in 0..8 {
+            // Inside the squares, the letters are written on the bottom row.
+            let bottom_cell = geometry.cell(col, 7);
+            let letter = self.file_letters[bottom_cell.file as usize];
+            let current_coord = if uppercase {
+                letter.to_uppercase().to_string()
+            } else {
+                letter.to_lowercase().to_string()
+            };
+            let is_light_cell = (7 + col) % 2 > 0;
+            let color = coordinates_color(placement, is_light_cell, env);
+            let label = coordinates_label(ctx, current_coord, font_size, color, env);
+            let label_size = label.size();
+
+            let centered_x = geometry.column_center_x(col) - label_size.width / 2.0;
+            let top_y = geometry.top_border_center_y() - label_size.height / 2.0;
+            let bottom_y = geometry.bottom_border_center_y() - label_size.height / 2.0;
+            let positions = match placement {
+                CoordinatesPlacement::AllSides => vec![(centered_x, top_y), (centered_x, bottom_y)],
+                CoordinatesPlacement::TwoSides => vec![(centered_x, bottom_y)],
+                CoordinatesPlacement::InsideSquares => {
+                    let cell_rect = geometry.column_row_rect(col, 7);
+                    vec![(
+                        cell_rect.x1 - label_size.width - inside_padding,
+                        cell_rect.y1 - label_size.height - inside_padding,
+                    )]
+                }
+                CoordinatesPlacement::Hidden => vec![],
+            };
 
             ctx.with_save(|ctx| {
-                label.draw(ctx, (x, y1));
-                label.draw(ctx, (x, y2));
+                for position in positions {
+                    label.draw(ctx, position);
+                }
             });
         }
 
-        for (index, current_coord) in rank_coordinates.chars().enumerate() {
-            let y = cells_size * ((index as f64) + 0.85);
-            let x1 = cells_size * 0.15;
-            let x2 = cells_size * 8.65;
-            let current_str = format!("{}", current_coord);
-
-            let color = Color::rgb(255.0, 199.0, 0.0);
-            let mut label = TextLayout::<ArcStr>::from_text(current_str);
-            label.set_font(
-                FontDescriptor::new(FontFamily::SANS_SERIF)
-                    .with_size(font_size)
-                    .with_weight(FontWeight::BOLD),
-            );
-            label.set_text_color(color);
-            label.rebuild_if_needed(ctx.text(), env);
+        for row in 0..8 {
+            // Inside the squares, the numbers are written on the left column.
+            let left_cell = geometry.cell(0, row);
+            let current_coord = ((b'1' + left_cell.rank) as char).to_string();
+            let is_light_cell = row % 2 > 0;
+            let color = coordinates_color(placement, is_light_cell, env);
+            let label = coordinates_label(ctx, current_coord, font_size, color, env);
+            let label_size = label.size();
+
+            let centered_y = geometry.row_center_y(row) - label_size.height / 2.0;
+            let left_x = geometry.left_border_center_x() - label_size.width / 2.0;
+            let right_x = geometry.right_border_center_x() - label_size.width / 2.0;
+            let positions = match placement {
+                CoordinatesPlacement::AllSides => vec![(left_x, centered_y), (right_x, centered_y)],
+                CoordinatesPlacement::TwoSides => vec![(left_x, centered_y)],
+                CoordinatesPlacement::InsideSquares => {
+                    let cell_origin = geometry.column_row_origin(0, row);
+                    vec![(
+                        cell_origin.x + inside_padding,
+                        cell_origin.y + inside_padding,
+                    )]
+                }
+                CoordinatesPlacement::Hidden => vec![],
+            };
 
             ctx.with_save(|ctx| {
-                label.draw(ctx, (x1, y));
-                label.draw(ctx, (x2, y));
+                for position in positions {
+                    label.draw(ctx, position);
+                }
             });
         }
     }
 
-    fn draw_pieces(&self, ctx: &mut PaintCtx, data: &ChessBoardData) {
-        let total_size = ctx.size().width;
-        let cells_size = total_size * 0.1111;
+    fn draw_pieces(&self, ctx: &mut PaintCtx, data: &ChessBoardData, geometry: &BoardGeometry) {
+        let ratio = geometry.cells_size() / 45_f64;
+        let moved_squares = data.board.moved_pieces_squares();
 
-        for row in 0..8 {
-            let rank = if data.reversed { row } else { 7 - row };
-            for col in 0..8 {
-                let file = if data.reversed { 7 - col } else { col };
+        for rank in 0..8 {
+            for file in 0..8 {
+                let cell = CellCoordinates { file, rank };
+                if self.dnd_state.start_cell == Some(cell) {
+                    continue;
+                }
 
-                if let Some(start_cell_coordinates) = &self.dnd_state.start_cell {
-                    let is_start_cell_piece =
-                        file == start_cell_coordinates.file && rank == start_cell_coordinates.rank;
-                    if is_start_cell_piece {
-                        continue;
-                    }
+                let square = SQ(file + 8 * rank);
+                let piece = data.board.piece_at(square);
+                if !data.is_piece_shown(square, piece, &moved_squares) {
+                    continue;
                 }
 
-                let square = SQ((file + 8 * rank) as u8);
-                let piece = data.board.inner_logic.piece_at_sq(square);
-
-                let piece_image_raw_data = ChessBoard::get_piece_image_raw_data(piece);
-                if let Some(piece_image_raw_data) = piece_image_raw_data {
-                    let piece_svg_data = match piece_image_raw_data.parse::<SvgData>() {
-                        Ok(svg) => svg,
-                        Err(err) => {
-                            error!("{}", err);
-                            error!("Using an empty SVG instead of {}.", piece_image_raw_data);
-                            SvgData::default()
-                        }
-                    };
-                    let ratio = (cells_size as f64) / 45_f64;
-                    let x = cells_size * (0.5 + (col as f64));
-                    let y = cells_size * (0.5 + (row as f64));
-                    let affine_matrix = Affine::translate((x, y)) * Affine::scale(ratio);
+                if let Some(piece_svg_data) = self.piece_set.image(piece) {
+                    let origin = geometry.cell_origin(cell);
+                    let affine_matrix = Affine::translate(origin.to_vec2()) * Affine::scale(ratio);
 
                     ctx.with_save(|ctx| {
                         piece_svg_data.to_piet(affine_matrix, ctx);
@@ -227,26 +268,25 @@ impl ChessBoard {
         }
     }
 
-    fn draw_moved_piece(&self, ctx: &mut PaintCtx) {
-        if let Some(moved_piece) = self.dnd_state.moved_piece_value {
-            let total_size = ctx.size().width;
-            let cells_size = total_size * 0.1111;
-            let ratio = (cells_size as f64) / 45_f64;
-            let piece_image_raw_data = ChessBoard::get_piece_image_raw_data(moved_piece);
-            if let Some(piece_image_raw_data) = piece_image_raw_data {
-                let piece_svg_data = match piece_image_raw_data.parse::<SvgData>() {
-                    Ok(svg) => svg,
-                    Err(err) => {
-                        error!("{}", err);
-                        error!("Using an empty SVG instead of {}.", piece_image_raw_data);
-                        SvgData::default()
-                    }
-                };
-
+    fn draw_moved_piece(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &ChessBoardData,
+        geometry: &BoardGeometry,
+    ) {
+        if let (Some(moved_piece), Some(start_cell)) =
+            (self.dnd_state.moved_piece_value, self.dnd_state.start_cell)
+        {
+            let square = SQ(start_cell.file + 8 * start_cell.rank);
+            let moved_squares = data.board.moved_pieces_squares();
+            if !data.is_piece_shown(square, moved_piece, &moved_squares) {
+                return;
+            }
+            let ratio = geometry.cells_size() / 45_f64;
+            if let Some(piece_svg_data) = self.piece_set.image(moved_piece) {
                 if let Some(piece_location) = self.dnd_state.moved_piece_location {
-                    let x = piece_location.0;
-                    let y = piece_location.1;
-                    let affine_matrix = Affine::translate((x, y)) * Affine::scale(ratio);
+                    let affine_matrix =
+                        Affine::translate(piece_location.to_vec2()) * Affine::scale(ratio);
 
                     ctx.with_save(|ctx| {
                         piece_svg_data.to_piet(affine_matrix, ctx);
@@ -256,187 +296,310 @@ impl ChessBoard {
         }
     }
 
-    fn draw_player_turn(&self, ctx: &mut PaintCtx, data: &ChessBoardData) {
-        let total_size = ctx.size().width;
-        let cells_size = total_size * 0.1111;
-
-        let location = cells_size * 8.7625;
-        let color = if data.board.inner_logic.turn() == Player::White {
-            Color::WHITE
+    fn draw_player_turn(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &ChessBoardData,
+        geometry: &BoardGeometry,
+    ) {
+        let (color, outline_color) = if data.board.turn() == Player::White {
+            (Color::WHITE, Color::BLACK)
         } else {
-            Color::BLACK
+            (Color::BLACK, Color::WHITE)
         };
-        let radius = cells_size * 0.2;
+        let min_radius = geometry.cells_size() * 0.12;
+        // Drawn in the bottom right corner of the border, so it must fit in it.
+        let radius = (geometry.cells_size() * 0.2).min(geometry.border_width() * 0.4);
 
-        let circle = Circle::new((location, location), radius);
-        ctx.fill(circle, &color);
+        if radius >= min_radius {
+            let circle = Circle::new(geometry.bottom_right_corner_center(), radius);
+            ctx.fill(circle, &color);
+        } else {
+            // The border is too thin : drawn inside the bottom right cell, outlined with the
+            // other colour so that it stays visible on a square of the same colour.
+            let cells_rect = geometry.cells_rect();
+            let center = Point::new(
+                cells_rect.x1 - min_radius * 1.5,
+                cells_rect.y1 - min_radius * 1.5,
+            );
+            let circle = Circle::new(center, min_radius);
+            ctx.fill(circle, &color);
+            ctx.stroke(circle, &outline_color, min_radius * 0.2);
+        }
     }
 
-    fn is_start_cell(&self, data: &ChessBoardData, col: u8, row: u8) -> bool {
-        if let Some(start_cell_coordinates) = &self.dnd_state.start_cell {
-            let start_cell_col = if data.reversed {
-                7 - start_cell_coordinates.file
-            } else {
-                start_cell_coordinates.file
-            };
-            let start_cell_row = if data.reversed {
-                start_cell_coordinates.rank
-            } else {
-                7 - start_cell_coordinates.rank
+    /// In King of the Hill, tints the four central squares the kings race to.
+    fn draw_hill(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &ChessBoardData,
+        geometry: &BoardGeometry,
+        env: &Env,
+    ) {
+        if data.board.variant != Variant::KingOfTheHill {
+            return;
+        }
+        let color = env.get(END_CELL_COLOR).with_alpha(0.4);
+        for square in &HILL_SQUARES {
+            let cell = CellCoordinates {
+                file: square.file_idx_of_sq(),
+                rank: square.rank_idx_of_sq(),
             };
-            start_cell_col == col && start_cell_row == row
-        } else {
-            false
+            ctx.fill(geometry.cell_rect(cell), &color);
         }
     }
 
-    fn is_end_cell(&self, data: &ChessBoardData, col: u8, row: u8) -> bool {
-        if let Some(end_cell_coordinates) = &self.dnd_state.end_cell {
-            let end_cell_col = if data.reversed {
-                7 - end_cell_coordinates.file
-            } else {
-                end_cell_coordinates.file
-            };
-            let end_cell_row = if data.reversed {
-                end_cell_coordinates.rank
-            } else {
-                7 - end_cell_coordinates.rank
-            };
-            end_cell_col == col && end_cell_row == row
+    /// In Three-check, writes the checks each player gave in the left corners of the border,
+    /// on their side of the board.
+    fn draw_checks_given(
+        &self,
+        ctx: &mut PaintCtx,
+        data: &ChessBoardData,
+        geometry: &BoardGeometry,
+        env: &Env,
+    ) {
+        if data.board.variant != Variant::ThreeCheck {
+            return;
+        }
+        // Written in the corners of the border, so it must fit in them.
+        let font_size = (geometry.cells_size() * 0.3).min(geometry.border_width() * 0.6);
+        let (bottom_player, top_player) = if data.reversed {
+            (Player::Black, Player::White)
         } else {
-            false
+            (Player::White, Player::Black)
+        };
+        let corners = [
+            (bottom_player, geometry.bottom_left_corner_center()),
+            (top_player, geometry.top_left_corner_center()),
+        ];
+        for (player, center) in corners.iter() {
+            let checks = data.board.checks[*player as usize];
+            let color = env.get(COORDINATES_COLOR);
+            let label = coordinates_label(ctx, format!("+{}", checks), font_size, color, env);
+            let size = label.size();
+            let origin = (center.x - size.width / 2.0, center.y - size.height / 2.0);
+            ctx.with_save(|ctx| label.draw(ctx, origin));
         }
     }
 
-    fn get_piece_image_raw_data(piece: pleco::Piece) -> Option<&'static str> {
-        match piece {
-            Piece::WhitePawn => Some(include_str!("../merida/wP.svg")),
-            Piece::WhiteKnight => Some(include_str!("../merida/wN.svg")),
-            Piece::WhiteBishop => Some(include_str!("../merida/wB.svg")),
-            Piece::WhiteRook => Some(include_str!("../merida/wR.svg")),
-            Piece::WhiteQueen => Some(include_str!("../merida/wQ.svg")),
-            Piece::WhiteKing => Some(include_str!("../merida/wK.svg")),
-            Piece::BlackPawn => Some(include_str!("../merida/bP.svg")),
-            Piece::BlackKnight => Some(include_str!("../merida/bN.svg")),
-            Piece::BlackBishop => Some(include_str!("../merida/bB.svg")),
-            Piece::BlackRook => Some(include_str!("../merida/bR.svg")),
-            Piece::BlackQueen => Some(include_str!("../merida/bQ.svg")),
-            Piece::BlackKing => Some(include_str!("../merida/bK.svg")),
-            Piece::None => None,
+    fn draw_keyboard_cursor(&self, ctx: &mut PaintCtx, geometry: &BoardGeometry, env: &Env) {
+        if !ctx.has_focus() {
+            return;
         }
+        let ring_width = geometry.cells_size() * 0.08;
+
+        let rect = geometry
+            .cell_rect(self.keyboard_cursor)
+            .inset(-ring_width / 2.0);
+        ctx.stroke(rect, &env.get(CURSOR_COLOR), ring_width);
     }
+
+    /// Sends the move from the drag start cell to the drag end cell, if legal, and ends the drag.
+    fn submit_dragged_move(&mut self, ctx: &mut EventCtx, data: &ChessBoardData) {
+        if let (Some(start_cell), Some(end_cell)) = (self.dnd_state.start_cell, self.dnd_state.end_cell) {
+            let origin = SQ(start_cell.file + 8 * start_cell.rank);
+            let destination = SQ(end_cell.file + 8 * end_cell.rank);
+            if let Some(chess_move) = data.board.dragged_move(origin, destination) {
+                ctx.submit_command(PLAY_MOVE.with(chess_move));
+            }
+        }
+
+        self.dnd_state.cancel();
+        ctx.request_update();
+    }
+
+    /// Moves the keyboard cursor by the given amount of columns and rows, as seen on screen.
+    fn move_keyboard_cursor(
+        &mut self,
+        ctx: &mut EventCtx,
+        geometry: &BoardGeometry,
+        col_delta: i8,
+        row_delta: i8,
+    ) {
+        let (col, row) = geometry.column_row(self.keyboard_cursor);
+        let col = (col as i8 + col_delta).clamp(0, 7) as u8;
+        let row = (row as i8 + row_delta).clamp(0, 7) as u8;
+        self.keyboard_cursor = geometry.cell(col, row);
+
+        if self.dnd_state.by_keyboard {
+            self.dnd_state.end_cell = Some(self.keyboard_cursor);
+            self.dnd_state.moved_piece_location = Some(geometry.cell_origin(self.keyboard_cursor));
+        }
+        ctx.request_update();
+    }
+
+    /// Picks up the piece under the keyboard cursor, or drops the piece being moved.
+    fn toggle_keyboard_drag(
+        &mut self,
+        ctx: &mut EventCtx,
+        data: &ChessBoardData,
+        geometry: &BoardGeometry,
+    ) {
+        if self.dnd_state.active {
+            self.submit_dragged_move(ctx, data);
+            return;
+        }
+
+        let cursor = self.keyboard_cursor;
+        let square = SQ(cursor.file + 8 * cursor.rank);
+        let piece = data.board.piece_at(square);
+        if piece == Piece::None {
+            return;
+        }
+
+        self.dnd_state.moved_piece_location = Some(geometry.cell_origin(cursor));
+        self.dnd_state.moved_piece_value = Some(piece);
+        self.dnd_state.start_cell = Some(cursor);
+        self.dnd_state.end_cell = Some(cursor);
+        self.dnd_state.active = true;
+        self.dnd_state.by_keyboard = true;
+        ctx.request_update();
+    }
+}
+
+/// Where the board is drawn in a widget of the given size.
+fn board_geometry(size: Size, data: &ChessBoardData, env: &Env) -> BoardGeometry {
+    let border_width = env.try_get(BORDER_WIDTH).unwrap_or(DEFAULT_BORDER_WIDTH);
+    BoardGeometry::new(size, border_width, data.reversed)
+}
+
+/// Inside the squares, coordinates take the colour of the other squares, as on lichess,
+/// so that they stay readable whatever the theme.
+fn coordinates_color(placement: CoordinatesPlacement, is_light_cell: bool, env: &Env) -> Color {
+    match placement {
+        CoordinatesPlacement::InsideSquares if is_light_cell => env.get(DARK_CELLS_COLOR),
+        CoordinatesPlacement::InsideSquares => env.get(LIGHT_CELLS_COLOR),
+        _ => env.get(COORDINATES_COLOR),
+    }
+}
+
+fn coordinates_label(
+    ctx: &mut PaintCtx,
+    text: String,
+    font_size: f64,
+    color: Color,
+    env: &Env,
+) -> TextLayout<ArcStr> {
+    let mut label = TextLayout::<ArcStr>::from_text(text);
+    label.set_font(
+        FontDescriptor::new(FontFamily::SANS_SERIF)
+            .with_size(font_size)
+            .with_weight(FontWeight::BOLD),
+    );
+    label.set_text_color(color);
+    label.rebuild_if_needed(ctx.text(), env);
+    label
 }
 
 impl Widget<ChessBoardData> for ChessBoard {
-    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut ChessBoardData, _env: &Env) {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut ChessBoardData, env: &Env) {
+        let geometry = board_geometry(ctx.size(), data, env);
+        // The moved piece must be around the mouse cursor.
+        let half_cell = (geometry.cells_size() / 2.0, geometry.cells_size() / 2.0);
         match event {
             Event::MouseDown(mouse_event) => {
+                ctx.request_focus();
                 if !self.dnd_state.active {
-                    let x = mouse_event.pos.x;
-                    let y = mouse_event.pos.y;
-
-                    let total_size = ctx.size().width;
-                    let cells_size = total_size * 0.1111;
-
-                    let col = ((x - cells_size * 0.5) / cells_size).floor() as i32;
-                    let row = ((y - cells_size * 0.5) / cells_size).floor() as i32;
-
-                    let out_of_bounds = (col < 0) || (col > 7) || (row < 0) || (row > 7);
-                    if out_of_bounds {
-                        return;
-                    }
-
-                    let file = if data.reversed { 7 - col } else { col } as u8;
-                    let rank = if data.reversed { row } else { 7 - row } as u8;
+                    let cell = match geometry.cell_at(mouse_event.pos) {
+                        Some(cell) => cell,
+                        None => return,
+                    };
 
-                    let square = SQ((file + 8 * rank) as u8);
-                    let piece = data.board.inner_logic.piece_at_sq(square);
+                    let square = SQ(cell.file + 8 * cell.rank);
+                    let piece = data.board.piece_at(square);
                     if piece == Piece::None {
                         return;
                     }
 
-                    // The moved piece must be around the mouse cursor.
-                    let x = x - cells_size * 0.5;
-                    let y = y - cells_size * 0.5;
-
-                    self.dnd_state.moved_piece_location = Some((x, y));
+                    self.dnd_state.moved_piece_location = Some(mouse_event.pos - half_cell);
                     self.dnd_state.moved_piece_value = Some(piece);
-                    self.dnd_state.start_cell = Some(CellCoordinates { file, rank });
+                    self.dnd_state.start_cell = Some(cell);
                     self.dnd_state.active = true;
                     ctx.request_update();
                 }
             }
-            Event::MouseUp(_mouse_event) => {
-                if self.dnd_state.active {
-                    let start_square_algebraic = if let Some(ref start_cell) = self.dnd_state.start_cell {
-                        coordinates_to_square_algebraic(start_cell)
-                    }
-                    else {
-                        "".to_string()
-                    };
-                    let end_square_algebraic = if let Some(ref end_cell) = self.dnd_state.end_cell {
-                        coordinates_to_square_algebraic(end_cell)
-                    }
-                    else {
-                        "".to_string()
-                    };
-                    let promotion_piece = "";
-                    let move_to_play = format!(
-                        "{}{}{}",
-                        start_square_algebraic,
-                        end_square_algebraic,
-                        promotion_piece,
-                    );
-                    data.board.inner_logic.apply_uci_move(&move_to_play);
-                    
-                    self.dnd_state.cancel();
-                    ctx.request_update();
-                }
+            Event::MouseUp(_mouse_event)
+                if self.dnd_state.active && !self.dnd_state.by_keyboard =>
+            {
+                self.submit_dragged_move(ctx, data);
             }
-            Event::MouseMove(mouse_event) => {
-                if self.dnd_state.active {
-                    let x = mouse_event.pos.x;
-                    let y = mouse_event.pos.y;
-
-                    let total_size = ctx.size().width;
-                    let cells_size = total_size * 0.1111;
-
-                    let col = ((x - cells_size * 0.5) / cells_size).floor() as i32;
-                    let row = ((y - cells_size * 0.5) / cells_size).floor() as i32;
+            Event::MouseMove(mouse_event) if self.dnd_state.active => {
+                let cell = match geometry.cell_at(mouse_event.pos) {
+                    Some(cell) => cell,
+                    None => return,
+                };
 
-                    let out_of_bounds = (col < 0) || (col > 7) || (row < 0) || (row > 7);
-                    if out_of_bounds {
-                        return;
+                self.dnd_state.moved_piece_location = Some(mouse_event.pos - half_cell);
+                self.dnd_state.end_cell = Some(cell);
+                ctx.request_update();
+            }
+            Event::KeyDown(key_event) => {
+                match &key_event.key {
+                    KbKey::ArrowUp => self.move_keyboard_cursor(ctx, &geometry, 0, -1),
+                    KbKey::ArrowDown => self.move_keyboard_cursor(ctx, &geometry, 0, 1),
+                    KbKey::ArrowLeft => self.move_keyboard_cursor(ctx, &geometry, -1, 0),
+                    KbKey::ArrowRight => self.move_keyboard_cursor(ctx, &geometry, 1, 0),
+                    KbKey::Enter => self.toggle_keyboard_drag(ctx, data, &geometry),
+                    KbKey::Character(character) if character == " " => {
+                        self.toggle_keyboard_drag(ctx, data, &geometry)
                     }
-
-                    let file = if data.reversed { 7 - col } else { col } as u8;
-                    let rank = if data.reversed { row } else { 7 - row } as u8;
-
-                    // The moved piece must be around the mouse cursor.
-                    let x = x - cells_size * 0.5;
-                    let y = y - cells_size * 0.5;
-
-                    self.dnd_state.moved_piece_location = Some((x, y));
-                    self.dnd_state.end_cell = Some(CellCoordinates { file, rank });
-                    ctx.request_update();
+                    KbKey::Escape => {
+                        self.dnd_state.cancel();
+                        ctx.request_paint();
+                    }
+                    KbKey::Character(character) if character == "d" => {
+                        ctx.submit_command(DESCRIBE_POSITION)
+                    }
+                    KbKey::Character(character) if character == "p" => {
+                        if !key_event.repeat && !data.shows_all_pieces() {
+                            ctx.submit_command(PEEK.with(true));
+                        }
+                    }
+                    _ => return,
                 }
+                ctx.set_handled();
             }
+            Event::KeyUp(key_event) => match &key_event.key {
+                KbKey::Character(character) if character == "p" && data.peeking => {
+                    ctx.submit_command(PEEK.with(false));
+                    ctx.set_handled();
+                }
+                _ => {}
+            },
             _ => {}
         }
     }
 
     fn lifecycle(
         &mut self,
-        _ctx: &mut LifeCycleCtx,
+        ctx: &mut LifeCycleCtx,
         event: &LifeCycle,
-        _data: &ChessBoardData,
-        _env: &Env,
+        data: &ChessBoardData,
+        env: &Env,
     ) {
         match event {
-            LifeCycle::HotChanged(false) => {
-                if self.dnd_state.active {
+            LifeCycle::WidgetAdded => {
+                ctx.register_for_focus();
+                self.update_piece_set(env);
+                self.file_letters = file_letters(env);
+            }
+            LifeCycle::HotChanged(false)
+                if self.dnd_state.active && !self.dnd_state.by_keyboard =>
+            {
+                self.dnd_state.cancel();
+            }
+            LifeCycle::FocusChanged(false) => {
+                if self.dnd_state.by_keyboard {
                     self.dnd_state.cancel();
                 }
+                // The release of the peek key would not be received anymore.
+                if data.peeking {
+                    ctx.submit_command(PEEK.with(false));
+                }
+                ctx.request_paint();
+            }
+            LifeCycle::FocusChanged(true) => {
+                ctx.request_paint();
             }
             _ => {}
         }
@@ -447,8 +610,15 @@ impl Widget<ChessBoardData> for ChessBoard {
         ctx: &mut UpdateCtx,
         _old_data: &ChessBoardData,
         _data: &ChessBoardData,
-        _env: &Env,
+        env: &Env,
     ) {
+        if ctx.env_key_changed(&PIECE_SET) {
+            self.update_piece_set(env);
+        }
+        // The letters follow the locale, which changes with the environment.
+        if ctx.env_changed() {
+            self.file_letters = file_letters(env);
+        }
         ctx.request_paint();
     }
 
@@ -472,11 +642,15 @@ impl Widget<ChessBoardData> for ChessBoard {
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &ChessBoardData, env: &Env) {
-        self.draw_background(ctx);
-        self.draw_cells(ctx, data);
-        self.draw_coordinates(ctx, data, env);
-        self.draw_pieces(ctx, data);
-        self.draw_moved_piece(ctx);
-        self.draw_player_turn(ctx, data);
+        let geometry = board_geometry(ctx.size(), data, env);
+        self.draw_background(ctx, &geometry, env);
+        self.draw_cells(ctx, &geometry, env);
+        self.draw_hill(ctx, data, &geometry, env);
+        self.draw_coordinates(ctx, &geometry, env);
+        self.draw_pieces(ctx, data, &geometry);
+        self.draw_keyboard_cursor(ctx, &geometry, env);
+        self.draw_moved_piece(ctx, data, &geometry);
+        self.draw_player_turn(ctx, data, &geometry);
+        self.draw_checks_given(ctx, data, &geometry, env);
     }
 }
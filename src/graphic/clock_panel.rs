@@ -0,0 +1,148 @@
+use druid::widget::{Button, Controller, CrossAxisAlignment, Flex, Label, RadioGroup};
+use druid::{
+    commands, Env, Event, EventCtx, FileDialogOptions, FileSpec, LifeCycle, LifeCycleCtx,
+    LocalizedString, TimerToken, Widget, WidgetExt,
+};
+
+use pleco::Player;
+
+use std::time::{Duration, Instant};
+
+use super::app_data::AppData;
+use super::localization::{clock_time_text, flag_fall_text, localize};
+use crate::chess::clock::{ChessClock, TimeBonus, TimeControl};
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Key of the name of each time control offered, in the order of `preset_control`.
+const PRESET_NAMES: [&str; 6] = [
+    "clock-preset-none",
+    "clock-preset-blitz",
+    "clock-preset-blitz-increment",
+    "clock-preset-rapid-increment",
+    "clock-preset-delay",
+    "clock-preset-classical",
+];
+
+/// The time control of the preset at this index, none meaning that the game is not timed.
+fn preset_control(index: usize) -> Option<TimeControl> {
+    let minutes = |count: u64| Duration::from_secs(count * 60);
+    let seconds = Duration::from_secs;
+    match index {
+        1 => Some(TimeControl::sudden_death(minutes(5))),
+        2 => Some(TimeControl::fischer(minutes(3), seconds(2))),
+        3 => Some(TimeControl::fischer(minutes(15), seconds(10))),
+        4 => Some(TimeControl::bronstein(minutes(5), seconds(3))),
+        5 => Some(TimeControl::multi_stage(
+            vec![(40, minutes(90))],
+            minutes(30),
+            TimeBonus::Increment(seconds(30)),
+        )),
+        _ => None,
+    }
+}
+
+/// The clocks of both players, the choice of the time control, and the export of the game.
+pub fn clock_panel_builder() -> impl Widget<AppData> {
+    let presets = RadioGroup::new(
+        PRESET_NAMES
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (LocalizedString::new(name), index)),
+    )
+    .lens(AppData::clock_preset);
+    let set_button = Button::new(LocalizedString::new("clock-set")).on_click(
+        |_ctx, data: &mut AppData, _env| {
+            let turn = data.board.board.turn();
+            data.clock =
+                preset_control(data.clock_preset).map(|control| ChessClock::new(control, turn));
+        },
+    );
+    let start_button = Button::dynamic(|data: &AppData, env| match &data.clock {
+        Some(clock) if clock.is_running() => localize(env, "clock-pause"),
+        _ => localize(env, "clock-start"),
+    })
+    .on_click(|_ctx, data: &mut AppData, _env| {
+        if let Some(clock) = &mut data.clock {
+            if clock.is_running() {
+                clock.pause(Instant::now());
+            } else if !data.board.board.is_game_over() {
+                clock.start(Instant::now());
+            }
+        }
+    });
+    let export_button = Button::new(LocalizedString::new("clock-export-pgn")).on_click(
+        |ctx, _data: &mut AppData, _env| {
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .default_name("game.pgn");
+            ctx.submit_command(commands::SHOW_SAVE_PANEL.with(options));
+        },
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("clock-panel-title")))
+        .with_child(Label::dynamic(|data: &AppData, env| {
+            clock_time_text(env, data.clock.as_ref(), Player::Black)
+        }))
+        .with_child(Label::dynamic(|data: &AppData, env| {
+            clock_time_text(env, data.clock.as_ref(), Player::White)
+        }))
+        .with_spacer(4.0)
+        .with_child(
+            Flex::row()
+                .with_child(start_button)
+                .with_spacer(4.0)
+                .with_child(export_button),
+        )
+        .with_spacer(4.0)
+        .with_child(presets)
+        .with_child(set_button)
+        .controller(ClockTicker {
+            timer: TimerToken::INVALID,
+        })
+}
+
+/// Updates the running clock every tenth of a second, and ends the game on flag fall.
+struct ClockTicker {
+    timer: TimerToken,
+}
+
+impl<W: Widget<AppData>> Controller<AppData, W> for ClockTicker {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::Timer(token) = event {
+            if *token == self.timer {
+                if let Some(clock) = &mut data.clock {
+                    if let Some(player) = clock.tick(Instant::now()) {
+                        data.announcement = flag_fall_text(env, player);
+                    }
+                }
+                self.timer = ctx.request_timer(TICK_INTERVAL);
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.timer = ctx.request_timer(TICK_INTERVAL);
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+}
@@ -0,0 +1,77 @@
+use druid::{FileInfo, Selector};
+use pleco::BitMove;
+
+use std::sync::Arc;
+
+use super::book_panel::LoadedBook;
+use super::review_panel::{BlundersSummary, ReviewProgress, ReviewSummary, VerificationSummary};
+use crate::chess::engine::AnalysisEvent;
+use crate::chess::exercise::JudgedMove;
+use crate::chess::syzygy::Tablebases;
+
+/// A move the user wants to play on the main board, whether dragged or typed.
+/// It is checked and applied by the application delegate.
+pub const PLAY_MOVE: Selector<BitMove> = Selector::new("chess-exercises-organizer.play-move");
+
+/// A move of the computer, with the position it was searched for, or the failure of the engine.
+pub const COMPUTER_MOVE: Selector<(String, Result<BitMove, String>)> =
+    Selector::new("chess-exercises-organizer.computer-move");
+
+/// Asks for the current position to be announced.
+pub const DESCRIBE_POSITION: Selector = Selector::new("chess-exercises-organizer.describe-position");
+
+/// Reveals the hidden pieces while the peek key is held, or hides them again.
+pub const PEEK: Selector<bool> = Selector::new("chess-exercises-organizer.peek");
+
+/// The judgement of the engine on a move played during a rush, sent from the thread
+/// judging it.
+pub const RUSH_MOVE_JUDGED: Selector<JudgedMove> =
+    Selector::new("chess-exercises-organizer.rush-move-judged");
+
+/// Lines found by the engine, or its failure, sent from the thread of the analysis.
+pub const ANALYSIS_EVENT: Selector<AnalysisEvent> =
+    Selector::new("chess-exercises-organizer.analysis-event");
+
+/// The opening book read again after its path changed, none when no book is chosen.
+pub const BOOK_LOADED: Selector<Result<Option<LoadedBook>, String>> =
+    Selector::new("chess-exercises-organizer.book-loaded");
+
+/// The tablebases listed again after their directory changed, none when no directory is chosen.
+pub const TABLEBASES_LOADED: Selector<Result<Option<Arc<Tablebases>>, String>> =
+    Selector::new("chess-exercises-organizer.tablebases-loaded");
+
+/// The file chosen for the annotated game, which starts its review.
+pub const SAVE_REVIEW_AS: Selector<FileInfo> =
+    Selector::new("chess-exercises-organizer.save-review-as");
+
+/// The PGN file of games whose blunders become exercises.
+pub const OPEN_GAMES_FOR_BLUNDERS: Selector<FileInfo> =
+    Selector::new("chess-exercises-organizer.open-games-for-blunders");
+
+/// Positions or games reviewed so far.
+pub const REVIEW_PROGRESS: Selector<ReviewProgress> =
+    Selector::new("chess-exercises-organizer.review-progress");
+
+/// The review is over, the annotated game being written or the engine having failed.
+pub const REVIEW_FINISHED: Selector<Result<ReviewSummary, String>> =
+    Selector::new("chess-exercises-organizer.review-finished");
+
+/// The collection of the blunders is written, or the review of the games failed.
+pub const BLUNDERS_FOUND: Selector<Result<BlundersSummary, String>> =
+    Selector::new("chess-exercises-organizer.blunders-found");
+
+/// The PGN file of the repertoire to drill.
+pub const OPEN_REPERTOIRE: Selector<FileInfo> =
+    Selector::new("chess-exercises-organizer.open-repertoire");
+
+/// The PGN file of endgame exercises to play against the computer.
+pub const OPEN_ENDGAMES: Selector<FileInfo> =
+    Selector::new("chess-exercises-organizer.open-endgames");
+
+/// The PGN file of exercises whose solutions are verified by the engine.
+pub const OPEN_EXERCISES_TO_VERIFY: Selector<FileInfo> =
+    Selector::new("chess-exercises-organizer.open-exercises-to-verify");
+
+/// The findings of the verification are written, or the engine failed.
+pub const VERIFICATION_FINISHED: Selector<Result<VerificationSummary, String>> =
+    Selector::new("chess-exercises-organizer.verification-finished");
@@ -0,0 +1,184 @@
+use druid::widget::{Controller, CrossAxisAlignment, Flex, Label, RadioGroup};
+use druid::{
+    Data, Env, ExtEventSink, LifeCycle, LifeCycleCtx, LocalizedString, Target, UpdateCtx, Widget,
+    WidgetExt,
+};
+
+use log::error;
+
+use pleco::{BitMove, Player};
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::app_data::AppData;
+use super::book_panel::LoadedBook;
+use super::commands::COMPUTER_MOVE;
+use crate::chess::engine::{analyse_position, EngineKind, SearchLimit};
+use crate::chess::polyglot::pick_book_move;
+use crate::chess::syzygy::{TablebaseError, Tablebases};
+use crate::chess::BoardLogic;
+
+/// Search of the moves of the computer, out of the tablebases and of the book.
+const COMPUTER_LIMIT: SearchLimit = SearchLimit {
+    depth: 12,
+    time: Duration::from_secs(1),
+};
+
+/// Side of the main board played by the computer.
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub enum ComputerSide {
+    Nobody,
+    White,
+    Black,
+}
+
+impl ComputerSide {
+    pub fn plays(self, player: Player) -> bool {
+        match self {
+            ComputerSide::Nobody => false,
+            ComputerSide::White => player == Player::White,
+            ComputerSide::Black => player == Player::Black,
+        }
+    }
+}
+
+/// Choice of the side played by the computer, with the moves of the tablebases or of the book
+/// first, and then the engine.
+pub fn computer_panel_builder() -> impl Widget<AppData> {
+    let sides = vec![
+        (
+            LocalizedString::new("computer-nobody"),
+            ComputerSide::Nobody,
+        ),
+        (LocalizedString::new("computer-white"), ComputerSide::White),
+        (LocalizedString::new("computer-black"), ComputerSide::Black),
+    ];
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("computer-panel-title")))
+        .with_child(RadioGroup::new(sides).lens(AppData::computer))
+        .controller(ComputerPlayer { searched_fen: None })
+}
+
+/// Whether the computer should play the next move of the board : not during a rush
+/// or a repertoire drill, nor once the game is over, nor in a variant the engines do not
+/// play. During an endgame drill, it plays against the solver whatever side is chosen,
+/// until the attempt is over.
+pub fn computer_to_move(data: &AppData) -> bool {
+    let logic = &data.board.board;
+    let flagged = data
+        .clock
+        .as_ref()
+        .map(|clock| clock.flagged().is_some())
+        .unwrap_or(false);
+    let plays = match &data.endgame.drill {
+        Some(drill) => drill.computer_to_move(logic),
+        None => data.computer.plays(logic.turn()),
+    };
+    plays
+        && logic.variant.is_known_to_engines()
+        && !data.rush.is_running()
+        && !data.repertoire.is_drilling()
+        && !logic.is_game_over()
+        && !flagged
+}
+
+/// Searches the move of the computer in another thread whenever it is its turn.
+/// The move reaches the delegate as a command, with the position it was searched for.
+struct ComputerPlayer {
+    searched_fen: Option<String>,
+}
+
+impl ComputerPlayer {
+    fn start_if_needed(&mut self, data: &AppData, sink: ExtEventSink) {
+        if !computer_to_move(data) {
+            self.searched_fen = None;
+            return;
+        }
+        let fen = data.board.board.fen();
+        if self.searched_fen.as_ref() == Some(&fen) {
+            return;
+        }
+        self.searched_fen = Some(fen.clone());
+        let engine = data.settings.engine();
+        let tablebases = data.tablebases.loaded.clone();
+        let book = data.book.loaded.clone();
+        thread::spawn(move || {
+            let outcome = computer_move(&engine, tablebases.as_ref(), book.as_ref(), &fen);
+            // The application may be closing, in which case the move is lost.
+            let _ = sink.submit_command(COMPUTER_MOVE, (fen, outcome), Target::Auto);
+        });
+    }
+}
+
+impl<W: Widget<AppData>> Controller<AppData, W> for ComputerPlayer {
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.start_if_needed(data, ctx.get_external_handle());
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        let changed = !old_data.board.same(&data.board)
+            || old_data.computer != data.computer
+            || !old_data.rush.same(&data.rush)
+            || old_data.repertoire.is_drilling() != data.repertoire.is_drilling()
+            || !old_data.endgame.drill.same(&data.endgame.drill)
+            || !old_data.clock.same(&data.clock);
+        if changed {
+            self.start_if_needed(data, ctx.get_external_handle());
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
+
+/// The perfect move of the tablebases when they have the position, else a move of the book
+/// picked by its weight, or else the best move of the engine.
+fn computer_move(
+    engine: &EngineKind,
+    tablebases: Option<&Arc<Tablebases>>,
+    book: Option<&LoadedBook>,
+    fen: &str,
+) -> Result<BitMove, String> {
+    let board = BoardLogic::from_fen(fen).ok_or_else(|| format!("Invalid position {}", fen))?;
+    // The castling rights of Chess960 are not on the board of pleco, which the tables read.
+    let tablebase_board = board.pleco_board().filter(|_| !board.has_castling_rights());
+    if let (Some(tablebases), Some(tablebase_board)) = (tablebases, tablebase_board) {
+        match tablebases.best_move(tablebase_board) {
+            Ok(Some(best_move)) => return Ok(best_move),
+            Ok(None) | Err(TablebaseError::NotCovered) => {}
+            Err(err) => error!("The engine plays instead of the tablebases : {}", err),
+        }
+    }
+    if let Some(book) = book {
+        let random = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| u64::from(time.subsec_nanos()))
+            .unwrap_or_default();
+        if let Some(book_move) = pick_book_move(&book.moves(&board), random) {
+            return Ok(book_move);
+        }
+    }
+    analyse_position(engine, fen, 1, COMPUTER_LIMIT)?
+        .first()
+        .and_then(|line| line.moves.first().cloned())
+        .ok_or_else(|| String::from("The engine found no move."))
+}
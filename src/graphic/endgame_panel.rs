@@ -0,0 +1,210 @@
+use druid::widget::{Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking};
+use druid::{
+    commands, Data, Env, Event, EventCtx, FileDialogOptions, FileSpec, Lens, LocalizedString,
+    Widget, WidgetExt,
+};
+use log::error;
+
+use pleco::Player;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::app_data::AppData;
+use super::commands::OPEN_ENDGAMES;
+use super::localization::{
+    endgame_exercise_text, endgame_outcome_text, endgame_status_text, localize, localize_with_args,
+    Argument,
+};
+use crate::chess::endgame::{endgame_exercises_from_games, EndgameDrill, EndgameExercise};
+use crate::chess::pgn::parse_pgn;
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct EndgameData {
+    /// The exercises read from the chosen file, with the name of the file.
+    pub exercises: Option<Arc<Vec<EndgameExercise>>>,
+    pub file_name: String,
+    /// The drill going on, if any.
+    pub drill: Option<EndgameDrill>,
+}
+
+impl EndgameData {
+    pub fn new() -> Self {
+        Self {
+            exercises: None,
+            file_name: String::new(),
+            drill: None,
+        }
+    }
+
+    /// Whether the moves played on the board are those of an endgame drill.
+    pub fn is_drilling(&self) -> bool {
+        self.drill.is_some()
+    }
+}
+
+/// Choice of the file of endgame exercises, and attempts at them against the computer.
+pub fn endgame_panel_builder() -> impl Widget<AppData> {
+    let open_button = Button::new(LocalizedString::new("endgame-open")).on_click(
+        |ctx, data: &mut AppData, _env| {
+            if data.endgame.is_drilling() {
+                return;
+            }
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .accept_command(OPEN_ENDGAMES);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options));
+        },
+    );
+    let file_name = Label::dynamic(|data: &EndgameData, env| {
+        if data.exercises.is_some() {
+            data.file_name.clone()
+        } else {
+            localize(env, "endgame-none")
+        }
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::endgame);
+
+    let start_button = Button::dynamic(|data: &AppData, env| {
+        if data.endgame.is_drilling() {
+            localize(env, "endgame-stop")
+        } else {
+            localize(env, "endgame-start")
+        }
+    })
+    .on_click(|_ctx, data: &mut AppData, env| {
+        if data.endgame.is_drilling() {
+            data.endgame.drill = None;
+            return;
+        }
+        if data.rush.is_running() || data.repertoire.is_drilling() {
+            return;
+        }
+        if let Some(exercises) = &data.endgame.exercises {
+            data.endgame.drill = Some(EndgameDrill::new(exercises.clone()));
+            start_attempt(data, env);
+        }
+    });
+    let retry_button = Button::new(LocalizedString::new("endgame-retry")).on_click(
+        |_ctx, data: &mut AppData, env| {
+            if let Some(drill) = &mut data.endgame.drill {
+                drill.retry();
+                start_attempt(data, env);
+            }
+        },
+    );
+    let next_button = Button::new(LocalizedString::new("endgame-next")).on_click(
+        |_ctx, data: &mut AppData, env| {
+            if let Some(drill) = &mut data.endgame.drill {
+                drill.next_exercise();
+                start_attempt(data, env);
+            }
+        },
+    );
+    let status = Label::dynamic(|data: &AppData, env| match &data.endgame.drill {
+        Some(drill) => endgame_status_text(env, drill, &data.board.board),
+        None => String::new(),
+    })
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("endgame-panel-title")))
+        .with_child(open_button)
+        .with_child(file_name)
+        .with_child(start_button)
+        .with_child(
+            Flex::row()
+                .with_child(retry_button)
+                .with_spacer(4.0)
+                .with_child(next_button),
+        )
+        .with_child(status)
+        .controller(EndgameLoader)
+}
+
+/// Reads the endgame exercises once their file is chosen.
+struct EndgameLoader;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for EndgameLoader {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::Command(cmd) = event {
+            if let Some(file_info) = cmd.get(OPEN_ENDGAMES) {
+                let path = file_info.path();
+                match load_endgames(path) {
+                    Ok(exercises) => {
+                        data.endgame.exercises = Some(Arc::new(exercises));
+                        data.endgame.file_name = path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                    }
+                    Err(message) => {
+                        error!("{}", message);
+                        data.announcement = localize_with_args(
+                            env,
+                            "endgame-load-failed",
+                            vec![("error", Argument::Text(message))],
+                        );
+                    }
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+/// The endgame exercises of the file, skipping the games which are not valid exercises.
+fn load_endgames(path: &Path) -> Result<Vec<EndgameExercise>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {} : {}", path.display(), err))?;
+    let games = parse_pgn(&content)
+        .map_err(|err| format!("Invalid PGN file {} : {}", path.display(), err))?;
+    let (exercises, errors) = endgame_exercises_from_games(games);
+    for (index, err) in errors {
+        error!("Skipping game {} of {:?} : {}", index + 1, path, err);
+    }
+    if exercises.is_empty() {
+        return Err(format!("No endgame exercise in {}", path.display()));
+    }
+    Ok(exercises)
+}
+
+/// Sets the board in the start position of the current exercise, the solver at the bottom.
+fn start_attempt(data: &mut AppData, env: &Env) {
+    let drill = match &data.endgame.drill {
+        Some(drill) => drill,
+        None => return,
+    };
+    let exercise = drill.exercise();
+    data.board.board = exercise.start_position();
+    data.board.reversed = exercise.solver() == Player::Black;
+    data.announcement = endgame_exercise_text(env, drill);
+}
+
+/// Judges the attempt after a move of the solver or of the computer, and tells its outcome
+/// once it is over.
+pub fn judge_endgame_move(data: &mut AppData, env: &Env) {
+    let drill = match &mut data.endgame.drill {
+        Some(drill) => drill,
+        None => return,
+    };
+    if drill.is_attempt_over() {
+        return;
+    }
+    if let Some(outcome) = drill.judge(&data.board.board) {
+        let outcome_text = endgame_outcome_text(env, outcome);
+        data.announcement = format!("{} {}", data.announcement, outcome_text);
+    }
+}
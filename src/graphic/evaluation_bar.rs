@@ -0,0 +1,169 @@
+use druid::text::{ArcStr, FontDescriptor, TextLayout};
+use druid::widget::prelude::*;
+use druid::{Color, FontFamily, FontWeight, Point, Rect};
+
+use super::analysis_panel::analysis_wanted;
+use super::app_data::AppData;
+use crate::chess::engine::Score;
+
+const BAR_WIDTH: f64 = 24.0;
+const WHITE_SIDE_COLOR: Color = Color::rgb8(238, 238, 238);
+const BLACK_SIDE_COLOR: Color = Color::rgb8(64, 64, 64);
+/// Part of the remaining distance to the new score covered in one second.
+const TRANSITION_SPEED: f64 = 6.0;
+/// Below this distance, the bar jumps to the new score.
+const TRANSITION_END: f64 = 0.001;
+
+/// Vertical bar beside the board, filled by White from their side of the board
+/// as much as the engine thinks they are winning, and entirely when they give mate.
+pub struct EvaluationBar {
+    /// Share of White drawn, moving towards the one of the score.
+    shown_share: f64,
+    target_share: f64,
+    /// Score of the position, or of the previous one while the engine starts.
+    score: Option<Score>,
+}
+
+impl EvaluationBar {
+    pub fn new() -> Self {
+        Self {
+            shown_share: 0.5,
+            target_share: 0.5,
+            score: None,
+        }
+    }
+
+    fn update_score(&mut self, ctx: &mut UpdateCtx, data: &AppData) {
+        if !analysis_wanted(data) {
+            self.score = None;
+            self.target_share = 0.5;
+        } else {
+            let fen = data.board.board.fen();
+            let best_line = data
+                .analysis
+                .update_for(&fen)
+                .and_then(|update| update.lines.first());
+            if let Some(line) = best_line {
+                self.score = Some(line.score);
+                self.target_share = white_share(line.score);
+            }
+        }
+        if (self.target_share - self.shown_share).abs() > TRANSITION_END {
+            ctx.request_anim_frame();
+        }
+        ctx.request_paint();
+    }
+}
+
+/// Win chances of White as the share of the bar, following a logistic curve of the score
+/// as lichess does : a few pawns already fill most of the bar.
+fn white_share(score: Score) -> f64 {
+    match score {
+        Score::Centipawns(centipawns) => 1.0 / (1.0 + (-0.004 * f64::from(centipawns)).exp()),
+        Score::Mate(moves) if moves > 0 => 1.0,
+        Score::Mate(_) => 0.0,
+    }
+}
+
+/// The score written at the end of the winning side, without its sign : "1.3" or "#4".
+fn score_label(score: Score) -> String {
+    match score {
+        Score::Centipawns(centipawns) => format!("{:.1}", f64::from(centipawns.abs()) / 100.0),
+        Score::Mate(moves) => format!("#{}", moves.abs()),
+    }
+}
+
+impl Widget<AppData> for EvaluationBar {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut AppData, _env: &Env) {
+        if let Event::AnimFrame(interval) = event {
+            let seconds = *interval as f64 / 1_000_000_000.0;
+            let distance = self.target_share - self.shown_share;
+            if distance.abs() <= TRANSITION_END {
+                self.shown_share = self.target_share;
+            } else {
+                self.shown_share += distance * (seconds * TRANSITION_SPEED).min(1.0);
+                ctx.request_anim_frame();
+            }
+            ctx.request_paint();
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        _ctx: &mut LifeCycleCtx,
+        _event: &LifeCycle,
+        _data: &AppData,
+        _env: &Env,
+    ) {
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &AppData, data: &AppData, _env: &Env) {
+        let changed = !old_data.analysis.same(&data.analysis)
+            || !old_data.board.same(&data.board)
+            || old_data.rush.is_running() != data.rush.is_running();
+        if changed {
+            self.update_score(ctx, data);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &AppData,
+        _env: &Env,
+    ) -> Size {
+        bc.constrain(Size::new(BAR_WIDTH, bc.max().height))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &AppData, env: &Env) {
+        if !analysis_wanted(data) {
+            return;
+        }
+        let size = ctx.size();
+        let white_height = size.height * self.shown_share;
+        // White fills the bar from the side of the board where their pieces start.
+        let white_at_bottom = !data.board.reversed;
+        let (white_rect, black_rect) = if white_at_bottom {
+            (
+                Rect::new(0.0, size.height - white_height, size.width, size.height),
+                Rect::new(0.0, 0.0, size.width, size.height - white_height),
+            )
+        } else {
+            (
+                Rect::new(0.0, 0.0, size.width, white_height),
+                Rect::new(0.0, white_height, size.width, size.height),
+            )
+        };
+        ctx.fill(white_rect, &WHITE_SIDE_COLOR);
+        ctx.fill(black_rect, &BLACK_SIDE_COLOR);
+
+        let score = match self.score {
+            Some(score) => score,
+            None => return,
+        };
+        let white_winning = score.as_centipawns() >= 0;
+        let text_color = if white_winning {
+            BLACK_SIDE_COLOR
+        } else {
+            WHITE_SIDE_COLOR
+        };
+        let mut label = TextLayout::<ArcStr>::from_text(score_label(score));
+        label.set_font(
+            FontDescriptor::new(FontFamily::SANS_SERIF)
+                .with_size(BAR_WIDTH * 0.4)
+                .with_weight(FontWeight::BOLD),
+        );
+        label.set_text_color(text_color);
+        label.rebuild_if_needed(ctx.text(), env);
+        let label_size = label.size();
+        let x = (size.width - label_size.width) / 2.0;
+        // The label is at the end of the winning side.
+        let y = if white_winning == white_at_bottom {
+            size.height - label_size.height - 2.0
+        } else {
+            2.0
+        };
+        label.draw(ctx, Point::new(x, y));
+    }
+}
@@ -0,0 +1,779 @@
+use druid::{Env, LocalizedString};
+
+use pleco::core::{sq::SQ, PieceType, Player};
+use pleco::BitMove;
+
+use super::book_panel::BookData;
+use super::review_panel::{BlundersSummary, ReviewProgress, ReviewSummary, VerificationSummary};
+use super::tablebase_panel::TablebaseData;
+use crate::chess::clock::{format_clock, ChessClock};
+use crate::chess::description::{
+    MoveAnnouncement, MoveKind, PiecesGroup, PositionDescription, TurnState,
+};
+use crate::chess::endgame::{DrawKind, EndgameDrill, EndgameGoal, EndgameOutcome};
+use crate::chess::engine::{format_line, AnalysisUpdate};
+use crate::chess::repertoire::RepertoireDrill;
+use crate::chess::rush::{PuzzleRush, ALLOWED_STRIKES};
+use crate::chess::san::{move_to_san, MoveParseError, PieceLetters};
+use crate::chess::syzygy::{TablebaseError, Wdl};
+use crate::chess::variant::VariantEnd;
+use crate::chess::BoardLogic;
+use crate::leaderboard::Leaderboard;
+
+/// Value of a placeholder of a translated message.
+#[derive(Debug, Clone)]
+pub enum Argument {
+    Text(String),
+    /// Numbers select the plural forms of the message.
+    Number(usize),
+}
+
+/// The message with the given key, in the language of the user.
+/// Messages are read from `resources/i18n/{locale}/builtin.ftl`.
+pub fn localize(env: &Env, key: &'static str) -> String {
+    localize_with_args(env, key, Vec::new())
+}
+
+pub fn localize_with_args(
+    env: &Env,
+    key: &'static str,
+    args: Vec<(&'static str, Argument)>,
+) -> String {
+    let mut message = LocalizedString::<()>::new(key);
+    for (name, value) in args {
+        message = message.with_arg(name, move |_data, _env| match &value {
+            Argument::Text(text) => text.clone().into(),
+            Argument::Number(number) => (*number).into(),
+        });
+    }
+    message.resolve(&(), env);
+    message.localized_str().to_string()
+}
+
+fn text(value: impl Into<String>) -> Argument {
+    Argument::Text(value.into())
+}
+
+/// Letters of the pieces in the algebraic notation of the user language,
+/// English ones if the translation does not give five valid letters.
+pub fn piece_letters(env: &Env) -> PieceLetters {
+    PieceLetters::from_letters(&localize(env, "san-piece-letters")).unwrap_or(PieceLetters::ENGLISH)
+}
+
+/// Letters of the files, from a to h, as written around the board.
+pub fn file_letters(env: &Env) -> Vec<char> {
+    let letters: Vec<char> = localize(env, "board-file-letters").chars().collect();
+    if letters.len() == 8 {
+        letters
+    } else {
+        "ABCDEFGH".chars().collect()
+    }
+}
+
+pub fn move_parse_error_text(env: &Env, err: &MoveParseError, letters: &PieceLetters) -> String {
+    match err {
+        MoveParseError::Empty => localize(env, "move-error-empty"),
+        MoveParseError::InvalidSyntax(input) => localize_with_args(
+            env,
+            "move-error-invalid-syntax",
+            vec![("input", text(input.as_str()))],
+        ),
+        MoveParseError::Illegal(input) => localize_with_args(
+            env,
+            "move-error-illegal",
+            vec![("input", text(input.as_str()))],
+        ),
+        MoveParseError::Ambiguous(input, candidates) => localize_with_args(
+            env,
+            "move-error-ambiguous",
+            vec![
+                ("input", text(input.as_str())),
+                ("candidates", text(join_words(env, "list-or", candidates))),
+            ],
+        ),
+        MoveParseError::MissingPromotion(input) => localize_with_args(
+            env,
+            "move-error-missing-promotion",
+            vec![
+                ("input", text(input.as_str())),
+                (
+                    "example",
+                    text(format!("{}={}", input, letters.letter(PieceType::Q))),
+                ),
+            ],
+        ),
+    }
+}
+
+/// The description as several sentences : the pieces of White, of Black, then the turn.
+pub fn position_description_text(env: &Env, description: &PositionDescription) -> String {
+    let sides = [
+        (Player::White, &description.white_pieces),
+        (Player::Black, &description.black_pieces),
+    ];
+    let mut text_lines = Vec::new();
+    for (player, groups) in sides.iter() {
+        let groups: Vec<String> = groups
+            .iter()
+            .map(|group| pieces_group_text(env, group))
+            .collect();
+        text_lines.push(localize_with_args(
+            env,
+            "position-side",
+            vec![
+                ("side", text(side_name(env, *player))),
+                ("pieces", text(groups.join(", "))),
+            ],
+        ));
+    }
+
+    let (turn_key, side) = match description.turn_state {
+        TurnState::ToMove => ("position-to-move", description.turn),
+        TurnState::InCheck => ("position-in-check", description.turn),
+        TurnState::Checkmated => ("position-checkmated", description.turn),
+        TurnState::Stalemated => ("position-stalemated", description.turn),
+        TurnState::VariantEnded(win) => {
+            let key = match win.end {
+                VariantEnd::KingOnHill => "position-king-on-hill",
+                VariantEnd::ThirdCheck => "position-third-check",
+                VariantEnd::NoMoveLeft => "position-no-move-left",
+            };
+            (key, win.winner)
+        }
+    };
+    text_lines.push(localize_with_args(
+        env,
+        turn_key,
+        vec![("side", text(side_name(env, side)))],
+    ));
+    if let Some([white, black]) = description.checks {
+        text_lines.push(localize_with_args(
+            env,
+            "position-checks",
+            vec![
+                ("white", Argument::Number(usize::from(white))),
+                ("black", Argument::Number(usize::from(black))),
+            ],
+        ));
+    }
+    text_lines.join("\n")
+}
+
+fn pieces_group_text(env: &Env, group: &PiecesGroup) -> String {
+    let key = match group.piece_type {
+        PieceType::K => "pieces-kings",
+        PieceType::Q => "pieces-queens",
+        PieceType::R => "pieces-rooks",
+        PieceType::B => "pieces-bishops",
+        PieceType::N => "pieces-knights",
+        _ => "pieces-pawns",
+    };
+    let squares: Vec<String> = group
+        .squares
+        .iter()
+        .map(|square| square.to_string())
+        .collect();
+    localize_with_args(
+        env,
+        key,
+        vec![
+            ("count", Argument::Number(squares.len())),
+            ("squares", text(join_words(env, "list-and", &squares))),
+        ],
+    )
+}
+
+/// The announcement as a single sentence, such as "White knight from g1 to f3."
+pub fn move_announcement_text(env: &Env, announcement: &MoveAnnouncement) -> String {
+    let player = announcement.player;
+    let opponent = player.other_player();
+    let square = |square: SQ| text(square.to_string());
+    let mut sentence = match announcement.kind {
+        MoveKind::KingSideCastle => localize_with_args(
+            env,
+            "announce-king-side-castle",
+            vec![("side", text(side_name(env, player)))],
+        ),
+        MoveKind::QueenSideCastle => localize_with_args(
+            env,
+            "announce-queen-side-castle",
+            vec![("side", text(side_name(env, player)))],
+        ),
+        MoveKind::Quiet {
+            piece_type,
+            origin,
+            destination,
+        } => localize_with_args(
+            env,
+            "announce-quiet-move",
+            vec![
+                ("piece", text(piece_name(env, player, piece_type))),
+                ("origin", square(origin)),
+                ("destination", square(destination)),
+            ],
+        ),
+        MoveKind::Capture {
+            piece_type,
+            origin,
+            captured_piece_type,
+            captured_square,
+        } => localize_with_args(
+            env,
+            "announce-capture",
+            vec![
+                ("piece", text(piece_name(env, player, piece_type))),
+                ("origin", square(origin)),
+                (
+                    "captured",
+                    text(piece_name(env, opponent, captured_piece_type)),
+                ),
+                ("square", square(captured_square)),
+            ],
+        ),
+        MoveKind::EnPassant {
+            origin,
+            captured_square,
+            destination,
+        } => localize_with_args(
+            env,
+            "announce-en-passant",
+            vec![
+                ("piece", text(piece_name(env, player, PieceType::P))),
+                ("origin", square(origin)),
+                ("captured", text(piece_name(env, opponent, PieceType::P))),
+                ("square", square(captured_square)),
+                ("destination", square(destination)),
+            ],
+        ),
+    };
+
+    if let Some(promotion) = announcement.promotion {
+        sentence = localize_with_args(
+            env,
+            "announce-promotion",
+            vec![
+                ("move", text(sentence)),
+                ("piece", text(piece_type_name(env, promotion))),
+            ],
+        );
+    }
+    let (ending_key, winner) = match announcement.opponent_state {
+        TurnState::ToMove => ("announce-end", None),
+        TurnState::InCheck => ("announce-check", None),
+        TurnState::Checkmated => ("announce-checkmate", None),
+        TurnState::Stalemated => ("announce-stalemate", None),
+        TurnState::VariantEnded(win) => {
+            let key = match win.end {
+                VariantEnd::KingOnHill => "announce-king-on-hill",
+                VariantEnd::ThirdCheck => "announce-third-check",
+                VariantEnd::NoMoveLeft => "announce-no-move-left",
+            };
+            (key, Some(win.winner))
+        }
+    };
+    let mut args = vec![("move", text(sentence))];
+    if let Some(winner) = winner {
+        args.push(("side", text(side_name(env, winner))));
+    }
+    capitalize(&localize_with_args(env, ending_key, args))
+}
+
+/// Remaining time of the player, such as "White: 0:04:59", marked while their clock runs.
+pub fn clock_time_text(env: &Env, clock: Option<&ChessClock>, player: Player) -> String {
+    let (key, time) = match clock {
+        Some(clock) if clock.flagged() == Some(player) => {
+            ("clock-time-flagged", format_clock(clock.remaining(player)))
+        }
+        Some(clock) if clock.is_running() && clock.turn() == player => {
+            ("clock-time-running", format_clock(clock.remaining(player)))
+        }
+        Some(clock) => ("clock-time", format_clock(clock.remaining(player))),
+        None => ("clock-time", String::from("-")),
+    };
+    localize_with_args(
+        env,
+        key,
+        vec![("side", text(side_name(env, player))), ("time", text(time))],
+    )
+}
+
+pub fn flag_fall_text(env: &Env, player: Player) -> String {
+    localize_with_args(
+        env,
+        "clock-flag-fall",
+        vec![("side", text(side_name(env, player)))],
+    )
+}
+
+/// Why a move is not played once the time of the player ran out.
+pub fn move_refused_text(env: &Env, player: Player) -> String {
+    localize_with_args(
+        env,
+        "clock-move-refused",
+        vec![("side", text(side_name(env, player)))],
+    )
+}
+
+/// Countdown, progress and score of a rush, one per line.
+pub fn rush_status_text(env: &Env, session: &PuzzleRush) -> String {
+    let (number, total) = session.progress();
+    let mut text_lines = vec![
+        localize_with_args(
+            env,
+            "rush-time-left",
+            vec![("time", text(format_clock(session.remaining())))],
+        ),
+        localize_with_args(
+            env,
+            "rush-progress",
+            vec![
+                ("number", Argument::Number(number)),
+                ("total", Argument::Number(total)),
+            ],
+        ),
+        localize_with_args(
+            env,
+            "rush-score",
+            vec![
+                ("score", Argument::Number(session.score() as usize)),
+                ("strikes", Argument::Number(session.strikes() as usize)),
+                ("allowed", Argument::Number(ALLOWED_STRIKES as usize)),
+            ],
+        ),
+    ];
+    if session.peeks() > 0 {
+        text_lines.push(localize_with_args(
+            env,
+            "rush-peeks",
+            vec![("peeks", Argument::Number(session.peeks() as usize))],
+        ));
+    }
+    if session.is_finished() {
+        text_lines.push(localize(env, "rush-over"));
+    }
+    text_lines.join("\n")
+}
+
+/// Announces the exercise set up on the board, such as "Exercise 3 of 20 : White to play."
+pub fn rush_exercise_text(env: &Env, session: &PuzzleRush) -> String {
+    let (number, total) = session.progress();
+    localize_with_args(
+        env,
+        "rush-exercise",
+        vec![
+            ("number", Argument::Number(number)),
+            ("total", Argument::Number(total)),
+            (
+                "side",
+                text(side_name(env, session.current_exercise().solver())),
+            ),
+        ],
+    )
+}
+
+/// The best scores of the collection, one per line with their date.
+pub fn rush_scores_text(env: &Env, leaderboard: &Leaderboard, collection: &str) -> String {
+    let scores = leaderboard.best_scores(collection);
+    if scores.is_empty() {
+        return localize(env, "rush-no-scores");
+    }
+    let mut text_lines = vec![localize(env, "rush-best-scores")];
+    for (index, score) in scores.iter().enumerate() {
+        text_lines.push(localize_with_args(
+            env,
+            "rush-score-line",
+            vec![
+                ("rank", Argument::Number(index + 1)),
+                ("score", Argument::Number(score.score as usize)),
+                ("date", text(score.date.as_str())),
+            ],
+        ));
+    }
+    text_lines.join("\n")
+}
+
+pub fn rush_end_text(env: &Env, score: u32, previous_best: Option<u32>) -> String {
+    match previous_best {
+        Some(best) if best >= score => localize_with_args(
+            env,
+            "rush-end",
+            vec![
+                ("score", Argument::Number(score as usize)),
+                ("best", Argument::Number(best as usize)),
+            ],
+        ),
+        _ => localize_with_args(
+            env,
+            "rush-end-new-best",
+            vec![("score", Argument::Number(score as usize))],
+        ),
+    }
+}
+
+/// Depth and nodes of the analysis, then its best lines with their score, one per line.
+pub fn analysis_text(
+    env: &Env,
+    update: Option<&AnalysisUpdate>,
+    error: Option<&str>,
+    board: &BoardLogic,
+    lines_count: usize,
+) -> String {
+    if let Some(error) = error {
+        return localize_with_args(env, "analysis-error", vec![("error", text(error))]);
+    }
+    let update = match update {
+        Some(update) if !update.lines.is_empty() => update,
+        _ => return localize(env, "analysis-waiting"),
+    };
+    let best = &update.lines[0];
+    let mut text_lines = vec![localize_with_args(
+        env,
+        "analysis-depth",
+        vec![
+            ("depth", Argument::Number(best.depth as usize)),
+            ("nodes", Argument::Number(best.nodes as usize)),
+        ],
+    )];
+    let letters = piece_letters(env);
+    for line in update.lines.iter().take(lines_count) {
+        text_lines.push(format!(
+            "{} {}",
+            line.score,
+            format_line(board, &line.moves, &letters)
+        ));
+    }
+    text_lines.join("\n")
+}
+
+pub fn review_progress_text(env: &Env, progress: ReviewProgress) -> String {
+    let (key, done, total) = match progress {
+        ReviewProgress::Positions(done, total) => ("review-progress", done, total),
+        ReviewProgress::Games(done, total) => ("review-games-progress", done, total),
+        ReviewProgress::Exercises(done, total) => ("review-exercises-progress", done, total),
+    };
+    localize_with_args(
+        env,
+        key,
+        vec![
+            ("done", Argument::Number(done)),
+            ("total", Argument::Number(total)),
+        ],
+    )
+}
+
+/// Counts of the bad moves found by the review, and the file of the annotated game.
+pub fn review_done_text(env: &Env, summary: &ReviewSummary) -> String {
+    localize_with_args(
+        env,
+        "review-done",
+        vec![
+            ("inaccuracies", Argument::Number(summary.inaccuracies)),
+            ("mistakes", Argument::Number(summary.mistakes)),
+            ("blunders", Argument::Number(summary.blunders)),
+            ("path", text(summary.path.as_str())),
+        ],
+    )
+}
+
+pub fn blunders_found_text(env: &Env, summary: &BlundersSummary) -> String {
+    localize_with_args(
+        env,
+        "review-blunders-done",
+        vec![
+            ("games", Argument::Number(summary.games)),
+            ("exercises", Argument::Number(summary.exercises)),
+            ("collection", text(summary.collection.as_str())),
+        ],
+    )
+}
+
+/// Counts of the exercises whose solution the engine does not agree with.
+pub fn verification_done_text(env: &Env, summary: &VerificationSummary) -> String {
+    localize_with_args(
+        env,
+        "review-verify-done",
+        vec![
+            ("exercises", Argument::Number(summary.exercises)),
+            ("not_best", Argument::Number(summary.not_best)),
+            ("multiple", Argument::Number(summary.multiple_solutions)),
+            ("path", text(summary.path.as_str())),
+        ],
+    )
+}
+
+/// The moves of the book for the position, the heaviest first, with their share of the weights.
+pub fn book_moves_text(env: &Env, book: &BookData, board: &BoardLogic) -> String {
+    if let Some(error) = &book.error {
+        return localize_with_args(env, "book-error", vec![("error", text(error.as_str()))]);
+    }
+    let moves = match &book.loaded {
+        Some(loaded) => loaded.moves(board),
+        None => return localize(env, "book-none"),
+    };
+    if moves.is_empty() {
+        return localize(env, "book-no-move");
+    }
+    let total: u32 = moves
+        .iter()
+        .map(|book_move| u32::from(book_move.weight))
+        .sum();
+    let letters = piece_letters(env);
+    let lines: Vec<String> = moves
+        .iter()
+        .map(|book_move| {
+            // Rounded to the nearest percent, none when the book gives no weights.
+            let share = (u32::from(book_move.weight) * 100 + total / 2)
+                .checked_div(total)
+                .unwrap_or(0);
+            localize_with_args(
+                env,
+                "book-move",
+                vec![
+                    (
+                        "move",
+                        text(move_to_san(board, book_move.chess_move, &letters)),
+                    ),
+                    ("share", Argument::Number(share as usize)),
+                ],
+            )
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The outcome of the position with perfect play according to the tablebases, with the
+/// number of plies before the next capture or pawn move when the DTZ table is there.
+pub fn tablebase_verdict_text(env: &Env, tablebases: &TablebaseData, board: &BoardLogic) -> String {
+    if let Some(error) = &tablebases.error {
+        return localize_with_args(
+            env,
+            "tablebase-error",
+            vec![("error", text(error.as_str()))],
+        );
+    }
+    let loaded = match &tablebases.loaded {
+        Some(loaded) => loaded,
+        None => return localize(env, "tablebase-none"),
+    };
+    // The castling rights of Chess960 are not on the board of pleco.
+    let board = match board.pleco_board() {
+        Some(pleco_board) if !board.has_castling_rights() => pleco_board,
+        _ => return localize(env, "tablebase-not-covered"),
+    };
+    let wdl = match loaded.probe_wdl(board) {
+        Ok(wdl) => wdl,
+        Err(TablebaseError::NotCovered) => return localize(env, "tablebase-not-covered"),
+        Err(TablebaseError::MissingTable(table)) => {
+            return localize_with_args(env, "tablebase-missing", vec![("table", text(table))])
+        }
+        Err(err) => {
+            return localize_with_args(
+                env,
+                "tablebase-error",
+                vec![("error", text(err.to_string()))],
+            )
+        }
+    };
+    let turn = board.turn();
+    let (key, winner) = match wdl {
+        Wdl::Win => ("tablebase-win", turn),
+        Wdl::CursedWin => ("tablebase-cursed-win", turn),
+        Wdl::Draw => return localize(env, "tablebase-draw"),
+        Wdl::BlessedLoss => ("tablebase-cursed-win", turn.other_player()),
+        Wdl::Loss => ("tablebase-win", turn.other_player()),
+    };
+    let verdict = localize_with_args(env, key, vec![("side", text(side_name(env, winner)))]);
+    match loaded.probe_dtz(board) {
+        Ok(dtz) => {
+            let plies = localize_with_args(
+                env,
+                "tablebase-dtz",
+                vec![("plies", Argument::Number(dtz.unsigned_abs() as usize))],
+            );
+            format!("{}\n{}", verdict, plies)
+        }
+        Err(_) => verdict,
+    }
+}
+
+/// Positions due for review in the drilled side of the repertoire, and answers of the drill.
+pub fn repertoire_status_text(env: &Env, drill: &RepertoireDrill, due: usize) -> String {
+    let total = drill.repertoire().positions_of(drill.side()).len();
+    let text_lines = [
+        localize_with_args(
+            env,
+            "repertoire-due",
+            vec![
+                ("due", Argument::Number(due)),
+                ("total", Argument::Number(total)),
+            ],
+        ),
+        localize_with_args(
+            env,
+            "repertoire-answers",
+            vec![
+                ("correct", Argument::Number(drill.correct() as usize)),
+                ("mistakes", Argument::Number(drill.mistakes() as usize)),
+            ],
+        ),
+    ];
+    text_lines.join("\n")
+}
+
+/// Tells the moves of the repertoire after a wrong one, such as "Expected : Nf3 or c4."
+pub fn repertoire_wrong_text(env: &Env, board: &BoardLogic, expected: &[BitMove]) -> String {
+    let letters = piece_letters(env);
+    let moves: Vec<String> = expected
+        .iter()
+        .map(|chess_move| move_to_san(board, *chess_move, &letters))
+        .collect();
+    localize_with_args(
+        env,
+        "repertoire-wrong",
+        vec![("moves", text(join_words(env, "list-or", &moves)))],
+    )
+}
+
+/// Announces the endgame exercise set up on the board, with its goal.
+pub fn endgame_exercise_text(env: &Env, drill: &EndgameDrill) -> String {
+    let (number, total) = drill.position();
+    let exercise = localize_with_args(
+        env,
+        "endgame-exercise",
+        vec![
+            ("number", Argument::Number(number)),
+            ("total", Argument::Number(total)),
+            ("title", text(drill.exercise().title())),
+        ],
+    );
+    format!("{} {}", exercise, endgame_goal_text(env, drill))
+}
+
+/// Such as "White to play and win within 20 moves."
+fn endgame_goal_text(env: &Env, drill: &EndgameDrill) -> String {
+    let exercise = drill.exercise();
+    let key = match exercise.goal {
+        EndgameGoal::Win => "endgame-goal-win",
+        EndgameGoal::Draw => "endgame-goal-draw",
+        EndgameGoal::MateIn(_) => "endgame-goal-mate",
+    };
+    localize_with_args(
+        env,
+        key,
+        vec![
+            ("side", text(side_name(env, exercise.solver()))),
+            ("moves", Argument::Number(exercise.move_limit as usize)),
+        ],
+    )
+}
+
+/// The exercise, its goal, the moves left or the outcome of the attempt, and the score,
+/// one per line.
+pub fn endgame_status_text(env: &Env, drill: &EndgameDrill, board: &BoardLogic) -> String {
+    let exercise = drill.exercise();
+    let mut text_lines = vec![endgame_exercise_text(env, drill)];
+    match drill.outcome() {
+        Some(outcome) => text_lines.push(endgame_outcome_text(env, outcome)),
+        None => {
+            let moves_left = exercise
+                .move_limit
+                .saturating_sub(exercise.solver_moves(board));
+            text_lines.push(localize_with_args(
+                env,
+                "endgame-moves-left",
+                vec![("moves", Argument::Number(moves_left as usize))],
+            ));
+        }
+    }
+    let (reached, attempts) = drill.score();
+    text_lines.push(localize_with_args(
+        env,
+        "endgame-score",
+        vec![
+            ("reached", Argument::Number(reached as usize)),
+            ("attempts", Argument::Number(attempts as usize)),
+        ],
+    ));
+    text_lines.join("\n")
+}
+
+/// Tells how an attempt ended, such as "Draw by repetition : the goal is missed."
+pub fn endgame_outcome_text(env: &Env, outcome: EndgameOutcome) -> String {
+    match outcome {
+        EndgameOutcome::Reached => localize(env, "endgame-reached"),
+        EndgameOutcome::Lost => localize(env, "endgame-lost"),
+        EndgameOutcome::OutOfMoves => localize(env, "endgame-out-of-moves"),
+        EndgameOutcome::Drawn(kind) => {
+            let reason = match kind {
+                DrawKind::Stalemate => localize(env, "endgame-draw-stalemate"),
+                DrawKind::InsufficientMaterial => localize(env, "endgame-draw-insufficient"),
+                DrawKind::FiftyMoves => localize(env, "endgame-draw-fifty-moves"),
+                DrawKind::Repetition => localize(env, "endgame-draw-repetition"),
+            };
+            localize_with_args(env, "endgame-drawn", vec![("reason", text(reason))])
+        }
+    }
+}
+
+fn side_name(env: &Env, player: Player) -> String {
+    match player {
+        Player::White => localize(env, "side-white"),
+        Player::Black => localize(env, "side-black"),
+    }
+}
+
+/// Name of a piece with its colour, such as "white knight".
+fn piece_name(env: &Env, player: Player, piece_type: PieceType) -> String {
+    let key = match (player, piece_type) {
+        (Player::White, PieceType::K) => "piece-white-king",
+        (Player::White, PieceType::Q) => "piece-white-queen",
+        (Player::White, PieceType::R) => "piece-white-rook",
+        (Player::White, PieceType::B) => "piece-white-bishop",
+        (Player::White, PieceType::N) => "piece-white-knight",
+        (Player::White, _) => "piece-white-pawn",
+        (Player::Black, PieceType::K) => "piece-black-king",
+        (Player::Black, PieceType::Q) => "piece-black-queen",
+        (Player::Black, PieceType::R) => "piece-black-rook",
+        (Player::Black, PieceType::B) => "piece-black-bishop",
+        (Player::Black, PieceType::N) => "piece-black-knight",
+        (Player::Black, _) => "piece-black-pawn",
+    };
+    localize(env, key)
+}
+
+/// Name of a piece type, such as "queen".
+fn piece_type_name(env: &Env, piece_type: PieceType) -> String {
+    let key = match piece_type {
+        PieceType::K => "piece-king",
+        PieceType::Q => "piece-queen",
+        PieceType::R => "piece-rook",
+        PieceType::B => "piece-bishop",
+        PieceType::N => "piece-knight",
+        _ => "piece-pawn",
+    };
+    localize(env, key)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// "a", "a and b", "a, b and c", the last link being given by the message `link_key`.
+fn join_words(env: &Env, link_key: &'static str, words: &[String]) -> String {
+    match words.len() {
+        0 => String::new(),
+        1 => words[0].clone(),
+        count => localize_with_args(
+            env,
+            link_key,
+            vec![
+                ("head", text(words[..count - 1].join(", "))),
+                ("last", text(words[count - 1].as_str())),
+            ],
+        ),
+    }
+}
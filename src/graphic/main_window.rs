@@ -1,18 +1,30 @@
-use druid::{AppLauncher, PlatformError, Widget, WindowDesc};
+use druid::{AppLauncher, ArcStr, LocalizedString, PlatformError, Widget, WidgetExt, WindowDesc};
 
+use super::app_data::AppData;
+use super::app_delegate::MainDelegate;
+use super::board_geometry::BORDER_WIDTH;
 use super::board_zone::game_zone_builder;
-use super::chess_board::ChessBoardData;
+use super::piece_set::PIECE_SET;
+use super::settings_window::SettingsSaver;
 
 pub fn launch() -> Result<(), PlatformError> {
     let main_window = WindowDesc::new(ui_builder)
-        .title("Chess exercises organizer")
+        .title(LocalizedString::new("window-title"))
         .window_size((600.0, 400.0));
-    let data = ChessBoardData::new();
+    let data = AppData::new();
     AppLauncher::with_window(main_window)
+        .delegate(MainDelegate)
         .use_simple_logger()
         .launch(data)
 }
 
-fn ui_builder() -> impl Widget<ChessBoardData> {
+fn ui_builder() -> impl Widget<AppData> {
     game_zone_builder()
+        .env_scope(|env, data: &AppData| {
+            env.set(PIECE_SET, ArcStr::from(data.settings.piece_set.as_str()));
+            data.settings.board_theme.apply_to_env(env);
+            data.settings.coordinates.apply_to_env(env);
+            env.set(BORDER_WIDTH, data.settings.border_width);
+        })
+        .controller(SettingsSaver)
 }
@@ -0,0 +1,95 @@
+use druid::widget::prelude::*;
+use druid::widget::{Controller, CrossAxisAlignment, Flex, Label, LineBreaking, TextBox};
+use druid::{Color, Data, KbKey, Lens, WidgetExt};
+
+use super::app_data::AppData;
+use super::commands::PLAY_MOVE;
+use super::localization::{localize, move_parse_error_text, piece_letters};
+use crate::chess::san::parse_move;
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct MoveEntryData {
+    pub text: String,
+    pub error: String,
+}
+
+impl MoveEntryData {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            error: String::new(),
+        }
+    }
+}
+
+/// A text entry where the user types moves such as `Nf3`, `exd5`, `e8=Q+` or `e2e4`,
+/// the pieces letters being the ones of the user language.
+pub fn move_entry_builder() -> impl Widget<AppData> {
+    let text_box = TextBox::new()
+        .controller(LocalizedPlaceholder("move-entry-placeholder"))
+        .expand_width()
+        .lens(MoveEntryData::text)
+        .lens(AppData::move_entry)
+        .controller(MoveEntryController);
+    let error_label = Label::dynamic(|data: &MoveEntryData, _env| data.error.clone())
+        .with_text_color(Color::rgb8(220, 50, 50))
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .lens(AppData::move_entry);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(text_box)
+        .with_spacer(4.0)
+        .with_child(error_label)
+}
+
+/// Plays the typed move when Enter is pressed, or explains why it cannot be played.
+struct MoveEntryController;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for MoveEntryController {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::KeyDown(key_event) = event {
+            if key_event.key == KbKey::Enter {
+                let letters = piece_letters(env);
+                let board = &data.board.board;
+                match parse_move(board, &data.move_entry.text, &letters) {
+                    Ok(chess_move) => {
+                        ctx.submit_command(PLAY_MOVE.with(chess_move));
+                        data.move_entry.text.clear();
+                        data.move_entry.error.clear();
+                    }
+                    Err(err) => data.move_entry.error = move_parse_error_text(env, &err, &letters),
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env);
+    }
+}
+
+/// Gives the text box the placeholder with the given message key, translated.
+struct LocalizedPlaceholder(&'static str);
+
+impl Controller<String, TextBox<String>> for LocalizedPlaceholder {
+    fn lifecycle(
+        &mut self,
+        child: &mut TextBox<String>,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &String,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            child.set_placeholder(localize(env, self.0));
+        }
+        child.lifecycle(ctx, event, data, env);
+    }
+}
@@ -0,0 +1,137 @@
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, TextBox};
+use druid::{Data, Env, Lens, LocalizedString, Widget, WidgetExt};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::app_data::AppData;
+use super::localization::{localize, localize_with_args, Argument};
+use crate::chess::chess960::{start_position_fen, START_POSITIONS};
+use crate::chess::variant::Variant;
+use crate::chess::BoardLogic;
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct NewGameData {
+    /// Number of the Chess960 start position to play, from 0 to 959, a random one when empty.
+    pub chess960_number: String,
+}
+
+impl NewGameData {
+    pub fn new() -> Self {
+        Self {
+            chess960_number: String::new(),
+        }
+    }
+}
+
+/// Sets the board in the standard start position or in a start position of Chess960,
+/// or starts a game of another variant.
+pub fn new_game_panel_builder() -> impl Widget<AppData> {
+    let standard_button = Button::new(LocalizedString::new("new-game-standard")).on_click(
+        |_ctx, data: &mut AppData, env| {
+            if is_training(data) {
+                return;
+            }
+            start_game(data, BoardLogic::new());
+            data.announcement = localize(env, "new-game-standard-started");
+        },
+    );
+    let chess960_button = Button::new(LocalizedString::new("new-game-chess960")).on_click(
+        |_ctx, data: &mut AppData, env| {
+            if !is_training(data) {
+                start_chess960_game(data, env);
+            }
+        },
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("new-game-panel-title")))
+        .with_child(standard_button)
+        .with_spacer(4.0)
+        .with_child(Label::new(LocalizedString::new("new-game-chess960-number")))
+        .with_child(
+            TextBox::new()
+                .lens(NewGameData::chess960_number)
+                .lens(AppData::new_game),
+        )
+        .with_child(chess960_button)
+        .with_spacer(4.0)
+        .with_child(variant_button(
+            Variant::KingOfTheHill,
+            "new-game-king-of-the-hill",
+            "new-game-king-of-the-hill-started",
+        ))
+        .with_child(variant_button(
+            Variant::ThreeCheck,
+            "new-game-three-check",
+            "new-game-three-check-started",
+        ))
+        .with_child(variant_button(
+            Variant::Antichess,
+            "new-game-antichess",
+            "new-game-antichess-started",
+        ))
+}
+
+/// Starts a game of the variant from the standard start position, announcing its rules.
+fn variant_button(
+    variant: Variant,
+    label_key: &'static str,
+    started_key: &'static str,
+) -> impl Widget<AppData> {
+    Button::new(LocalizedString::new(label_key)).on_click(move |_ctx, data: &mut AppData, env| {
+        if is_training(data) {
+            return;
+        }
+        start_game(data, BoardLogic::with_variant(variant));
+        data.announcement = localize(env, started_key);
+    })
+}
+
+/// Whether the board is used by a rush or a drill, which sets its positions itself.
+fn is_training(data: &AppData) -> bool {
+    data.rush.is_running() || data.repertoire.is_drilling() || data.endgame.is_drilling()
+}
+
+fn start_chess960_game(data: &mut AppData, env: &Env) {
+    let typed_number = data.new_game.chess960_number.trim();
+    let number = if typed_number.is_empty() {
+        random_start_number()
+    } else {
+        match typed_number.parse::<u32>() {
+            Ok(number) if number < START_POSITIONS => number,
+            _ => {
+                data.announcement = localize_with_args(
+                    env,
+                    "new-game-chess960-invalid",
+                    vec![("number", Argument::Text(typed_number.to_string()))],
+                );
+                return;
+            }
+        }
+    };
+    let board = start_position_fen(number).and_then(|fen| BoardLogic::from_chess960_fen(&fen));
+    if let Some(board) = board {
+        start_game(data, board);
+        data.announcement = localize_with_args(
+            env,
+            "new-game-chess960-started",
+            vec![("number", Argument::Text(number.to_string()))],
+        );
+    }
+}
+
+/// A start position picked from the clock, which is random enough to vary the games.
+fn random_start_number() -> u32 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    nanos % START_POSITIONS
+}
+
+/// Replaces the game on the board, whose clocks no longer apply.
+fn start_game(data: &mut AppData, board: BoardLogic) {
+    data.board.board = board;
+    data.clock = None;
+}
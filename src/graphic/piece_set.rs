@@ -0,0 +1,198 @@
+use druid::widget::SvgData;
+use druid::{ArcStr, Key};
+use log::error;
+
+use pleco::core::Piece;
+
+use crate::settings::config_dir;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the piece set the board must use.
+pub const PIECE_SET: Key<ArcStr> = Key::new("chess-exercises-organizer.piece-set");
+
+pub const DEFAULT_PIECE_SET: &str = "merida";
+
+const USER_PIECE_SETS_DIRECTORY_NAME: &str = "piece_sets";
+
+/// Files a piece set is made of, in the order of `PieceSet::images`.
+const PIECES_FILES_NAMES: [&str; 12] = [
+    "wP.svg", "wN.svg", "wB.svg", "wR.svg", "wQ.svg", "wK.svg", "bP.svg", "bN.svg", "bB.svg",
+    "bR.svg", "bQ.svg", "bK.svg",
+];
+
+macro_rules! bundled_piece_set {
+    ($dir:literal) => {
+        [
+            include_str!(concat!("../", $dir, "/wP.svg")),
+            include_str!(concat!("../", $dir, "/wN.svg")),
+            include_str!(concat!("../", $dir, "/wB.svg")),
+            include_str!(concat!("../", $dir, "/wR.svg")),
+            include_str!(concat!("../", $dir, "/wQ.svg")),
+            include_str!(concat!("../", $dir, "/wK.svg")),
+            include_str!(concat!("../", $dir, "/bP.svg")),
+            include_str!(concat!("../", $dir, "/bN.svg")),
+            include_str!(concat!("../", $dir, "/bB.svg")),
+            include_str!(concat!("../", $dir, "/bR.svg")),
+            include_str!(concat!("../", $dir, "/bQ.svg")),
+            include_str!(concat!("../", $dir, "/bK.svg")),
+        ]
+    };
+}
+
+const BUNDLED_PIECE_SETS_NAMES: [&str; 3] = ["merida", "geometric", "letters"];
+
+fn bundled_piece_set(name: &str) -> Option<[&'static str; 12]> {
+    match name {
+        "merida" => Some(bundled_piece_set!("merida")),
+        "geometric" => Some(bundled_piece_set!("geometric")),
+        "letters" => Some(bundled_piece_set!("letters")),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub enum PieceSetError {
+    UnreadableFile(PathBuf, io::Error),
+    InvalidSvg(PathBuf, String),
+}
+
+impl fmt::Display for PieceSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PieceSetError::UnreadableFile(path, err) => {
+                write!(f, "could not read piece image {:?} : {}", path, err)
+            }
+            PieceSetError::InvalidSvg(path, err) => {
+                write!(f, "invalid svg in piece image {:?} : {}", path, err)
+            }
+        }
+    }
+}
+
+/// The twelve images used to draw the pieces, parsed once when the set is loaded
+/// so that painting the board does not have to parse them again.
+#[derive(Clone)]
+pub struct PieceSet {
+    name: String,
+    images: Vec<SvgData>,
+}
+
+impl PieceSet {
+    /// Loads the set with the given name, either bundled or from the user piece sets directory.
+    /// Falls back to the default set if the user set is missing or invalid.
+    pub fn load(name: &str) -> Self {
+        if let Some(images) = bundled_piece_set(name) {
+            return PieceSet {
+                name: name.to_string(),
+                images: images.iter().map(|image| parse_svg_image(image)).collect(),
+            };
+        }
+
+        let loading_result = match user_piece_sets_dir() {
+            Some(dir) => PieceSet::load_from_directory(name, &dir.join(name)),
+            None => Err(PieceSetError::UnreadableFile(
+                PathBuf::from(name),
+                io::Error::new(io::ErrorKind::NotFound, "no configuration directory"),
+            )),
+        };
+        match loading_result {
+            Ok(piece_set) => piece_set,
+            Err(err) => {
+                error!("{}", err);
+                error!("Using piece set {} instead of {}.", DEFAULT_PIECE_SET, name);
+                PieceSet::load(DEFAULT_PIECE_SET)
+            }
+        }
+    }
+
+    /// Reads the twelve images (wP.svg, ..., bK.svg) of a directory, checking that each one
+    /// is a valid SVG.
+    pub fn load_from_directory(name: &str, dir: &Path) -> Result<Self, PieceSetError> {
+        let mut images = Vec::with_capacity(PIECES_FILES_NAMES.len());
+        for file_name in PIECES_FILES_NAMES.iter() {
+            let path = dir.join(file_name);
+            let content = fs::read_to_string(&path)
+                .map_err(|err| PieceSetError::UnreadableFile(path.clone(), err))?;
+            let image = content
+                .parse::<SvgData>()
+                .map_err(|err| PieceSetError::InvalidSvg(path, err.to_string()))?;
+            images.push(image);
+        }
+        Ok(PieceSet {
+            name: name.to_string(),
+            images,
+        })
+    }
+
+    /// Names of the bundled sets, followed by the names of the user sets.
+    pub fn available_names() -> Vec<String> {
+        let mut names: Vec<String> = BUNDLED_PIECE_SETS_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let mut user_names = Vec::new();
+        if let Some(dir) = user_piece_sets_dir() {
+            if let Ok(entries) = fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let is_dir = entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false);
+                    if !is_dir {
+                        continue;
+                    }
+                    if let Some(name) = entry.file_name().to_str() {
+                        if !names.iter().any(|existing| existing == name) {
+                            user_names.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        user_names.sort();
+        names.append(&mut user_names);
+        names
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn image(&self, piece: Piece) -> Option<&SvgData> {
+        let index = match piece {
+            Piece::WhitePawn => 0,
+            Piece::WhiteKnight => 1,
+            Piece::WhiteBishop => 2,
+            Piece::WhiteRook => 3,
+            Piece::WhiteQueen => 4,
+            Piece::WhiteKing => 5,
+            Piece::BlackPawn => 6,
+            Piece::BlackKnight => 7,
+            Piece::BlackBishop => 8,
+            Piece::BlackRook => 9,
+            Piece::BlackQueen => 10,
+            Piece::BlackKing => 11,
+            Piece::None => return None,
+        };
+        self.images.get(index)
+    }
+}
+
+/// Parses an SVG bundled with the application, an empty image standing for an invalid one.
+pub fn parse_svg_image(raw_data: &str) -> SvgData {
+    match raw_data.parse::<SvgData>() {
+        Ok(svg) => svg,
+        Err(err) => {
+            error!("{}", err);
+            error!("Using an empty SVG instead of {}.", raw_data);
+            SvgData::default()
+        }
+    }
+}
+
+/// Each subdirectory of this directory is a user piece set.
+pub fn user_piece_sets_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(USER_PIECE_SETS_DIRECTORY_NAME))
+}
@@ -0,0 +1,41 @@
+use druid::widget::{Button, CrossAxisAlignment, Flex, Label, LineBreaking};
+use druid::{LocalizedString, Widget, WidgetExt};
+
+use super::app_data::AppData;
+use super::chess_board::ChessBoardData;
+use super::commands::DESCRIBE_POSITION;
+use super::localization::{localize, position_description_text};
+use crate::chess::description::describe_position;
+
+/// Textual view of the game, for the users who cannot see the board :
+/// the last announcement (move played or position asked for) and the current position.
+pub fn position_panel_builder() -> impl Widget<AppData> {
+    let announcement = Label::dynamic(|data: &AppData, _env| data.announcement.clone())
+        .with_line_break_mode(LineBreaking::WordWrap);
+    let position = Label::dynamic(|data: &ChessBoardData, env| {
+        if data.shows_all_pieces() {
+            position_description_text(env, &describe_position(&data.board))
+        } else {
+            localize(env, "position-hidden")
+        }
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::board);
+    let describe_button = Button::new(LocalizedString::new("position-panel-describe")).on_click(
+        |ctx, _data: &mut AppData, _env| {
+            ctx.submit_command(DESCRIBE_POSITION);
+        },
+    );
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new(
+            "position-panel-last-announcement",
+        )))
+        .with_child(announcement)
+        .with_spacer(8.0)
+        .with_child(Label::new(LocalizedString::new("position-panel-position")))
+        .with_child(position)
+        .with_spacer(4.0)
+        .with_child(describe_button)
+}
@@ -0,0 +1,307 @@
+use druid::widget::{
+    Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, RadioGroup,
+};
+use druid::{
+    commands, Data, Env, Event, EventCtx, FileDialogOptions, FileSpec, Lens, LocalizedString,
+    Widget, WidgetExt,
+};
+use log::error;
+
+use pleco::{BitMove, Player};
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::app_data::AppData;
+use super::commands::OPEN_REPERTOIRE;
+use super::localization::{
+    localize, localize_with_args, move_announcement_text, repertoire_status_text,
+    repertoire_wrong_text, Argument,
+};
+use crate::chess::description::announce_move;
+use crate::chess::pgn::parse_pgn;
+use crate::chess::repertoire::{DrillStep, Repertoire, RepertoireDrill};
+use crate::chess::BoardLogic;
+use crate::repetition::{today_number, RepetitionSchedule};
+
+/// Side of the repertoire played by the user.
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub enum DrilledSide {
+    White,
+    Black,
+}
+
+impl DrilledSide {
+    fn player(self) -> Player {
+        match self {
+            DrilledSide::White => Player::White,
+            DrilledSide::Black => Player::Black,
+        }
+    }
+}
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct RepertoireData {
+    /// The repertoire read from the chosen file, with the name of the file.
+    pub repertoire: Option<Arc<Repertoire>>,
+    pub file_name: String,
+    pub side: DrilledSide,
+    /// Whether the opponent plays the moves of more lines of the file more often,
+    /// rather than all of them alike.
+    pub weighted: bool,
+    /// The drill going on, if any.
+    pub drill: Option<RepertoireDrill>,
+    pub schedule: RepetitionSchedule,
+}
+
+impl RepertoireData {
+    pub fn new() -> Self {
+        Self {
+            repertoire: None,
+            file_name: String::new(),
+            side: DrilledSide::White,
+            weighted: true,
+            drill: None,
+            schedule: RepetitionSchedule::load(),
+        }
+    }
+
+    /// Whether the moves played on the board are the answers of a repertoire drill.
+    pub fn is_drilling(&self) -> bool {
+        self.drill.is_some()
+    }
+}
+
+/// Choice of the repertoire file and of the drilled side, and answers of the drill.
+pub fn repertoire_panel_builder() -> impl Widget<AppData> {
+    let open_button = Button::new(LocalizedString::new("repertoire-open")).on_click(
+        |ctx, data: &mut AppData, _env| {
+            if data.repertoire.is_drilling() {
+                return;
+            }
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .accept_command(OPEN_REPERTOIRE);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options));
+        },
+    );
+    let file_name = Label::dynamic(|data: &RepertoireData, env| {
+        if data.repertoire.is_some() {
+            data.file_name.clone()
+        } else {
+            localize(env, "repertoire-none")
+        }
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::repertoire);
+    let sides = RadioGroup::new(vec![
+        (LocalizedString::new("repertoire-white"), DrilledSide::White),
+        (LocalizedString::new("repertoire-black"), DrilledSide::Black),
+    ])
+    .lens(RepertoireData::side)
+    .lens(AppData::repertoire);
+    let opponent_choices = RadioGroup::new(vec![
+        (LocalizedString::new("repertoire-weighted"), true),
+        (LocalizedString::new("repertoire-random"), false),
+    ])
+    .lens(RepertoireData::weighted)
+    .lens(AppData::repertoire);
+
+    let start_button = Button::dynamic(|data: &AppData, env| {
+        if data.repertoire.is_drilling() {
+            localize(env, "repertoire-stop")
+        } else {
+            localize(env, "repertoire-start")
+        }
+    })
+    .on_click(|_ctx, data: &mut AppData, env| {
+        if data.repertoire.is_drilling() {
+            data.repertoire.drill = None;
+        } else if data.repertoire.repertoire.is_some()
+            && !data.rush.is_running()
+            && !data.endgame.is_drilling()
+        {
+            start_drill(data, env);
+        }
+    });
+    let status = Label::dynamic(|data: &RepertoireData, env| match &data.drill {
+        Some(drill) => {
+            let today = today_number();
+            let due = drill
+                .repertoire()
+                .positions_of(drill.side())
+                .iter()
+                .filter(|position| data.schedule.is_due(position, today))
+                .count();
+            repertoire_status_text(env, drill, due)
+        }
+        None => String::new(),
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::repertoire);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("repertoire-panel-title")))
+        .with_child(open_button)
+        .with_child(file_name)
+        .with_child(sides)
+        .with_child(Label::new(LocalizedString::new("repertoire-opponent")))
+        .with_child(opponent_choices)
+        .with_child(start_button)
+        .with_child(status)
+        .controller(RepertoireLoader)
+}
+
+/// Reads the repertoire once its file is chosen.
+struct RepertoireLoader;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for RepertoireLoader {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::Command(cmd) = event {
+            if let Some(file_info) = cmd.get(OPEN_REPERTOIRE) {
+                let path = file_info.path();
+                match load_repertoire(path) {
+                    Ok(repertoire) => {
+                        data.repertoire.repertoire = Some(Arc::new(repertoire));
+                        data.repertoire.file_name = path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                    }
+                    Err(message) => {
+                        error!("{}", message);
+                        data.announcement = localize_with_args(
+                            env,
+                            "repertoire-load-failed",
+                            vec![("error", Argument::Text(message))],
+                        );
+                    }
+                }
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+fn load_repertoire(path: &Path) -> Result<Repertoire, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {} : {}", path.display(), err))?;
+    let games = parse_pgn(&content)
+        .map_err(|err| format!("Invalid PGN file {} : {}", path.display(), err))?;
+    Repertoire::from_games(&games).map_err(|err| err.to_string())
+}
+
+fn start_drill(data: &mut AppData, env: &Env) {
+    let repertoire = match &data.repertoire.repertoire {
+        Some(repertoire) => repertoire.clone(),
+        None => return,
+    };
+    let side = data.repertoire.side.player();
+    data.repertoire.drill = Some(RepertoireDrill::new(
+        repertoire,
+        side,
+        data.repertoire.weighted,
+    ));
+    data.board.reversed = side == Player::Black;
+    start_line(data, env);
+}
+
+/// Sets the board in the start position of the repertoire, and plays the first move
+/// of the opponent when they start. The drill stops when the user has nothing to answer.
+fn start_line(data: &mut AppData, env: &Env) {
+    let drill = match &data.repertoire.drill {
+        Some(drill) => drill,
+        None => return,
+    };
+    let mut board = drill.repertoire().start_board();
+    let mut announcement = localize(env, "repertoire-new-line");
+    if let Some(reply) = opponent_move(drill, &data.repertoire.schedule, &board) {
+        announcement.push(' ');
+        announcement.push_str(&move_announcement_text(env, &announce_move(&board, reply)));
+        board.play_move(reply);
+    }
+    if drill.expects_answer(&board) {
+        data.board.board = board;
+        data.announcement = announcement;
+    } else {
+        data.repertoire.drill = None;
+        data.announcement = localize(env, "repertoire-nothing-to-drill");
+    }
+}
+
+/// A move of the opponent from the tree, preferring the lines with positions due for review.
+fn opponent_move(
+    drill: &RepertoireDrill,
+    schedule: &RepetitionSchedule,
+    board: &BoardLogic,
+) -> Option<BitMove> {
+    let today = today_number();
+    let random = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| u64::from(time.subsec_nanos()))
+        .unwrap_or_default();
+    drill.opponent_move(board, random, &|position: &str| {
+        schedule.is_due(position, today)
+    })
+}
+
+/// Checks a move played during a drill, and schedules the next review of its position.
+/// A repertoire move is played with the reply of the opponent, and a new line starts
+/// once the user has nothing more to answer. After a wrong move, the user tries again.
+pub fn play_repertoire_move(data: &mut AppData, chess_move: BitMove, env: &Env) {
+    let RepertoireData {
+        drill, schedule, ..
+    } = &mut data.repertoire;
+    let drill = match drill {
+        Some(drill) => drill,
+        None => return,
+    };
+    let board = &mut data.board.board;
+    let position = board.position_key();
+    let step = drill.play(board, chess_move);
+    // Only the first answer in the position counts.
+    let answer = match &step {
+        DrillStep::Correct { first_try: true } => Some(true),
+        DrillStep::Wrong {
+            first_miss: true, ..
+        } => Some(false),
+        _ => None,
+    };
+    if let Some(right) = answer {
+        schedule.record(&position, right, today_number());
+        if let Err(err) = schedule.save() {
+            error!("Could not save repertoire reviews : {}", err);
+        }
+    }
+    if let DrillStep::Wrong { expected, .. } = step {
+        data.announcement = repertoire_wrong_text(env, board, &expected);
+        return;
+    }
+
+    let mut announcement = move_announcement_text(env, &announce_move(board, chess_move));
+    board.play_move(chess_move);
+    if let Some(reply) = opponent_move(drill, schedule, board) {
+        announcement.push(' ');
+        announcement.push_str(&move_announcement_text(env, &announce_move(board, reply)));
+        board.play_move(reply);
+    }
+    if drill.expects_answer(board) {
+        data.announcement = announcement;
+    } else {
+        let line_done = localize(env, "repertoire-line-done");
+        start_line(data, env);
+        data.announcement = format!("{} {} {}", announcement, line_done, data.announcement);
+    }
+}
@@ -0,0 +1,360 @@
+use druid::widget::{Button, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, TextBox};
+use druid::{
+    commands, Data, Env, Event, EventCtx, ExtEventSink, FileDialogOptions, FileSpec, Lens,
+    LocalizedString, Target, Widget, WidgetExt,
+};
+use log::error;
+
+use pleco::BitMove;
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use super::app_data::AppData;
+use super::commands::{
+    BLUNDERS_FOUND, OPEN_EXERCISES_TO_VERIFY, OPEN_GAMES_FOR_BLUNDERS, REVIEW_FINISHED,
+    REVIEW_PROGRESS, SAVE_REVIEW_AS, VERIFICATION_FINISHED,
+};
+use super::localization::review_progress_text;
+use crate::chess::annotation::{annotate_game, review_game, MoveQuality};
+use crate::chess::blunders::blunder_exercises;
+use crate::chess::engine::{EngineKind, SearchLimit};
+use crate::chess::exercise::{main_line_moves, Exercise};
+use crate::chess::pgn::{parse_pgn, PgnGame};
+use crate::chess::verification::{record_findings, verify_exercise, Finding};
+use crate::collections::save_collection;
+
+/// Search of each position of a reviewed game.
+const REVIEW_LIMIT: SearchLimit = SearchLimit {
+    depth: 12,
+    time: Duration::from_secs(1),
+};
+
+/// Search of each position of the games searched for blunders, which are many.
+const BLUNDER_SEARCH_LIMIT: SearchLimit = SearchLimit {
+    depth: 10,
+    time: Duration::from_millis(300),
+};
+
+/// Search of each position where the solver of a verified exercise is to move.
+const VERIFICATION_LIMIT: SearchLimit = SearchLimit {
+    depth: 12,
+    time: Duration::from_secs(1),
+};
+
+#[derive(Data, Clone, Copy, Debug, PartialEq)]
+pub enum ReviewProgress {
+    /// Positions of the reviewed game evaluated, and their total.
+    Positions(usize, usize),
+    /// Games searched for blunders, and their total.
+    Games(usize, usize),
+    /// Exercises whose solution is verified, and their total.
+    Exercises(usize, usize),
+}
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct ReviewData {
+    /// Progress of the review going on, if any.
+    pub progress: Option<ReviewProgress>,
+    /// Name of the player whose blunders become exercises, all blunders being kept when empty.
+    pub player: String,
+}
+
+impl ReviewData {
+    pub fn new() -> Self {
+        Self {
+            progress: None,
+            player: String::new(),
+        }
+    }
+}
+
+/// Where the annotated game was written, and how many bad moves it has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewSummary {
+    pub path: String,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+/// The collection made of the blunders found in a PGN file of games.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlundersSummary {
+    pub collection: String,
+    pub games: usize,
+    pub exercises: usize,
+}
+
+/// How many verified exercises have a key move which is not the best, or other winning moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationSummary {
+    pub path: String,
+    pub exercises: usize,
+    pub not_best: usize,
+    pub multiple_solutions: usize,
+}
+
+/// Buttons reviewing the game played on the board, the games of a PGN file
+/// to make exercises of their blunders, or the solutions of a PGN file of exercises,
+/// and progress of the review going on.
+pub fn review_panel_builder() -> impl Widget<AppData> {
+    let review_button = Button::new(LocalizedString::new("review-game")).on_click(
+        |ctx, data: &mut AppData, _env| {
+            if data.review.progress.is_some()
+                || data.board.board.history.is_empty()
+                || data.rush.is_running()
+                || !data.board.board.variant.is_known_to_engines()
+            {
+                return;
+            }
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .default_name("review.pgn")
+                .accept_command(SAVE_REVIEW_AS);
+            ctx.submit_command(commands::SHOW_SAVE_PANEL.with(options));
+        },
+    );
+    let blunders_button = Button::new(LocalizedString::new("review-blunders")).on_click(
+        |ctx, data: &mut AppData, _env| {
+            if data.review.progress.is_some() {
+                return;
+            }
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .accept_command(OPEN_GAMES_FOR_BLUNDERS);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options));
+        },
+    );
+    let verify_button = Button::new(LocalizedString::new("review-verify")).on_click(
+        |ctx, data: &mut AppData, _env| {
+            if data.review.progress.is_some() {
+                return;
+            }
+            let options = FileDialogOptions::new()
+                .allowed_types(vec![FileSpec::new("PGN", &["pgn"])])
+                .accept_command(OPEN_EXERCISES_TO_VERIFY);
+            ctx.submit_command(commands::SHOW_OPEN_PANEL.with(options));
+        },
+    );
+    let progress = Label::dynamic(|data: &ReviewData, env| match data.progress {
+        Some(progress) => review_progress_text(env, progress),
+        None => String::new(),
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::review);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("review-panel-title")))
+        .with_child(review_button)
+        .with_spacer(4.0)
+        .with_child(Label::new(LocalizedString::new("review-player")))
+        .with_child(
+            TextBox::new()
+                .lens(ReviewData::player)
+                .lens(AppData::review),
+        )
+        .with_child(blunders_button)
+        .with_spacer(4.0)
+        .with_child(verify_button)
+        .with_child(progress)
+        .controller(ReviewLauncher)
+}
+
+/// Starts the reviews in another thread once their file is chosen.
+/// Their progress and summary reach the delegate as commands.
+struct ReviewLauncher;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for ReviewLauncher {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::Command(cmd) = event {
+            if let Some(file_info) = cmd.get(SAVE_REVIEW_AS) {
+                let sink = ctx.get_external_handle();
+                let engine = data.settings.engine();
+                let moves = data.board.board.history.clone();
+                let game = data.game_pgn();
+                let path = file_info.path().to_path_buf();
+                data.review.progress = Some(ReviewProgress::Positions(0, moves.len() + 1));
+                thread::spawn(move || {
+                    let outcome = review_and_save(&engine, &moves, game, &path, &sink);
+                    let _ = sink.submit_command(REVIEW_FINISHED, outcome, Target::Auto);
+                });
+                ctx.set_handled();
+                return;
+            }
+            if let Some(file_info) = cmd.get(OPEN_GAMES_FOR_BLUNDERS) {
+                let sink = ctx.get_external_handle();
+                let engine = data.settings.engine();
+                let player = data.review.player.clone();
+                let path = file_info.path().to_path_buf();
+                data.review.progress = Some(ReviewProgress::Games(0, 0));
+                thread::spawn(move || {
+                    let outcome = collect_blunders(&engine, &path, &player, &sink);
+                    let _ = sink.submit_command(BLUNDERS_FOUND, outcome, Target::Auto);
+                });
+                ctx.set_handled();
+                return;
+            }
+            if let Some(file_info) = cmd.get(OPEN_EXERCISES_TO_VERIFY) {
+                let sink = ctx.get_external_handle();
+                let engine = data.settings.engine();
+                let path = file_info.path().to_path_buf();
+                data.review.progress = Some(ReviewProgress::Exercises(0, 0));
+                thread::spawn(move || {
+                    let outcome = verify_solutions(&engine, &path, &sink);
+                    let _ = sink.submit_command(VERIFICATION_FINISHED, outcome, Target::Auto);
+                });
+                ctx.set_handled();
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+/// Reviews the moves of the game, starting from its start position, and writes it annotated.
+fn review_and_save(
+    engine: &EngineKind,
+    moves: &[BitMove],
+    mut game: PgnGame,
+    path: &Path,
+    sink: &ExtEventSink,
+) -> Result<ReviewSummary, String> {
+    let start = game
+        .start_board()
+        .ok_or_else(|| format!("Invalid position {}", game.start_fen()))?;
+    let reviews = review_game(engine, &start, moves, REVIEW_LIMIT, |done, total| {
+        let progress = ReviewProgress::Positions(done, total);
+        let _ = sink.submit_command(REVIEW_PROGRESS, progress, Target::Auto);
+    })?;
+    annotate_game(&mut game, &reviews);
+    fs::write(path, game.to_pgn_string())
+        .map_err(|err| format!("Could not write the review to {} : {}", path.display(), err))?;
+    let count = |quality| {
+        reviews
+            .iter()
+            .filter(|review| review.quality == Some(quality))
+            .count()
+    };
+    Ok(ReviewSummary {
+        path: path.display().to_string(),
+        inaccuracies: count(MoveQuality::Inaccuracy),
+        mistakes: count(MoveQuality::Mistake),
+        blunders: count(MoveQuality::Blunder),
+    })
+}
+
+/// Reviews the games of the PGN file, and saves the exercises made of their blunders
+/// as a collection named after the file.
+fn collect_blunders(
+    engine: &EngineKind,
+    path: &Path,
+    player: &str,
+    sink: &ExtEventSink,
+) -> Result<BlundersSummary, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {} : {}", path.display(), err))?;
+    let games = parse_pgn(&content)
+        .map_err(|err| format!("Invalid PGN file {} : {}", path.display(), err))?;
+    let mut exercises = Vec::new();
+    for (index, game) in games.iter().enumerate() {
+        let progress = ReviewProgress::Games(index, games.len());
+        let _ = sink.submit_command(REVIEW_PROGRESS, progress, Target::Auto);
+        if !game.is_known_to_engines() {
+            error!(
+                "Skipping game {} of {:?} : the engine does not play its variant",
+                index + 1,
+                path
+            );
+            continue;
+        }
+        let moves = match main_line_moves(game) {
+            Ok(moves) => moves,
+            Err(err) => {
+                error!("Skipping game {} of {:?} : {}", index + 1, path, err);
+                continue;
+            }
+        };
+        // The moves were read from the start position, which is then valid.
+        let start = game.start_board().unwrap();
+        let reviews = review_game(engine, &start, &moves, BLUNDER_SEARCH_LIMIT, |_, _| {})?;
+        exercises.extend(blunder_exercises(game, &reviews, Some(player)));
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("games");
+    let collection = format!("{} blunders", stem);
+    save_collection(&collection, &exercises).map_err(|err| err.to_string())?;
+    Ok(BlundersSummary {
+        collection,
+        games: games.len(),
+        exercises: exercises.len(),
+    })
+}
+
+/// Verifies the solution of every exercise of the PGN file with the engine, and writes
+/// the findings in the tags of the exercises. Games which are not valid exercises
+/// are written back untouched.
+fn verify_solutions(
+    engine: &EngineKind,
+    path: &Path,
+    sink: &ExtEventSink,
+) -> Result<VerificationSummary, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {} : {}", path.display(), err))?;
+    let mut games = parse_pgn(&content)
+        .map_err(|err| format!("Invalid PGN file {} : {}", path.display(), err))?;
+    let mut summary = VerificationSummary {
+        path: path.display().to_string(),
+        exercises: 0,
+        not_best: 0,
+        multiple_solutions: 0,
+    };
+    let total = games.len();
+    for (index, game) in games.iter_mut().enumerate() {
+        let progress = ReviewProgress::Exercises(index, total);
+        let _ = sink.submit_command(REVIEW_PROGRESS, progress, Target::Auto);
+        if !game.is_known_to_engines() {
+            error!(
+                "Skipping game {} of {:?} : the engine does not play its variant",
+                index + 1,
+                path
+            );
+            continue;
+        }
+        let exercise = match Exercise::from_pgn_game(game.clone()) {
+            Ok(exercise) => exercise,
+            Err(err) => {
+                error!("Skipping game {} of {:?} : {}", index + 1, path, err);
+                continue;
+            }
+        };
+        let findings = verify_exercise(engine, &exercise, VERIFICATION_LIMIT)?;
+        let has = |is_kind: fn(&Finding) -> bool| findings.iter().any(is_kind);
+        if has(|finding| matches!(finding, Finding::NotBest { .. })) {
+            summary.not_best += 1;
+        }
+        if has(|finding| matches!(finding, Finding::Alternatives { .. })) {
+            summary.multiple_solutions += 1;
+        }
+        summary.exercises += 1;
+        record_findings(game, &exercise.solution, &findings);
+    }
+
+    let content: Vec<String> = games.iter().map(PgnGame::to_pgn_string).collect();
+    fs::write(path, content.join("\n"))
+        .map_err(|err| format!("Could not write {} : {}", path.display(), err))?;
+    Ok(summary)
+}
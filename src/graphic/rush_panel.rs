@@ -0,0 +1,363 @@
+use druid::widget::{
+    Button, Checkbox, Controller, CrossAxisAlignment, Flex, Label, LineBreaking, RadioGroup,
+};
+use druid::{
+    Data, Env, Event, EventCtx, Lens, LifeCycle, LifeCycleCtx, LocalizedString, Target, TimerToken,
+    UpdateCtx, Widget, WidgetExt,
+};
+use log::error;
+
+use pleco::{BitMove, Player};
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::app_data::AppData;
+use super::commands::RUSH_MOVE_JUDGED;
+use super::localization::{
+    localize, localize_with_args, move_announcement_text, rush_end_text, rush_exercise_text,
+    rush_scores_text, rush_status_text, Argument,
+};
+use crate::chess::description::announce_move;
+use crate::chess::exercise::{AcceptancePolicy, JudgedMove};
+use crate::chess::rush::{PuzzleRush, RushStep};
+use crate::collections::{available_collections, load_collection, CollectionPolicies};
+use crate::leaderboard::Leaderboard;
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Lengths of a rush offered, in minutes.
+const RUSH_MINUTES: [usize; 3] = [3, 5, 10];
+
+/// Tolerances offered for the scores of moves other than the solution, in centipawns.
+const EVAL_TOLERANCES: [u32; 3] = [30, 50, 100];
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct RushData {
+    /// Collection chosen for the next rush.
+    pub collection: String,
+    /// Length of the next rush, in minutes.
+    pub minutes: usize,
+    /// The rush going on, or the last one once it is over.
+    pub session: Option<PuzzleRush>,
+    pub leaderboard: Leaderboard,
+    pub policies: CollectionPolicies,
+}
+
+impl RushData {
+    pub fn new() -> Self {
+        Self {
+            collection: available_collections().first().cloned().unwrap_or_default(),
+            minutes: RUSH_MINUTES[1],
+            session: None,
+            leaderboard: Leaderboard::load(),
+            policies: CollectionPolicies::load(),
+        }
+    }
+
+    /// Whether the moves played on the board are the answers to a rush exercise.
+    pub fn is_running(&self) -> bool {
+        self.session
+            .as_ref()
+            .map(|session| !session.is_finished())
+            .unwrap_or(false)
+    }
+}
+
+/// Choice of the collection and length, countdown, score and best scores of the puzzle rush.
+pub fn rush_panel_builder() -> impl Widget<AppData> {
+    let collections = available_collections();
+    let collection_choice: Box<dyn Widget<RushData>> = if collections.is_empty() {
+        Box::new(
+            Label::new(LocalizedString::new("rush-no-collection"))
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+    } else {
+        Box::new(
+            RadioGroup::new(collections.into_iter().map(|name| (name.clone(), name)))
+                .lens(RushData::collection),
+        )
+    };
+    let durations = RadioGroup::new(RUSH_MINUTES.iter().map(|minutes| {
+        let minutes = *minutes;
+        (
+            LocalizedString::new("rush-minutes")
+                .with_arg("minutes", move |_data: &usize, _env: &Env| minutes.into()),
+            minutes,
+        )
+    }))
+    .lens(RushData::minutes);
+    let mut tolerance_choices = vec![(LocalizedString::new("rush-tolerance-off"), None)];
+    for tolerance in EVAL_TOLERANCES.iter() {
+        let pawns = (f64::from(*tolerance) / 100.0).to_string();
+        tolerance_choices.push((
+            LocalizedString::new("rush-tolerance")
+                .with_arg("pawns", move |_data: &Option<u32>, _env: &Env| {
+                    pawns.clone().into()
+                }),
+            Some(*tolerance),
+        ));
+    }
+    let tolerances = RadioGroup::new(tolerance_choices);
+    let acceptance = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("rush-acceptance")))
+        .with_child(
+            Checkbox::new(LocalizedString::new("rush-accept-variations"))
+                .lens(AcceptancePolicy::variations),
+        )
+        .with_child(
+            Checkbox::new(LocalizedString::new("rush-accept-mates"))
+                .lens(AcceptancePolicy::same_mate),
+        )
+        .with_child(
+            Label::new(LocalizedString::new("rush-accept-tolerance"))
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+        .with_child(tolerances.lens(AcceptancePolicy::eval_tolerance))
+        .lens(ChosenCollectionPolicy)
+        .lens(AppData::rush);
+
+    let start_button = Button::dynamic(|data: &AppData, env| {
+        if data.rush.is_running() {
+            localize(env, "rush-stop")
+        } else {
+            localize(env, "rush-start")
+        }
+    })
+    .on_click(|_ctx, data: &mut AppData, env| {
+        if data.rush.is_running() {
+            data.rush.session = None;
+        } else if !data.rush.collection.is_empty()
+            && !data.repertoire.is_drilling()
+            && !data.endgame.is_drilling()
+        {
+            start_rush(data, env);
+        }
+    });
+    let status = Label::dynamic(|data: &RushData, env| match &data.session {
+        Some(session) => rush_status_text(env, session),
+        None => String::new(),
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::rush);
+    let scores = Label::dynamic(|data: &RushData, env| {
+        rush_scores_text(env, &data.leaderboard, &data.collection)
+    })
+    .with_line_break_mode(LineBreaking::WordWrap)
+    .lens(AppData::rush);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("rush-panel-title")))
+        .with_child(collection_choice.lens(AppData::rush))
+        .with_child(durations.lens(AppData::rush))
+        .with_child(acceptance)
+        .with_child(start_button)
+        .with_child(status)
+        .with_spacer(4.0)
+        .with_child(scores)
+        .controller(RushTicker {
+            timer: TimerToken::INVALID,
+        })
+}
+
+/// The acceptance policy of the collection chosen for the next rush, saved when it changes.
+struct ChosenCollectionPolicy;
+
+impl Lens<RushData, AcceptancePolicy> for ChosenCollectionPolicy {
+    fn with<V, F: FnOnce(&AcceptancePolicy) -> V>(&self, data: &RushData, f: F) -> V {
+        f(&data.policies.policy(&data.collection))
+    }
+
+    fn with_mut<V, F: FnOnce(&mut AcceptancePolicy) -> V>(&self, data: &mut RushData, f: F) -> V {
+        let previous = data.policies.policy(&data.collection);
+        let mut policy = previous;
+        let value = f(&mut policy);
+        if policy != previous {
+            data.policies.set_policy(&data.collection, policy);
+            if let Err(err) = data.policies.save() {
+                error!("Could not save acceptance policies : {}", err);
+            }
+        }
+        value
+    }
+}
+
+fn start_rush(data: &mut AppData, env: &Env) {
+    match load_collection(&data.rush.collection) {
+        Ok(exercises) if !exercises.is_empty() => {
+            let duration = Duration::from_secs(data.rush.minutes as u64 * 60);
+            data.rush.session = Some(PuzzleRush::new(
+                data.rush.collection.clone(),
+                Arc::new(exercises),
+                data.rush.policies.policy(&data.rush.collection),
+                data.settings.engine(),
+                duration,
+                Instant::now(),
+            ));
+            set_up_exercise(data, env);
+        }
+        Ok(_) => data.announcement = localize(env, "rush-empty-collection"),
+        Err(err) => {
+            error!("{}", err);
+            data.announcement = localize_with_args(
+                env,
+                "rush-load-failed",
+                vec![("error", Argument::Text(err.to_string()))],
+            );
+        }
+    }
+}
+
+/// Sets the board in the start position of the current exercise, from the solver's side.
+fn set_up_exercise(data: &mut AppData, env: &Env) {
+    if let Some(session) = &data.rush.session {
+        let exercise = session.current_exercise();
+        data.board.board = exercise.start_position();
+        data.board.reversed = exercise.solver() == Player::Black;
+        data.announcement = rush_exercise_text(env, session);
+    }
+}
+
+/// Checks a move played during a rush, plays it with the reply of the opponent when it is
+/// the expected one, and moves on to the next exercise once the current one is over.
+pub fn play_rush_move(data: &mut AppData, chess_move: BitMove, env: &Env) {
+    let step = match &mut data.rush.session {
+        Some(session) => session.play(chess_move, Instant::now()),
+        None => return,
+    };
+    apply_rush_step(data, chess_move, step, env);
+}
+
+/// Ends the check of a move of the rush with the judgement of the engine.
+pub fn conclude_rush_move(data: &mut AppData, judged: &JudgedMove, env: &Env) {
+    let step = match &mut data.rush.session {
+        Some(session) => session.conclude(judged, Instant::now()),
+        None => return,
+    };
+    if let Some(step) = step {
+        apply_rush_step(data, judged.chess_move, step, env);
+    }
+}
+
+fn apply_rush_step(data: &mut AppData, chess_move: BitMove, step: RushStep, env: &Env) {
+    let finished = data
+        .rush
+        .session
+        .as_ref()
+        .map_or(true, |session| session.is_finished());
+    match step {
+        RushStep::Continue(reply) => {
+            let board = &mut data.board.board;
+            let mut announcement = move_announcement_text(env, &announce_move(board, chess_move));
+            board.play_move(chess_move);
+            if let Some(reply) = reply {
+                announcement.push(' ');
+                announcement.push_str(&move_announcement_text(env, &announce_move(board, reply)));
+                board.play_move(reply);
+            }
+            data.announcement = announcement;
+        }
+        RushStep::Solved | RushStep::Failed => {
+            let verdict = if step == RushStep::Solved {
+                localize(env, "rush-solved")
+            } else {
+                localize(env, "rush-strike")
+            };
+            if finished {
+                finish_rush(data, env);
+            } else {
+                set_up_exercise(data, env);
+            }
+            data.announcement = format!("{} {}", verdict, data.announcement);
+        }
+        RushStep::Judging => data.announcement = localize(env, "rush-judging"),
+    }
+}
+
+/// Records the score of the rush which has just ended, and announces it.
+fn finish_rush(data: &mut AppData, env: &Env) {
+    let (collection, score) = match &data.rush.session {
+        Some(session) => (session.collection().to_string(), session.score()),
+        None => return,
+    };
+    let previous_best = data.rush.leaderboard.personal_best(&collection);
+    data.rush.leaderboard.record(&collection, score);
+    if let Err(err) = data.rush.leaderboard.save() {
+        error!("Could not save rush scores : {}", err);
+    }
+    data.announcement = rush_end_text(env, score, previous_best);
+}
+
+/// Updates the countdown of the rush every tenth of a second, and ends it when time runs out.
+/// Also judges the moves left to the engine in another thread.
+struct RushTicker {
+    timer: TimerToken,
+}
+
+impl<W: Widget<AppData>> Controller<AppData, W> for RushTicker {
+    fn event(
+        &mut self,
+        child: &mut W,
+        ctx: &mut EventCtx,
+        event: &Event,
+        data: &mut AppData,
+        env: &Env,
+    ) {
+        if let Event::Timer(token) = event {
+            if *token == self.timer {
+                let time_out = match &mut data.rush.session {
+                    Some(session) => session.tick(Instant::now()),
+                    None => false,
+                };
+                if time_out {
+                    finish_rush(data, env);
+                }
+                self.timer = ctx.request_timer(TICK_INTERVAL);
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+
+    fn lifecycle(
+        &mut self,
+        child: &mut W,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.timer = ctx.request_timer(TICK_INTERVAL);
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if let Some(session) = &data.rush.session {
+            let was_judging = |judging| {
+                old_data.rush.session.as_ref().and_then(|old| old.judging()) == Some(judging)
+            };
+            if let Some(judging) = session.judging().filter(|judging| !was_judging(judging)) {
+                let judging = judging.clone();
+                let engine = session.engine().clone();
+                let sink = ctx.get_external_handle();
+                thread::spawn(move || {
+                    let judged = judging.judge(&engine);
+                    let _ = sink.submit_command(RUSH_MOVE_JUDGED, judged, Target::Auto);
+                });
+            }
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,203 @@
+use druid::widget::prelude::*;
+use druid::widget::{
+    Checkbox, Controller, CrossAxisAlignment, Flex, Label, LabelText, LineBreaking, Painter,
+    RadioGroup, Scroll, Slider, TextBox,
+};
+use druid::{Color, Lens, LocalizedString, WidgetExt, WindowDesc};
+use log::error;
+
+use super::app_data::AppData;
+use super::board_coordinates::{
+    CoordinatesPlacement, CoordinatesStyle, MAX_FONT_SCALE, MIN_FONT_SCALE,
+};
+use super::board_geometry::MAX_BORDER_WIDTH;
+use super::board_theme::{BoardTheme, HexColorFormatter};
+use super::piece_set::PieceSet;
+use crate::settings::Settings;
+
+pub fn settings_window() -> WindowDesc<AppData> {
+    WindowDesc::new(settings_builder)
+        .title(LocalizedString::new("settings-window-title"))
+        .window_size((360.0, 560.0))
+}
+
+fn settings_builder() -> impl Widget<AppData> {
+    let piece_sets = PieceSet::available_names()
+        .into_iter()
+        .map(|name| (name.clone(), name));
+
+    let content = Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("settings-piece-set")))
+        .with_spacer(4.0)
+        .with_child(RadioGroup::new(piece_sets).lens(Settings::piece_set))
+        .with_spacer(12.0)
+        .with_child(board_theme_builder().lens(Settings::board_theme))
+        .with_spacer(12.0)
+        .with_child(coordinates_builder().lens(Settings::coordinates))
+        .with_spacer(4.0)
+        .with_child(border_width_builder().lens(Settings::border_width))
+        .with_spacer(12.0)
+        .with_child(
+            path_builder("settings-engine", "settings-engine-hint").lens(Settings::engine_path),
+        )
+        .with_spacer(12.0)
+        .with_child(path_builder("settings-book", "settings-book-hint").lens(Settings::book_path))
+        .with_spacer(12.0)
+        .with_child(
+            path_builder("settings-tablebases", "settings-tablebases-hint")
+                .lens(Settings::tablebase_path),
+        )
+        .padding(10.0)
+        .lens(AppData::settings);
+
+    Scroll::new(content).vertical()
+}
+
+/// Presets of the board theme, followed by an editor for each of its colours.
+fn board_theme_builder() -> impl Widget<BoardTheme> {
+    let presets = BoardTheme::presets()
+        .into_iter()
+        .map(|theme| (preset_label(&theme.name), theme));
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("settings-board-theme")))
+        .with_spacer(4.0)
+        .with_child(RadioGroup::new(presets))
+        .with_spacer(8.0)
+        .with_child(color_editor("color-background", BoardTheme::background))
+        .with_child(color_editor("color-light-cells", BoardTheme::light_cells))
+        .with_child(color_editor("color-dark-cells", BoardTheme::dark_cells))
+        .with_child(color_editor("color-start-cell", BoardTheme::start_cell))
+        .with_child(color_editor("color-end-cell", BoardTheme::end_cell))
+        .with_child(color_editor("color-coordinates", BoardTheme::coordinates))
+        .with_child(color_editor("color-cursor", BoardTheme::cursor))
+}
+
+/// Translated name of a preset board theme.
+fn preset_label(name: &str) -> LabelText<BoardTheme> {
+    let key = match name {
+        "Classic" => "board-theme-classic",
+        "Ocean" => "board-theme-ocean",
+        "Forest" => "board-theme-forest",
+        "High contrast" => "board-theme-high-contrast",
+        "Colour-blind friendly" => "board-theme-colour-blind-friendly",
+        _ => return LabelText::from(name),
+    };
+    LocalizedString::new(key)
+        .with_placeholder(name.to_string())
+        .into()
+}
+
+/// Row editing one colour of the theme, `label_key` being the message naming the colour.
+fn color_editor(
+    label_key: &'static str,
+    lens: impl Lens<BoardTheme, Color> + Clone + 'static,
+) -> impl Widget<BoardTheme> {
+    let swatch = Painter::new(|ctx, color: &Color, _env| {
+        let rect = ctx.size().to_rect();
+        ctx.fill(rect, color);
+    })
+    .fix_size(20.0, 20.0)
+    .lens(lens.clone());
+    let text_box = TextBox::new()
+        .with_formatter(HexColorFormatter)
+        .update_data_while_editing(true)
+        .fix_width(90.0)
+        .lens(lens);
+
+    Flex::row()
+        .with_child(Label::new(LocalizedString::new(label_key)).fix_width(130.0))
+        .with_child(text_box)
+        .with_spacer(6.0)
+        .with_child(swatch)
+        .padding((0.0, 2.0))
+}
+
+/// Placement, case and size of the coordinates.
+fn coordinates_builder() -> impl Widget<CoordinatesStyle> {
+    let placements = CoordinatesPlacement::ALL.iter().map(|placement| {
+        let key = match placement {
+            CoordinatesPlacement::AllSides => "coordinates-all-sides",
+            CoordinatesPlacement::TwoSides => "coordinates-two-sides",
+            CoordinatesPlacement::InsideSquares => "coordinates-inside-squares",
+            CoordinatesPlacement::Hidden => "coordinates-hidden",
+        };
+        (LocalizedString::new(key), *placement)
+    });
+    let font_scale = Flex::row()
+        .with_child(Label::new(LocalizedString::new("coordinates-font-size")).fix_width(130.0))
+        .with_child(
+            Slider::new()
+                .with_range(MIN_FONT_SCALE, MAX_FONT_SCALE)
+                .fix_width(120.0),
+        )
+        .with_spacer(6.0)
+        .with_child(Label::dynamic(|scale: &f64, _env| {
+            format!("{:.0} %", scale * 100.0)
+        }))
+        .lens(CoordinatesStyle::font_scale);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("settings-coordinates")))
+        .with_spacer(4.0)
+        .with_child(RadioGroup::new(placements).lens(CoordinatesStyle::placement))
+        .with_spacer(4.0)
+        .with_child(
+            Checkbox::new(LocalizedString::new("coordinates-uppercase"))
+                .lens(CoordinatesStyle::uppercase),
+        )
+        .with_spacer(4.0)
+        .with_child(font_scale)
+}
+
+fn border_width_builder() -> impl Widget<f64> {
+    Flex::row()
+        .with_child(Label::new(LocalizedString::new("settings-border-width")).fix_width(130.0))
+        .with_child(
+            Slider::new()
+                .with_range(0.0, MAX_BORDER_WIDTH)
+                .fix_width(120.0),
+        )
+        .with_spacer(6.0)
+        .with_child(Label::dynamic(|width: &f64, _env| {
+            format!("{:.0} %", width * 100.0)
+        }))
+}
+
+/// Path of a file, such as the UCI engine program, with a hint telling what an empty path means.
+fn path_builder(label: &'static str, hint: &'static str) -> impl Widget<String> {
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new(label)))
+        .with_spacer(4.0)
+        .with_child(TextBox::new().fix_width(300.0))
+        .with_child(
+            Label::new(LocalizedString::new(hint))
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .fix_width(300.0),
+        )
+}
+
+/// Writes the settings to disk every time they change.
+pub struct SettingsSaver;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for SettingsSaver {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if !old_data.settings.same(&data.settings) {
+            if let Err(err) = data.settings.save() {
+                error!("Could not save settings : {}", err);
+            }
+        }
+        child.update(ctx, old_data, data, env);
+    }
+}
@@ -3,20 +3,20 @@ use druid::{theme, Affine, LinearGradient, Size, UnitPoint};
 use druid::widget::prelude::*;
 use druid::widget::{SvgData};
 
-use log::error;
+use super::piece_set::parse_svg_image;
 
 pub struct SvgImageToggleButton {
     svg_image_size : Size,
-    image_path_inactive: String,
-    image_path_active: String,
+    image_inactive: SvgData,
+    image_active: SvgData,
 }
 
 impl SvgImageToggleButton {
     pub fn new(svg_image_size: Size, image_path_inactive: String, image_path_active: String) -> Self {
         Self {
             svg_image_size,
-            image_path_active,
-            image_path_inactive,
+            image_active: parse_svg_image(&image_path_active),
+            image_inactive: parse_svg_image(&image_path_inactive),
         }
     }
 }
@@ -34,12 +34,10 @@ impl Widget<bool> for SvgImageToggleButton {
                 ctx.set_active(true);
                 ctx.request_paint();
             }
-            Event::MouseUp(_) => {
-                if ctx.is_active() {
-                    ctx.set_active(false);
-                    ctx.request_paint();
-                    *data = ! *data;
-                }
+            Event::MouseUp(_) if ctx.is_active() => {
+                ctx.set_active(false);
+                ctx.request_paint();
+                *data = ! *data;
             }
             _ => (),
         }
@@ -126,18 +124,10 @@ impl Widget<bool> for SvgImageToggleButton {
 
         let affine_matrix = Affine::scale(ratio);
 
-        let image_to_use = if *data {
-            &self.image_path_active
+        let image_svg_data = if *data {
+            &self.image_active
         } else {
-            &self.image_path_inactive
-        };
-        let image_svg_data = match image_to_use.parse::<SvgData>() {
-            Ok(svg) => svg,
-            Err(err) => {
-                error!("{}", err);
-                error!("Using an empty SVG instead of {}.", image_to_use);
-                SvgData::default()
-            }
+            &self.image_inactive
         };
 
         ctx.with_save(|ctx| {
@@ -0,0 +1,86 @@
+use druid::widget::{Controller, CrossAxisAlignment, Flex, Label, LineBreaking};
+use druid::{Data, Env, LocalizedString, UpdateCtx, Widget, WidgetExt};
+use log::error;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use super::app_data::AppData;
+use super::commands::TABLEBASES_LOADED;
+use super::localization::{localize, tablebase_verdict_text};
+use crate::chess::syzygy::Tablebases;
+
+#[derive(Data, Clone, Debug)]
+pub struct TablebaseData {
+    /// None when no directory is chosen, or when it has no table.
+    pub loaded: Option<Arc<Tablebases>>,
+    /// Why the directory of the settings could not be read.
+    pub error: Option<String>,
+}
+
+impl TablebaseData {
+    pub fn load(directory: Option<&Path>) -> Self {
+        match load_tablebases(directory) {
+            Ok(loaded) => Self {
+                loaded,
+                error: None,
+            },
+            Err(error) => Self {
+                loaded: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// Lists the Syzygy tables of the directory, if one is chosen.
+pub fn load_tablebases(directory: Option<&Path>) -> Result<Option<Arc<Tablebases>>, String> {
+    let directory = match directory {
+        Some(directory) => directory,
+        None => return Ok(None),
+    };
+    match Tablebases::open(directory) {
+        Ok(tablebases) => Ok(Some(Arc::new(tablebases))),
+        Err(err) => {
+            error!("{}", err);
+            Err(err.to_string())
+        }
+    }
+}
+
+/// The outcome of the position of the board according to the tablebases.
+pub fn tablebase_panel_builder() -> impl Widget<AppData> {
+    let verdict = Label::dynamic(|data: &AppData, env| {
+        if !data.board.board.variant.is_known_to_engines() {
+            return localize(env, "variant-unknown-to-engines");
+        }
+        tablebase_verdict_text(env, &data.tablebases, &data.board.board)
+    })
+    .with_line_break_mode(LineBreaking::WordWrap);
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("tablebase-panel-title")))
+        .with_child(verdict)
+        .controller(TablebaseLoader)
+}
+
+/// Lists the tables again whenever their directory changes in the settings.
+struct TablebaseLoader;
+
+impl<W: Widget<AppData>> Controller<AppData, W> for TablebaseLoader {
+    fn update(
+        &mut self,
+        child: &mut W,
+        ctx: &mut UpdateCtx,
+        old_data: &AppData,
+        data: &AppData,
+        env: &Env,
+    ) {
+        if old_data.settings.tablebase_path != data.settings.tablebase_path {
+            let outcome = load_tablebases(data.settings.tablebases().as_deref());
+            ctx.submit_command(TABLEBASES_LOADED.with(outcome));
+        }
+        child.update(ctx, old_data, data, env)
+    }
+}
@@ -0,0 +1,32 @@
+use druid::widget::{CrossAxisAlignment, Flex, Label, LineBreaking, RadioGroup};
+use druid::{LocalizedString, Widget, WidgetExt};
+
+use super::app_data::AppData;
+use super::chess_board::{ChessBoardData, PiecesVisibility};
+
+/// Choice of the pieces hidden on the board, for visualisation training.
+pub fn visibility_panel_builder() -> impl Widget<AppData> {
+    let choices = PiecesVisibility::ALL.iter().map(|visibility| {
+        let key = match visibility {
+            PiecesVisibility::All => "visibility-all",
+            PiecesVisibility::None => "visibility-none",
+            PiecesVisibility::HideWhite => "visibility-hide-white",
+            PiecesVisibility::HideBlack => "visibility-hide-black",
+            PiecesVisibility::HideMoved => "visibility-hide-moved",
+        };
+        (LocalizedString::new(key), *visibility)
+    });
+
+    Flex::column()
+        .cross_axis_alignment(CrossAxisAlignment::Start)
+        .with_child(Label::new(LocalizedString::new("visibility-panel-title")))
+        .with_child(
+            RadioGroup::new(choices)
+                .lens(ChessBoardData::visibility)
+                .lens(AppData::board),
+        )
+        .with_child(
+            Label::new(LocalizedString::new("visibility-peek-hint"))
+                .with_line_break_mode(LineBreaking::WordWrap),
+        )
+}
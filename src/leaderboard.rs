@@ -0,0 +1,128 @@
+use druid::Data;
+use log::error;
+
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+use crate::chess::pgn::today;
+use crate::settings::config_dir;
+
+const LEADERBOARD_FILE_NAME: &str = "rush_scores.cfg";
+
+/// Number of scores kept for each collection.
+pub const KEPT_SCORES: usize = 10;
+
+#[derive(Data, Clone, Debug, PartialEq)]
+pub struct RushScore {
+    pub collection: String,
+    pub score: u32,
+    /// Date of the rush, as `YYYY.MM.DD`.
+    pub date: String,
+}
+
+/// The best puzzle rush scores of the user, for each collection.
+#[derive(Data, Clone, Debug)]
+pub struct Leaderboard {
+    scores: Arc<Vec<RushScore>>,
+}
+
+impl Leaderboard {
+    /// Reads the scores file from the configuration directory, a missing file giving no scores.
+    pub fn load() -> Self {
+        let mut leaderboard = Leaderboard {
+            scores: Arc::new(Vec::new()),
+        };
+        let path = match config_dir() {
+            Some(dir) => dir.join(LEADERBOARD_FILE_NAME),
+            None => return leaderboard,
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => leaderboard.scores = Arc::new(Leaderboard::parse(&content)),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!("Could not read scores file {:?} : {}", path, err);
+                }
+            }
+        }
+        leaderboard
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(LEADERBOARD_FILE_NAME), self.serialize())
+    }
+
+    /// Scores are stored as `collection = score date` lines.
+    fn parse(content: &str) -> Vec<RushScore> {
+        let mut scores = Vec::new();
+        for line in content.lines() {
+            let mut parts = line.rsplitn(2, '=');
+            let value = parts.next().unwrap_or("").trim();
+            let collection = match parts.next() {
+                Some(collection) => collection.trim(),
+                None => continue,
+            };
+            let mut fields = value.split_whitespace();
+            match fields.next().map(|score| score.parse()) {
+                Some(Ok(score)) => scores.push(RushScore {
+                    collection: collection.to_string(),
+                    score,
+                    date: fields.next().unwrap_or("????.??.??").to_string(),
+                }),
+                _ => error!("Invalid score {} in scores file.", value),
+            }
+        }
+        scores
+    }
+
+    fn serialize(&self) -> String {
+        self.scores
+            .iter()
+            .map(|score| format!("{} = {} {}\n", score.collection, score.score, score.date))
+            .collect()
+    }
+
+    /// The best scores of the collection, the highest first.
+    pub fn best_scores(&self, collection: &str) -> Vec<RushScore> {
+        let mut scores: Vec<RushScore> = self
+            .scores
+            .iter()
+            .filter(|score| score.collection == collection)
+            .cloned()
+            .collect();
+        scores.sort_by_key(|score| Reverse(score.score));
+        scores.truncate(KEPT_SCORES);
+        scores
+    }
+
+    pub fn personal_best(&self, collection: &str) -> Option<u32> {
+        self.best_scores(collection)
+            .first()
+            .map(|score| score.score)
+    }
+
+    /// Adds a score made today, keeping only the best ones of the collection.
+    pub fn record(&mut self, collection: &str, score: u32) {
+        let mut collection_scores = self.best_scores(collection);
+        collection_scores.push(RushScore {
+            collection: collection.to_string(),
+            score,
+            date: today(),
+        });
+        collection_scores.sort_by_key(|score| Reverse(score.score));
+        collection_scores.truncate(KEPT_SCORES);
+
+        let mut scores: Vec<RushScore> = self
+            .scores
+            .iter()
+            .filter(|score| score.collection != collection)
+            .cloned()
+            .collect();
+        scores.append(&mut collection_scores);
+        self.scores = Arc::new(scores);
+    }
+}
@@ -1,4 +1,9 @@
+mod chess;
+mod collections;
 mod graphic;
+mod leaderboard;
+mod repetition;
+mod settings;
 use graphic::launch;
 
 use druid::PlatformError;
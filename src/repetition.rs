@@ -0,0 +1,129 @@
+use druid::Data;
+use log::error;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::settings::config_dir;
+
+const SCHEDULE_FILE_NAME: &str = "repertoire_reviews.cfg";
+
+/// Longest wait before a known position is asked again, in days.
+const MAX_INTERVAL: u32 = 180;
+
+/// When a position of a repertoire is asked again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReviewCard {
+    /// Days waited since the last answer, doubled at each right answer.
+    interval: u32,
+    /// Day of the next review, counted from 1970-01-01.
+    due: u64,
+}
+
+/// Spaced repetition of the positions of the repertoires where the user has to find a move :
+/// a position answered right waits twice as long as the previous time before being asked again,
+/// and a missed one is asked again at once. Positions never answered are due.
+#[derive(Data, Clone, Debug)]
+pub struct RepetitionSchedule {
+    cards: Arc<HashMap<String, ReviewCard>>,
+}
+
+impl RepetitionSchedule {
+    /// Reads the schedule file from the configuration directory, a missing file giving
+    /// an empty schedule.
+    pub fn load() -> Self {
+        let mut schedule = RepetitionSchedule {
+            cards: Arc::new(HashMap::new()),
+        };
+        let path = match config_dir() {
+            Some(dir) => dir.join(SCHEDULE_FILE_NAME),
+            None => return schedule,
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => schedule.cards = Arc::new(RepetitionSchedule::parse(&content)),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!(
+                        "Could not read repertoire reviews file {:?} : {}",
+                        path, err
+                    );
+                }
+            }
+        }
+        schedule
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no configuration directory"))?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(SCHEDULE_FILE_NAME), self.serialize())
+    }
+
+    /// Cards are stored as `position = interval due` lines, the position being a FEN
+    /// without its move counters.
+    fn parse(content: &str) -> HashMap<String, ReviewCard> {
+        let mut cards = HashMap::new();
+        for line in content.lines() {
+            let mut parts = line.rsplitn(2, '=');
+            let value = parts.next().unwrap_or("").trim();
+            let position = match parts.next() {
+                Some(position) => position.trim(),
+                None => continue,
+            };
+            let mut fields = value.split_whitespace().map(str::parse::<u64>);
+            match (fields.next(), fields.next()) {
+                (Some(Ok(interval)), Some(Ok(due))) => {
+                    let interval = interval.min(u64::from(MAX_INTERVAL)) as u32;
+                    cards.insert(position.to_string(), ReviewCard { interval, due });
+                }
+                _ => error!("Invalid review {} in repertoire reviews file.", value),
+            }
+        }
+        cards
+    }
+
+    fn serialize(&self) -> String {
+        let mut lines: Vec<String> = self
+            .cards
+            .iter()
+            .map(|(position, card)| format!("{} = {} {}\n", position, card.interval, card.due))
+            .collect();
+        lines.sort();
+        lines.concat()
+    }
+
+    pub fn is_due(&self, position: &str, today: u64) -> bool {
+        self.cards
+            .get(position)
+            .map(|card| card.due <= today)
+            .unwrap_or(true)
+    }
+
+    /// Schedules the next review of a position after an answer given today.
+    pub fn record(&mut self, position: &str, right: bool, today: u64) {
+        let interval = match (right, self.cards.get(position)) {
+            (false, _) => 0,
+            (true, Some(card)) if card.interval > 0 => (card.interval * 2).min(MAX_INTERVAL),
+            (true, _) => 1,
+        };
+        Arc::make_mut(&mut self.cards).insert(
+            position.to_string(),
+            ReviewCard {
+                interval,
+                due: today + u64::from(interval),
+            },
+        );
+    }
+}
+
+/// The current day, counted from 1970-01-01.
+pub fn today_number() -> u64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::default());
+    since_epoch.as_secs() / 86_400
+}
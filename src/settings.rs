@@ -0,0 +1,207 @@
+use druid::{Color, Data, Lens};
+use log::error;
+
+use crate::chess::engine::EngineKind;
+use crate::graphic::{
+    color_from_hex, color_to_hex, BoardTheme, CoordinatesPlacement, CoordinatesStyle,
+    DEFAULT_BORDER_WIDTH, MAX_BORDER_WIDTH, MAX_FONT_SCALE, MIN_FONT_SCALE,
+};
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const APPLICATION_DIRECTORY_NAME: &str = "chess-exercises-organizer";
+const SETTINGS_FILE_NAME: &str = "settings.cfg";
+
+#[derive(Lens, Data, Clone, Debug)]
+pub struct Settings {
+    pub piece_set: String,
+    pub board_theme: BoardTheme,
+    pub coordinates: CoordinatesStyle,
+    /// Width of the board border, as a ratio of the cells size.
+    pub border_width: f64,
+    /// Program of a UCI engine used for the analysis, the internal search when empty.
+    pub engine_path: String,
+    /// Polyglot opening book, none when empty.
+    pub book_path: String,
+    /// Directory of Syzygy tablebases, none when empty.
+    pub tablebase_path: String,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            piece_set: String::from("merida"),
+            board_theme: BoardTheme::new(),
+            coordinates: CoordinatesStyle::new(),
+            border_width: DEFAULT_BORDER_WIDTH,
+            engine_path: String::new(),
+            book_path: String::new(),
+            tablebase_path: String::new(),
+        }
+    }
+
+    pub fn engine(&self) -> EngineKind {
+        if self.engine_path.trim().is_empty() {
+            EngineKind::Internal
+        } else {
+            EngineKind::Uci(PathBuf::from(self.engine_path.trim()))
+        }
+    }
+
+    pub fn book(&self) -> Option<PathBuf> {
+        if self.book_path.trim().is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(self.book_path.trim()))
+        }
+    }
+
+    pub fn tablebases(&self) -> Option<PathBuf> {
+        if self.tablebase_path.trim().is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(self.tablebase_path.trim()))
+        }
+    }
+
+    /// Reads the settings file from the configuration directory.
+    /// Missing or unreadable files give the default settings.
+    pub fn load() -> Self {
+        let path = match config_dir() {
+            Some(dir) => dir.join(SETTINGS_FILE_NAME),
+            None => return Settings::new(),
+        };
+        match fs::read_to_string(&path) {
+            Ok(content) => Settings::parse(&content),
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!("Could not read settings file {:?} : {}", path, err);
+                }
+                Settings::new()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no configuration directory")
+        })?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(SETTINGS_FILE_NAME), self.serialize())
+    }
+
+    /// Settings are stored as `key = value` lines, unknown keys being ignored.
+    fn parse(content: &str) -> Self {
+        let mut settings = Settings::new();
+        for line in content.lines() {
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = match parts.next() {
+                Some(value) => value.trim(),
+                None => continue,
+            };
+            let theme = &mut settings.board_theme;
+            let coordinates = &mut settings.coordinates;
+            match key {
+                "piece_set" => settings.piece_set = value.to_string(),
+                "board_theme" => {
+                    *theme = BoardTheme::preset(value).unwrap_or_else(|| BoardTheme {
+                        name: value.to_string(),
+                        ..BoardTheme::new()
+                    })
+                }
+                "board_theme.background" => set_color(&mut theme.background, value),
+                "board_theme.light_cells" => set_color(&mut theme.light_cells, value),
+                "board_theme.dark_cells" => set_color(&mut theme.dark_cells, value),
+                "board_theme.start_cell" => set_color(&mut theme.start_cell, value),
+                "board_theme.end_cell" => set_color(&mut theme.end_cell, value),
+                "board_theme.coordinates" => set_color(&mut theme.coordinates, value),
+                "board_theme.cursor" => set_color(&mut theme.cursor, value),
+                "border_width" => match value.parse::<f64>() {
+                    Ok(width) => settings.border_width = width.clamp(0.0, MAX_BORDER_WIDTH),
+                    Err(_) => error!("Invalid border width {} in settings file.", value),
+                },
+                "coordinates.placement" => match CoordinatesPlacement::from_name(value) {
+                    Some(placement) => coordinates.placement = placement,
+                    None => error!("Invalid coordinates placement {} in settings file.", value),
+                },
+                "coordinates.uppercase" => match value.parse() {
+                    Ok(uppercase) => coordinates.uppercase = uppercase,
+                    Err(_) => error!("Invalid coordinates case {} in settings file.", value),
+                },
+                "coordinates.font_scale" => match value.parse::<f64>() {
+                    Ok(scale) => {
+                        coordinates.font_scale = scale.clamp(MIN_FONT_SCALE, MAX_FONT_SCALE)
+                    }
+                    Err(_) => error!("Invalid coordinates font scale {} in settings file.", value),
+                },
+                "engine_path" => settings.engine_path = value.to_string(),
+                "book_path" => settings.book_path = value.to_string(),
+                "tablebase_path" => settings.tablebase_path = value.to_string(),
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    fn serialize(&self) -> String {
+        let theme = &self.board_theme;
+        let mut content = String::new();
+        content.push_str(&format!("piece_set = {}\n", self.piece_set));
+        content.push_str(&format!("board_theme = {}\n", theme.name));
+        let colors = [
+            ("background", &theme.background),
+            ("light_cells", &theme.light_cells),
+            ("dark_cells", &theme.dark_cells),
+            ("start_cell", &theme.start_cell),
+            ("end_cell", &theme.end_cell),
+            ("coordinates", &theme.coordinates),
+            ("cursor", &theme.cursor),
+        ];
+        for (name, color) in colors.iter() {
+            content.push_str(&format!("board_theme.{} = {}\n", name, color_to_hex(color)));
+        }
+        content.push_str(&format!("border_width = {}\n", self.border_width));
+        let coordinates = &self.coordinates;
+        content.push_str(&format!(
+            "coordinates.placement = {}\n",
+            coordinates.placement.name()
+        ));
+        content.push_str(&format!(
+            "coordinates.uppercase = {}\n",
+            coordinates.uppercase
+        ));
+        content.push_str(&format!(
+            "coordinates.font_scale = {}\n",
+            coordinates.font_scale
+        ));
+        content.push_str(&format!("engine_path = {}\n", self.engine_path));
+        content.push_str(&format!("book_path = {}\n", self.book_path));
+        content.push_str(&format!("tablebase_path = {}\n", self.tablebase_path));
+        content
+    }
+}
+
+fn set_color(color: &mut Color, hex: &str) {
+    match color_from_hex(hex) {
+        Some(parsed) => *color = parsed,
+        None => error!("Invalid colour {} in settings file.", hex),
+    }
+}
+
+/// The directory where the application stores its settings and user data.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if let Some(dir) = env::var_os("XDG_CONFIG_HOME") {
+        PathBuf::from(dir)
+    } else if let Some(dir) = env::var_os("APPDATA") {
+        PathBuf::from(dir)
+    } else if let Some(home) = env::var_os("HOME") {
+        PathBuf::from(home).join(".config")
+    } else {
+        return None;
+    };
+    Some(base.join(APPLICATION_DIRECTORY_NAME))
+}